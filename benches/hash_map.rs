@@ -1,6 +1,22 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use scc::HashMap;
-use std::time::Instant;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct IdentityHasher(u64);
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, _: &[u8]) {
+        unreachable!();
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.0 = i;
+    }
+}
 
 fn insert_cold(c: &mut Criterion) {
     c.bench_function("HashMap: insert, cold", |b| {
@@ -44,5 +60,44 @@ fn read(c: &mut Criterion) {
     });
 }
 
-criterion_group!(hash_map, insert_cold, insert_warmed_up, read);
+fn insert_identity_hasher(c: &mut Criterion, mixing: bool) {
+    let name = if mixing {
+        "HashMap: insert 1024, identity hasher, mixed"
+    } else {
+        "HashMap: insert 1024, identity hasher"
+    };
+    c.bench_function(name, |b| {
+        b.iter_custom(|iters| {
+            let mut elapsed = Duration::default();
+            for _ in 0..iters {
+                let hashmap: HashMap<u64, u64, BuildHasherDefault<IdentityHasher>> =
+                    HashMap::with_capacity_and_hasher(1024, BuildHasherDefault::default())
+                        .with_mixing(mixing);
+                let start = Instant::now();
+                for i in 0..1024 {
+                    assert!(hashmap.insert(i, i).is_ok());
+                }
+                elapsed += start.elapsed();
+            }
+            elapsed
+        })
+    });
+}
+
+fn insert_identity(c: &mut Criterion) {
+    insert_identity_hasher(c, false);
+}
+
+fn insert_identity_mixed(c: &mut Criterion) {
+    insert_identity_hasher(c, true);
+}
+
+criterion_group!(
+    hash_map,
+    insert_cold,
+    insert_warmed_up,
+    read,
+    insert_identity,
+    insert_identity_mixed
+);
 criterion_main!(hash_map);
@@ -47,6 +47,24 @@ fn iter_with(c: &mut Criterion) {
     });
 }
 
+fn range_rev(c: &mut Criterion) {
+    c.bench_function("TreeIndex: range_rev", |b| {
+        b.iter_custom(|iters| {
+            let treeindex: TreeIndex<u64, u64> = TreeIndex::default();
+            for i in 0..iters {
+                assert!(treeindex.insert(i, i).is_ok());
+            }
+            let start = Instant::now();
+            let guard = Guard::new();
+            let range_rev = treeindex.range_rev(.., &guard);
+            for e in range_rev {
+                assert_eq!(e.0, e.1);
+            }
+            start.elapsed()
+        })
+    });
+}
+
 fn peek(c: &mut Criterion) {
     c.bench_function("TreeIndex: peek", |b| {
         b.iter_custom(|iters| {
@@ -64,5 +82,5 @@ fn peek(c: &mut Criterion) {
     });
 }
 
-criterion_group!(tree_index, insert, insert_rev, iter_with, peek);
+criterion_group!(tree_index, insert, insert_rev, iter_with, range_rev, peek);
 criterion_main!(tree_index);
@@ -22,6 +22,9 @@ pub struct Bag<T, const ARRAY_LEN: usize = DEFAULT_ARRAY_LEN> {
     /// Primary storage.
     primary_storage: Storage<T, ARRAY_LEN>,
 
+    /// Pre-allocated storage.
+    reserved_storage: Vec<Storage<T, ARRAY_LEN>>,
+
     /// Fallback storage.
     stack: Stack<Storage<T, ARRAY_LEN>>,
 }
@@ -31,6 +34,7 @@ pub struct Bag<T, const ARRAY_LEN: usize = DEFAULT_ARRAY_LEN> {
 pub struct IterMut<'b, T, const ARRAY_LEN: usize = DEFAULT_ARRAY_LEN> {
     bag: &'b mut Bag<T, ARRAY_LEN>,
     current_index: u32,
+    current_storage_index: usize,
     current_stack_entry: Option<&'b mut LinkedEntry<Storage<T, ARRAY_LEN>>>,
 }
 
@@ -82,6 +86,43 @@ impl<T, const ARRAY_LEN: usize> Bag<T, ARRAY_LEN> {
         assert!(ARRAY_LEN <= DEFAULT_ARRAY_LEN);
         Self {
             primary_storage: Storage::new(),
+            reserved_storage: Vec::new(),
+            stack: Stack::default(),
+        }
+    }
+
+    /// Creates a new [`Bag`] that can hold at least `capacity` instances without allocating
+    /// memory.
+    ///
+    /// In addition to the primary array of `ARRAY_LEN` slots, `capacity - ARRAY_LEN` rounded up
+    /// to a multiple of `ARRAY_LEN` slots are pre-allocated in arrays of `ARRAY_LEN` slots; the
+    /// pre-allocated arrays are retained until the [`Bag`] is dropped, and instances exceeding the
+    /// pre-allocated capacity are stored in dynamically allocated arrays.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the specified `ARRAY_LEN` value is larger than `usize::BITS / 2`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Bag;
+    ///
+    /// let bag: Bag<usize, 16> = Bag::with_capacity(40);
+    ///
+    /// for i in 0..48 {
+    ///     bag.push(i);
+    /// }
+    /// assert_eq!(bag.pop_all(0, |a, v| a + v), 1128);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(ARRAY_LEN <= DEFAULT_ARRAY_LEN);
+        let num_arrays = capacity.saturating_sub(ARRAY_LEN).div_ceil(ARRAY_LEN);
+        Self {
+            primary_storage: Storage::new(),
+            reserved_storage: (0..num_arrays).map(|_| Storage::new()).collect(),
             stack: Stack::default(),
         }
     }
@@ -99,21 +140,28 @@ impl<T, const ARRAY_LEN: usize> Bag<T, ARRAY_LEN> {
     /// ```
     #[inline]
     pub fn push(&self, val: T) {
-        if let Some(val) = self.primary_storage.push(val, true) {
-            self.stack.peek_with(|e| {
-                if let Some(storage) = e {
-                    if let Some(val) = storage.push(val, false) {
-                        unsafe {
-                            self.stack.push_unchecked(Storage::with_val(val));
-                        }
-                    }
-                } else {
+        let Some(mut val) = self.primary_storage.push(val, true) else {
+            return;
+        };
+        for storage in &self.reserved_storage {
+            match storage.push(val, true) {
+                Some(v) => val = v,
+                None => return,
+            }
+        }
+        self.stack.peek_with(|e| {
+            if let Some(storage) = e {
+                if let Some(val) = storage.push(val, false) {
                     unsafe {
                         self.stack.push_unchecked(Storage::with_val(val));
                     }
                 }
-            });
-        }
+            } else {
+                unsafe {
+                    self.stack.push_unchecked(Storage::with_val(val));
+                }
+            }
+        });
     }
 
     /// Pops an instance in the [`Bag`] if not empty.
@@ -145,10 +193,64 @@ impl<T, const ARRAY_LEN: usize> Bag<T, ARRAY_LEN> {
                 }
                 current = storage.next_ptr(Acquire, &guard).as_ref();
             }
+            for storage in self.reserved_storage.iter().rev() {
+                if let (Some(val), _) = storage.pop() {
+                    return Some(val);
+                }
+            }
             self.primary_storage.pop().0
         })
     }
 
+    /// Pops up to `n` instances in the [`Bag`] at once.
+    ///
+    /// Instances are removed from each internal array in a single atomic operation, and the
+    /// returned [`Vec`] may contain fewer than `n` instances if the [`Bag`] is nearly empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Bag;
+    ///
+    /// let bag: Bag<usize> = Bag::default();
+    ///
+    /// bag.push(7);
+    /// bag.push(17);
+    /// bag.push(37);
+    ///
+    /// assert_eq!(bag.pop_many(2).len(), 2);
+    /// assert_eq!(bag.pop_many(2).len(), 1);
+    /// assert!(bag.pop_many(2).is_empty());
+    /// ```
+    #[inline]
+    pub fn pop_many(&self, n: usize) -> Vec<T> {
+        let mut popped = Vec::with_capacity(n.min(ARRAY_LEN));
+        if n == 0 {
+            return popped;
+        }
+        let guard = Guard::new();
+        self.stack.peek_with(|e| {
+            let mut current = e;
+            while let Some(storage) = current {
+                if storage.pop_many(n - popped.len(), &mut popped) {
+                    storage.delete_self(Relaxed);
+                }
+                if popped.len() == n {
+                    return;
+                }
+                current = storage.next_ptr(Acquire, &guard).as_ref();
+            }
+            for storage in self.reserved_storage.iter().rev() {
+                storage.pop_many(n - popped.len(), &mut popped);
+                if popped.len() == n {
+                    return;
+                }
+            }
+            self.primary_storage.pop_many(n - popped.len(), &mut popped);
+        });
+        popped
+    }
+
     /// Pops all the entries at once, and folds them into an accumulator.
     ///
     /// # Examples
@@ -176,9 +278,63 @@ impl<T, const ARRAY_LEN: usize> Bag<T, ARRAY_LEN> {
         while let Some(storage) = popped.pop() {
             acc = storage.pop_all(acc, &mut fold, false);
         }
+        for storage in self.reserved_storage.iter().rev() {
+            acc = storage.pop_all(acc, &mut fold, true);
+        }
         self.primary_storage.pop_all(acc, &mut fold, true)
     }
 
+    /// Removes and returns all the entries in the [`Bag`].
+    ///
+    /// The returned [`Vec`] contains the entries that were in the [`Bag`] at the moment each
+    /// internal array was visited; instances pushed concurrently may or may not be included, but
+    /// no instance is lost or returned twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Bag;
+    ///
+    /// let bag: Bag<usize> = Bag::default();
+    ///
+    /// bag.push(7);
+    /// bag.push(17);
+    ///
+    /// let mut drained = bag.drain();
+    /// drained.sort_unstable();
+    /// assert_eq!(drained, vec![7, 17]);
+    /// assert!(bag.is_empty());
+    /// ```
+    #[inline]
+    pub fn drain(&self) -> Vec<T> {
+        self.pop_all(Vec::new(), |mut v, e| {
+            v.push(e);
+            v
+        })
+    }
+
+    /// Drops all the entries in the [`Bag`].
+    ///
+    /// Dynamically allocated arrays are detached from the [`Bag`] and released through EBR.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Bag;
+    ///
+    /// let bag: Bag<usize> = Bag::default();
+    ///
+    /// bag.push(7);
+    /// bag.push(17);
+    ///
+    /// bag.clear();
+    /// assert!(bag.is_empty());
+    /// ```
+    #[inline]
+    pub fn clear(&self) {
+        self.pop_all((), |(), e| drop(e));
+    }
+
     /// Returns `true` if the [`Bag`] is empty.
     ///
     /// # Examples
@@ -197,7 +353,7 @@ impl<T, const ARRAY_LEN: usize> Bag<T, ARRAY_LEN> {
     /// ```
     #[inline]
     pub fn is_empty(&self) -> bool {
-        if self.primary_storage.is_empty() {
+        if self.primary_storage.is_empty() && self.reserved_storage.iter().all(Storage::is_empty) {
             self.stack.is_empty()
         } else {
             false
@@ -228,9 +384,16 @@ impl<T, const ARRAY_LEN: usize> Bag<T, ARRAY_LEN> {
         IterMut {
             bag: self,
             current_index: 0,
+            current_storage_index: 0,
             current_stack_entry: None,
         }
     }
+
+    /// Returns `true` if any dynamically allocated array is in use.
+    #[cfg(test)]
+    pub(crate) fn has_fallback_storage(&self) -> bool {
+        !self.stack.is_empty()
+    }
 }
 
 impl<T> Default for Bag<T, DEFAULT_ARRAY_LEN> {
@@ -238,6 +401,7 @@ impl<T> Default for Bag<T, DEFAULT_ARRAY_LEN> {
     fn default() -> Self {
         Self {
             primary_storage: Storage::new(),
+            reserved_storage: Vec::new(),
             stack: Stack::default(),
         }
     }
@@ -285,8 +449,10 @@ impl<'b, T, const ARRAY_LEN: usize> Iterator for IterMut<'b, T, ARRAY_LEN> {
         while self.current_index != u32::MAX {
             let current_storage = if let Some(linked) = self.current_stack_entry.as_mut() {
                 &mut **linked
-            } else {
+            } else if self.current_storage_index == 0 {
                 &mut self.bag.primary_storage
+            } else {
+                &mut self.bag.reserved_storage[self.current_storage_index - 1]
             };
 
             let instance_bitmap =
@@ -309,6 +475,9 @@ impl<'b, T, const ARRAY_LEN: usize> Iterator for IterMut<'b, T, ARRAY_LEN> {
                     self.current_stack_entry = unsafe { entry_mut.as_mut() };
                     self.current_index = 0;
                 }
+            } else if self.current_storage_index < self.bag.reserved_storage.len() {
+                self.current_storage_index += 1;
+                self.current_index = 0;
             } else {
                 self.bag.stack.peek_with(|e| {
                     if let Some(e) = e {
@@ -481,6 +650,60 @@ impl<T, const ARRAY_LEN: usize> Storage<T, ARRAY_LEN> {
         }
     }
 
+    /// Pops up to `n` values, and returns `true` if the [`Storage`] has become empty.
+    #[allow(clippy::cast_possible_truncation)]
+    fn pop_many(&self, n: usize, popped: &mut Vec<T>) -> bool {
+        let mut metadata = self.metadata.load(Relaxed);
+        loop {
+            // Look for instantiated, yet to be owned entries.
+            let instance_bitmap = Self::instance_bitmap(metadata) as usize;
+            let owned_bitmap = Self::owned_bitmap(metadata) as usize;
+            let mut candidates = instance_bitmap & (!owned_bitmap);
+            if candidates == 0 || n == 0 {
+                return instance_bitmap == 0;
+            }
+
+            // Select the lowest `n` candidates.
+            let mut instances_to_pop = 0;
+            for _ in 0..n {
+                if candidates == 0 {
+                    break;
+                }
+                let lowest = candidates & candidates.wrapping_neg();
+                instances_to_pop |= lowest;
+                candidates &= !lowest;
+            }
+
+            match self.metadata.compare_exchange_weak(
+                metadata,
+                metadata | instances_to_pop,
+                Acquire,
+                Relaxed,
+            ) {
+                Ok(_) => {
+                    // Now the selected slots are owned by the thread.
+                    let mut remaining = instances_to_pop;
+                    while remaining != 0 {
+                        let index = remaining.trailing_zeros() as usize;
+                        remaining &= remaining - 1;
+                        popped.push(unsafe { self.storage[index].as_ptr().read() });
+                    }
+
+                    let mut empty = false;
+                    let result = self.metadata.fetch_update(Relaxed, Relaxed, |m| {
+                        debug_assert_eq!(m & instances_to_pop, instances_to_pop);
+                        let new = m & (!((instances_to_pop << ARRAY_LEN) | instances_to_pop));
+                        empty = Self::instance_bitmap(new) == 0;
+                        Some(new)
+                    });
+                    debug_assert!(result.is_ok());
+                    return empty;
+                }
+                Err(actual) => metadata = actual,
+            }
+        }
+    }
+
     /// Pops all the values, and folds them.
     #[allow(clippy::cast_possible_truncation)]
     fn pop_all<B, F: FnMut(B, T) -> B>(&self, init: B, fold: &mut F, allow_nonempty: bool) -> B {
@@ -61,3 +61,85 @@ mod ref_counted;
 pub fn suspend() -> bool {
     collector::Collector::pass_garbage()
 }
+
+/// Sets the number of [`Guard`] instances to be dropped in a thread before the thread tries to
+/// advance the global epoch.
+///
+/// A lower threshold lets retired instances be reclaimed sooner at the cost of more frequent
+/// scans of other threads, whereas a higher threshold reduces the overhead of dropping a
+/// [`Guard`]. The threshold is reduced to a quarter when the thread has retired instances, and the
+/// default is `255`.
+///
+/// # Examples
+///
+/// ```
+/// use scc::ebr::{set_advance_threshold, Guard, Shared};
+/// use std::sync::atomic::AtomicBool;
+/// use std::sync::atomic::Ordering::Relaxed;
+///
+/// set_advance_threshold(0);
+///
+/// let executed = Shared::new(AtomicBool::new(false));
+/// let executed_clone = executed.clone();
+/// let guard = Guard::new();
+/// guard.defer_execute(move || executed_clone.store(true, Relaxed));
+/// drop(guard);
+///
+/// // Every `Guard` being dropped tries to advance the global epoch.
+/// for _ in 0..4 {
+///     drop(Guard::new());
+/// }
+/// assert!(executed.load(Relaxed));
+///
+/// set_advance_threshold(255);
+/// ```
+#[inline]
+pub fn set_advance_threshold(threshold: usize) {
+    collector::Collector::set_advance_threshold(threshold);
+}
+
+/// Returns the number of threads that have a [`Guard`].
+///
+/// The returned value is a momentary snapshot that may be outdated by the time it is returned; it
+/// is mainly useful for diagnosing [`Guard`] instances that are kept alive longer than expected,
+/// preventing retired instances from being reclaimed.
+///
+/// # Examples
+///
+/// ```
+/// use scc::ebr::{pinned_thread_count, Guard};
+///
+/// let guard = Guard::new();
+/// assert!(pinned_thread_count() >= 1);
+/// drop(guard);
+/// ```
+#[inline]
+#[must_use]
+pub fn pinned_thread_count() -> usize {
+    collector::Collector::num_pinned_threads()
+}
+
+/// Asserts that the current thread does not have a [`Guard`] if debug assertions are enabled.
+///
+/// # Panics
+///
+/// Panics if debug assertions are enabled and the current thread has a [`Guard`].
+///
+/// # Examples
+///
+/// ```
+/// use scc::ebr::{debug_assert_unpinned, Guard};
+///
+/// {
+///     let guard = Guard::new();
+/// }
+/// debug_assert_unpinned();
+/// ```
+#[inline]
+#[track_caller]
+pub fn debug_assert_unpinned() {
+    debug_assert!(
+        !collector::Collector::is_pinned(),
+        "the current thread has a Guard"
+    );
+}
@@ -3,7 +3,7 @@ use crate::exit_guard::ExitGuard;
 use std::panic;
 use std::ptr::{self, NonNull};
 use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
-use std::sync::atomic::{fence, AtomicPtr, AtomicU8};
+use std::sync::atomic::{fence, AtomicPtr, AtomicU8, AtomicUsize};
 
 /// [`Collector`] is a garbage collector that reclaims thread-locally unreachable instances
 /// when they are globally unreachable.
@@ -11,7 +11,7 @@ use std::sync::atomic::{fence, AtomicPtr, AtomicU8};
 pub(super) struct Collector {
     state: AtomicU8,
     announcement: u8,
-    next_epoch_update: u8,
+    next_epoch_update: usize,
     has_garbage: bool,
     num_readers: u32,
     previous_instance_link: Option<NonNull<dyn Collectible>>,
@@ -22,8 +22,8 @@ pub(super) struct Collector {
 }
 
 impl Collector {
-    /// The cadence of an epoch update.
-    const CADENCE: u8 = u8::MAX;
+    /// The default cadence of an epoch update.
+    const CADENCE: usize = 255;
 
     /// A bit field representing a thread state where the thread does not have a
     /// [`Guard`].
@@ -84,10 +84,11 @@ impl Collector {
                 if self.has_garbage || Tag::into_tag(GLOBAL_ANCHOR.load(Relaxed)) != Tag::First {
                     self.try_scan();
                 }
+                let cadence = ADVANCE_THRESHOLD.load(Relaxed);
                 self.next_epoch_update = if self.has_garbage {
-                    Self::CADENCE / 4
+                    cadence / 4
                 } else {
-                    Self::CADENCE
+                    cadence
                 };
             } else {
                 self.next_epoch_update = self.next_epoch_update.saturating_sub(1);
@@ -112,7 +113,7 @@ impl Collector {
                 self.next_epoch_update = self
                     .next_epoch_update
                     .saturating_sub(1)
-                    .min(Self::CADENCE / 4);
+                    .min(ADVANCE_THRESHOLD.load(Relaxed) / 4);
                 self.has_garbage = true;
             }
         }
@@ -131,6 +132,51 @@ impl Collector {
         })
     }
 
+    /// Sets the number of [`Guard`] instances to be dropped before trying to advance the global
+    /// epoch.
+    #[inline]
+    pub(super) fn set_advance_threshold(threshold: usize) {
+        ADVANCE_THRESHOLD.store(threshold, Relaxed);
+    }
+
+    /// Returns `true` if the current thread has a [`Guard`].
+    #[inline]
+    pub(super) fn is_pinned() -> bool {
+        LOCAL_COLLECTOR.with(|local_collector| {
+            let collector_ptr = local_collector.load(Relaxed);
+            unsafe { collector_ptr.as_ref() }.is_some_and(|collector| collector.num_readers != 0)
+        })
+    }
+
+    /// Returns the number of threads that have a [`Guard`].
+    pub(super) fn num_pinned_threads() -> usize {
+        // Acquire the anchor lock to prevent `Collector` instances from being unlinked.
+        let mut collector_ptr = loop {
+            let lock_result = GLOBAL_ANCHOR.fetch_update(Acquire, Acquire, |p| {
+                let tag = Tag::into_tag(p);
+                if tag == Tag::First || tag == Tag::Both {
+                    None
+                } else {
+                    Some(Tag::update_tag(p, Tag::First).cast_mut())
+                }
+            });
+            if let Ok(p) = lock_result {
+                break Tag::unset_tag(p).cast_mut();
+            }
+            std::thread::yield_now();
+        };
+        let _guard = ExitGuard::new((), |()| unlock_global_anchor());
+
+        let mut num_pinned_threads = 0;
+        while let Some(collector) = unsafe { collector_ptr.as_ref() } {
+            if collector.state.load(Relaxed) & (Self::INACTIVE | Self::INVALID) == 0 {
+                num_pinned_threads += 1;
+            }
+            collector_ptr = collector.next_link;
+        }
+        num_pinned_threads
+    }
+
     /// Passes its garbage instances to other threads.
     #[inline]
     pub(super) fn pass_garbage() -> bool {
@@ -187,7 +233,7 @@ impl Collector {
         let boxed = Box::new(Collector {
             state: AtomicU8::new(Self::INACTIVE),
             announcement: 0,
-            next_epoch_update: Self::CADENCE,
+            next_epoch_update: ADVANCE_THRESHOLD.load(Relaxed),
             has_garbage: false,
             num_readers: 0,
             previous_instance_link: None,
@@ -234,24 +280,7 @@ impl Collector {
             })
             .map(|p| Tag::unset_tag(p).cast_mut());
         if let Ok(mut collector_ptr) = lock_result {
-            let _guard = ExitGuard::new(&GLOBAL_ANCHOR, |a| {
-                // Unlock the anchor.
-                loop {
-                    let result = a.fetch_update(Release, Relaxed, |p| {
-                        let tag = Tag::into_tag(p);
-                        debug_assert!(tag == Tag::First || tag == Tag::Both);
-                        let new_tag = if tag == Tag::Both {
-                            Tag::Second
-                        } else {
-                            Tag::None
-                        };
-                        Some(Tag::update_tag(p, new_tag).cast_mut())
-                    });
-                    if result.is_ok() {
-                        break;
-                    }
-                }
-            });
+            let _guard = ExitGuard::new((), |()| unlock_global_anchor());
 
             let known_epoch = self.state.load(Relaxed);
             let mut update_global_epoch = true;
@@ -347,6 +376,25 @@ impl Drop for CollectorAnchor {
     }
 }
 
+/// Unlocks `ANCHOR` that was locked for scanning `Collector` instances.
+fn unlock_global_anchor() {
+    loop {
+        let result = GLOBAL_ANCHOR.fetch_update(Release, Relaxed, |p| {
+            let tag = Tag::into_tag(p);
+            debug_assert!(tag == Tag::First || tag == Tag::Both);
+            let new_tag = if tag == Tag::Both {
+                Tag::Second
+            } else {
+                Tag::None
+            };
+            Some(Tag::update_tag(p, new_tag).cast_mut())
+        });
+        if result.is_ok() {
+            break;
+        }
+    }
+}
+
 /// Marks `ANCHOR` that there is a potentially unreachable `Collector`.
 fn mark_scan_enforced() {
     // `Tag::Second` indicates that there is a garbage `Collector`.
@@ -396,5 +444,8 @@ thread_local! {
 /// a thread and the global is considered to be an epoch change to the thread.
 static EPOCH: AtomicU8 = AtomicU8::new(0);
 
+/// The number of [`Guard`] instances to be dropped before trying to advance the global epoch.
+static ADVANCE_THRESHOLD: AtomicUsize = AtomicUsize::new(Collector::CADENCE);
+
 /// The global anchor for thread-local instances of [`Collector`].
 static GLOBAL_ANCHOR: AtomicPtr<Collector> = AtomicPtr::new(ptr::null_mut());
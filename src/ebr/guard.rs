@@ -77,6 +77,8 @@ impl Guard {
     /// It is guaranteed that the closure will be executed after every [`Guard`] at the moment when
     /// the method was invoked is dropped, however it is totally non-deterministic when exactly the
     /// closure will be executed.
+    /// The closure is executed exactly once, therefore it can be used for arbitrary cleanup
+    /// operations, e.g., closing a file, that must not happen while the object may be observed.
     ///
     /// Note that the supplied closure is stored in the heap memory, and it has to be `Sync` as it
     /// can be referred to by another thread.
@@ -94,6 +96,39 @@ impl Guard {
         self.defer(Box::new(DeferredClosure::new(f)));
     }
 
+    /// Unpins and re-pins the current thread to let the global epoch advance.
+    ///
+    /// A [`Guard`] held for a long time, e.g., throughout a scan over a large container, prevents
+    /// the global epoch from advancing, and therefore no retired instances can be reclaimed in
+    /// the meantime. Calling this method periodically bounds the reclamation latency. It takes
+    /// `&mut self` since every reference obtained through the [`Guard`] must be dropped before
+    /// the thread is unpinned. The method has no effect if the thread holds other [`Guard`]
+    /// instances.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::ebr::{AtomicShared, Guard};
+    /// use std::sync::atomic::Ordering::Acquire;
+    ///
+    /// let atomic_shared: AtomicShared<usize> = AtomicShared::new(17);
+    /// let mut guard = Guard::new();
+    ///
+    /// for _ in 0..4 {
+    ///     assert_eq!(*atomic_shared.load(Acquire, &guard).as_ref().unwrap(), 17);
+    ///     guard.repin();
+    /// }
+    /// ```
+    #[inline]
+    pub fn repin(&mut self) {
+        unsafe {
+            (*self.collector_ptr).end_guard();
+            if (*self.collector_ptr).new_guard() {
+                (*self.collector_ptr).epoch_updated();
+            }
+        }
+    }
+
     /// Creates a new [`Guard`] for dropping an instance.
     #[inline]
     pub(super) fn new_for_drop() -> Self {
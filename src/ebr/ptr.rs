@@ -188,6 +188,13 @@ impl<'g, T> Ptr<'g, T> {
 
     /// Tries to convert itself into a [`Shared`].
     ///
+    /// A [`Ptr`] does not keep the instance alive, and can therefore be used as a weak reference
+    /// for the lifetime of the [`Guard`](super::Guard) it is bound to. The memory the [`Ptr`]
+    /// points to is not reclaimed while the [`Guard`](super::Guard) is alive, so this method is
+    /// always safe to call, and it returns `None` if the last [`Shared`] has been dropped; a
+    /// [`Ptr`] cannot outlive the [`Guard`](super::Guard), and it must be reloaded from its source
+    /// under a new [`Guard`](super::Guard) to observe the instance again.
+    ///
     /// # Examples
     ///
     /// ```
@@ -4,7 +4,9 @@
 use crate::hash_table::LockedEntry;
 
 use super::ebr::{AtomicShared, Guard, Shared, Tag};
-use super::hash_table::bucket::{EntryPtr, Evictable, Locker, Reader, CACHE};
+use super::hash_table::bucket::{
+    DataBlock, EntryPtr, Evictable, Locker, Reader, BUCKET_LEN, CACHE,
+};
 use super::hash_table::bucket_array::BucketArray;
 use super::hash_table::HashTable;
 use super::wait_queue::AsyncWait;
@@ -14,6 +16,7 @@ use std::fmt::{self, Debug};
 use std::hash::{BuildHasher, Hash};
 use std::mem::replace;
 use std::ops::RangeInclusive;
+use std::panic::{RefUnwindSafe, UnwindSafe};
 use std::pin::Pin;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::{Acquire, Relaxed};
@@ -28,7 +31,14 @@ use std::sync::atomic::Ordering::{Acquire, Relaxed};
 ///
 /// [`HashCache`] and [`HashMap`](super::HashMap) share the same runtime characteristic, except
 /// that [`HashCache`] does not allow a bucket to allocate a linked list of entries when it is
-/// full, instead [`HashCache`] starts evicting the least recently used entries.
+/// full, instead [`HashCache`] starts evicting the least recently used entries, or the least
+/// frequently used entries if [`EvictionPolicy::Lfu`] is set.
+///
+/// ### Weighted capacity
+///
+/// A [`HashCache`] created with [`HashCache::with_weigher`] bounds the total weight of its entries
+/// in addition to the number of entries; whenever a new entry does not fit in the budget, the least
+/// recently used entries are evicted, starting from the bucket that receives the new entry.
 ///
 /// ### Unwind safety
 ///
@@ -42,6 +52,13 @@ where
     array: AtomicShared<BucketArray<K, Evictable<V>, CACHE>>,
     minimum_capacity: AtomicUsize,
     maximum_capacity: usize,
+    max_weight: usize,
+    total_weight: AtomicUsize,
+    weigher: Option<Weigher<K, V>>,
+    eviction_policy: EvictionPolicy,
+    num_hits: AtomicUsize,
+    num_misses: AtomicUsize,
+    num_evictions: AtomicUsize,
     build_hasher: H,
 }
 
@@ -51,6 +68,43 @@ pub const DEFAULT_MAXIMUM_CAPACITY: usize = 256;
 /// [`EvictedEntry`] is a type alias for `Option<(K, V)>`.
 pub type EvictedEntry<K, V> = Option<(K, V)>;
 
+/// [`CacheStats`] is a snapshot of the access statistics of a [`HashCache`].
+///
+/// The counters are monotonically increasing, and they are updated with relaxed memory ordering,
+/// therefore a snapshot taken while other threads are accessing the [`HashCache`] may not be
+/// consistent across the fields.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    /// The number of [`HashCache::get`] and [`HashCache::get_async`] calls that found the key.
+    pub hits: usize,
+
+    /// The number of [`HashCache::get`] and [`HashCache::get_async`] calls that did not find the
+    /// key.
+    pub misses: usize,
+
+    /// The number of entries evicted to make room for new entries.
+    pub evictions: usize,
+}
+
+/// [`EvictionPolicy`] determines which entry in a bucket is evicted when space is needed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum EvictionPolicy {
+    /// The least recently used entry is evicted.
+    #[default]
+    Lru,
+
+    /// The least frequently used entry is evicted.
+    ///
+    /// The access frequency of an entry is approximate; it is a saturating counter that is
+    /// incremented whenever the entry is put or accessed through [`HashCache::get`] or
+    /// [`HashCache::entry`], and it only decays when the entry is evicted or replaced. The least
+    /// recently used entry is evicted among the entries having the same frequency.
+    Lfu,
+}
+
+/// [`Weigher`] computes the weight of an entry.
+type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize + Send + Sync + RefUnwindSafe + UnwindSafe>;
+
 /// [`Entry`] represents a single cache entry in a [`HashCache`].
 pub enum Entry<'h, K, V, H = RandomState>
 where
@@ -109,6 +163,13 @@ where
             array: AtomicShared::null(),
             minimum_capacity: AtomicUsize::new(0),
             maximum_capacity: DEFAULT_MAXIMUM_CAPACITY,
+            max_weight: 0,
+            total_weight: AtomicUsize::new(0),
+            weigher: None,
+            eviction_policy: EvictionPolicy::Lru,
+            num_hits: AtomicUsize::new(0),
+            num_misses: AtomicUsize::new(0),
+            num_evictions: AtomicUsize::new(0),
             build_hasher,
         }
     }
@@ -159,10 +220,82 @@ where
             array,
             minimum_capacity,
             maximum_capacity,
+            max_weight: 0,
+            total_weight: AtomicUsize::new(0),
+            weigher: None,
+            eviction_policy: EvictionPolicy::Lru,
+            num_hits: AtomicUsize::new(0),
+            num_misses: AtomicUsize::new(0),
+            num_evictions: AtomicUsize::new(0),
             build_hasher,
         }
     }
 
+    /// Creates an empty [`HashCache`] bounded by the total weight of entries with the given
+    /// [`BuildHasher`].
+    ///
+    /// The weight of an entry is computed by `weigher` when the entry is put into the
+    /// [`HashCache`], and least recently used entries are evicted until the new entry fits in
+    /// `max_weight`. An entry heavier than `max_weight` is still stored, however it is marked as
+    /// the least recently used entry in its bucket, so that it is evicted before any other entry
+    /// when space is needed. The number of entries is separately bounded by `maximum_capacity`
+    /// in the same way as [`HashCache::with_capacity_and_hasher`].
+    ///
+    /// The weight of an entry is recomputed only when its value is replaced by
+    /// [`OccupiedEntry::put`], and the weight computed last is subtracted from the total weight
+    /// when the entry is removed; modifying the value in place does not change its weight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let hashcache: HashCache<u64, String, RandomState> = HashCache::with_weigher_and_hasher(
+    ///     256,
+    ///     64,
+    ///     |_, v: &String| v.len(),
+    ///     RandomState::new(),
+    /// );
+    ///
+    /// assert!(hashcache.put(1, "hello".to_string()).is_ok());
+    /// assert_eq!(hashcache.weight(), 5);
+    /// assert_eq!(hashcache.capacity_range(), 0..=256);
+    /// ```
+    #[inline]
+    pub fn with_weigher_and_hasher<W>(
+        maximum_capacity: usize,
+        max_weight: usize,
+        weigher: W,
+        build_hasher: H,
+    ) -> Self
+    where
+        W: Fn(&K, &V) -> usize + Send + Sync + RefUnwindSafe + UnwindSafe + 'static,
+    {
+        let mut hashcache = Self::with_capacity_and_hasher(0, maximum_capacity, build_hasher);
+        hashcache.max_weight = max_weight;
+        hashcache.weigher.replace(Box::new(weigher));
+        hashcache
+    }
+
+    /// Sets the [`EvictionPolicy`] of the [`HashCache`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    /// use scc::hash_cache::EvictionPolicy;
+    ///
+    /// let hashcache: HashCache<u64, u32> =
+    ///     HashCache::default().with_eviction_policy(EvictionPolicy::Lfu);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_eviction_policy(mut self, eviction_policy: EvictionPolicy) -> Self {
+        self.eviction_policy = eviction_policy;
+        self
+    }
+
     /// Gets the entry associated with the given key in the map for in-place manipulation.
     ///
     /// # Examples
@@ -255,9 +388,36 @@ where
         }
     }
 
+    /// Gets the entry associated with the given key, or puts the value constructed by the supplied
+    /// closure if the key does not exist.
+    ///
+    /// Returns `Some` along with the entry if an entry was evicted for the new key-value pair. The
+    /// closure is invoked while the bucket is locked, therefore it is invoked at most once even if
+    /// multiple threads race to fill the same missing key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert_eq!(*hashcache.get_or_insert_with(1, || 7).1.get(), 7);
+    /// assert_eq!(*hashcache.get_or_insert_with(1, || 11).1.get(), 7);
+    /// ```
+    #[inline]
+    pub fn get_or_insert_with<F: FnOnce() -> V>(
+        &self,
+        key: K,
+        constructor: F,
+    ) -> (EvictedEntry<K, V>, OccupiedEntry<'_, K, V, H>) {
+        self.entry(key).or_put_with(constructor)
+    }
+
     /// Puts a key-value pair into the [`HashCache`].
     ///
-    /// Returns `Some` if an entry was evicted for the new key-value pair.
+    /// Returns `Some` if an entry was evicted for the new key-value pair. If the [`HashCache`] is
+    /// bounded by weight, more than one entry can be evicted, and only the first one is returned.
     ///
     /// # Errors
     ///
@@ -278,26 +438,11 @@ where
         let guard = Guard::new();
         let hash = self.hash(&key);
         let result = match self.reserve_entry(&key, hash, &mut (), &guard) {
-            Ok(LockedEntry {
-                mut locker,
-                data_block_mut,
-                entry_ptr,
-                index: _,
-            }) => {
-                if entry_ptr.is_valid() {
+            Ok(mut locked_entry) => {
+                if locked_entry.entry_ptr.is_valid() {
                     return Err((key, val));
                 }
-                let evicted = locker
-                    .evict_lru_head(data_block_mut)
-                    .map(|(k, v)| (k, v.take()));
-                let entry_ptr = locker.insert_with(
-                    data_block_mut,
-                    BucketArray::<K, V, CACHE>::partial_hash(hash),
-                    || (key, Evictable::new(val)),
-                    &guard,
-                );
-                locker.update_lru_tail(data_block_mut, &entry_ptr);
-                Ok(evicted)
+                Ok(self.put_locked(&mut locked_entry, hash, key, val, &guard))
             }
             Err(()) => Err((key, val)),
         };
@@ -306,8 +451,9 @@ where
 
     /// Puts a key-value pair into the [`HashCache`].
     ///
-    /// Returns `Some` if an entry was evicted for the new key-value pair. It is an asynchronous
-    /// method returning an `impl Future` for the caller to await.
+    /// Returns `Some` if an entry was evicted for the new key-value pair. If the [`HashCache`] is
+    /// bounded by weight, more than one entry can be evicted, and only the first one is returned.
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
     ///
     /// # Errors
     ///
@@ -329,27 +475,13 @@ where
             let mut async_wait_pinned = Pin::new(&mut async_wait);
             {
                 let guard = Guard::new();
-                if let Ok(LockedEntry {
-                    mut locker,
-                    data_block_mut,
-                    entry_ptr,
-                    index: _,
-                }) = self.reserve_entry(&key, hash, &mut async_wait_pinned, &guard)
+                if let Ok(mut locked_entry) =
+                    self.reserve_entry(&key, hash, &mut async_wait_pinned, &guard)
                 {
-                    if entry_ptr.is_valid() {
+                    if locked_entry.entry_ptr.is_valid() {
                         return Err((key, val));
                     }
-                    let evicted = locker
-                        .evict_lru_head(data_block_mut)
-                        .map(|(k, v)| (k, v.take()));
-                    let entry_ptr = locker.insert_with(
-                        data_block_mut,
-                        BucketArray::<K, V, CACHE>::partial_hash(hash),
-                        || (key, Evictable::new(val)),
-                        &guard,
-                    );
-                    locker.update_lru_tail(data_block_mut, &entry_ptr);
-                    return Ok(evicted);
+                    return Ok(self.put_locked(&mut locked_entry, hash, key, val, &guard));
                 };
             }
             async_wait_pinned.await;
@@ -378,7 +510,7 @@ where
         Q: Eq + Hash + ?Sized,
     {
         let guard = Guard::new();
-        let mut locked_entry = self
+        let Some(mut locked_entry) = self
             .get_entry(
                 key,
                 self.hash(key),
@@ -386,7 +518,12 @@ where
                 self.prolonged_guard_ref(&guard),
             )
             .ok()
-            .flatten()?;
+            .flatten()
+        else {
+            self.num_misses.fetch_add(1, Relaxed);
+            return None;
+        };
+        self.num_hits.fetch_add(1, Relaxed);
         locked_entry
             .locker
             .update_lru_tail(locked_entry.data_block_mut, &locked_entry.entry_ptr);
@@ -427,6 +564,7 @@ where
                 self.prolonged_guard_ref(&Guard::new()),
             ) {
                 if let Some(mut locked_entry) = result {
+                    self.num_hits.fetch_add(1, Relaxed);
                     locked_entry
                         .locker
                         .update_lru_tail(locked_entry.data_block_mut, &locked_entry.entry_ptr);
@@ -435,6 +573,7 @@ where
                         locked_entry,
                     });
                 }
+                self.num_misses.fetch_add(1, Relaxed);
                 return None;
             }
             async_wait_pinned.await;
@@ -443,7 +582,9 @@ where
 
     /// Reads a key-value pair.
     ///
-    /// Returns `None` if the key does not exist.
+    /// Returns `None` if the key does not exist. Unlike [`HashCache::get`], reading an entry does
+    /// not mark it as the most recently used one, and the read is not counted in
+    /// [`HashCache::stats`].
     ///
     /// # Examples
     ///
@@ -880,7 +1021,13 @@ where
     /// ```
     #[inline]
     pub fn retain<F: FnMut(&K, &mut V) -> bool>(&self, mut pred: F) {
-        self.retain_entries(|k, v| pred(k, v));
+        self.retain_entries(|k, v| {
+            if pred(k, v) {
+                return true;
+            }
+            self.unweigh(v);
+            false
+        });
     }
 
     /// Retains the entries specified by the predicate.
@@ -925,6 +1072,7 @@ where
                                 while entry_ptr.next(&locker, &guard) {
                                     let (k, v) = entry_ptr.get_mut(data_block_mut, &mut locker);
                                     if !filter(k, v) {
+                                        self.unweigh(v);
                                         locker.erase(data_block_mut, &entry_ptr);
                                         removed = true;
                                     }
@@ -1063,6 +1211,184 @@ where
         self.minimum_capacity.load(Relaxed)..=self.maximum_capacity()
     }
 
+    /// Returns the total weight of the entries in the [`HashCache`].
+    ///
+    /// Returns `0` if the [`HashCache`] was not created with a weigher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::with_weigher(64, 16, |_, v| *v as usize);
+    ///
+    /// assert!(hashcache.put(1, 3).is_ok());
+    /// assert!(hashcache.put(2, 4).is_ok());
+    /// assert_eq!(hashcache.weight(), 7);
+    /// ```
+    #[inline]
+    pub fn weight(&self) -> usize {
+        self.total_weight.load(Relaxed)
+    }
+
+    /// Returns the access statistics of the [`HashCache`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert!(hashcache.put(1, 0).is_ok());
+    /// assert!(hashcache.get(&1).is_some());
+    /// assert!(hashcache.get(&2).is_none());
+    ///
+    /// let stats = hashcache.stats();
+    /// assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 1, 0));
+    /// ```
+    #[inline]
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.num_hits.load(Relaxed),
+            misses: self.num_misses.load(Relaxed),
+            evictions: self.num_evictions.load(Relaxed),
+        }
+    }
+
+    /// Puts a new entry into the locked bucket.
+    fn put_locked<'g>(
+        &self,
+        locked_entry: &mut LockedEntry<'g, K, Evictable<V>, CACHE>,
+        hash: u64,
+        key: K,
+        val: V,
+        guard: &'g Guard,
+    ) -> EvictedEntry<K, V> {
+        let mut evicted = None;
+        let weight = self.weigher.as_ref().map(|weigher| weigher(&key, &val));
+        if let Some(weight) = weight {
+            if weight <= self.max_weight {
+                evicted = self.evict_overweight(locked_entry, weight, guard);
+            }
+        }
+        let victim = if self.eviction_policy == EvictionPolicy::Lfu {
+            locked_entry
+                .locker
+                .evict_lfu_head(locked_entry.data_block_mut)
+        } else {
+            locked_entry
+                .locker
+                .evict_lru_head(locked_entry.data_block_mut)
+        };
+        if let Some((k, v)) = victim {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(index = locked_entry.index, "evicted an entry");
+            self.num_evictions.fetch_add(1, Relaxed);
+            self.unweigh(&v);
+            if evicted.is_none() {
+                evicted.replace((k, v.take()));
+            }
+        }
+
+        let entry_ptr = locked_entry.locker.insert_with(
+            locked_entry.data_block_mut,
+            BucketArray::<K, V, CACHE>::partial_hash(hash),
+            || (key, Evictable::new(val, weight.unwrap_or(0))),
+            guard,
+        );
+        if let Some(weight) = weight {
+            self.total_weight.fetch_add(weight, Relaxed);
+            if weight > self.max_weight {
+                // The entry cannot fit in the budget by itself, therefore it is evicted first.
+                locked_entry
+                    .locker
+                    .update_lru_head(locked_entry.data_block_mut, &entry_ptr);
+                locked_entry.entry_ptr = entry_ptr;
+                return evicted;
+            }
+        }
+        locked_entry
+            .locker
+            .update_lru_tail(locked_entry.data_block_mut, &entry_ptr);
+        locked_entry.entry_ptr = entry_ptr;
+        evicted
+    }
+
+    /// Evicts least recently used entries until an entry of the specified weight fits in the
+    /// budget.
+    ///
+    /// The least recently used entry in each bucket is evicted in a round-robin manner starting
+    /// from the locked bucket; other buckets are skipped if they cannot be locked immediately.
+    fn evict_overweight<'g>(
+        &self,
+        locked_entry: &mut LockedEntry<'g, K, Evictable<V>, CACHE>,
+        weight: usize,
+        guard: &'g Guard,
+    ) -> EvictedEntry<K, V> {
+        let mut evicted = None;
+        let num_buckets = self
+            .array
+            .load(Acquire, guard)
+            .as_ref()
+            .map_or(1, BucketArray::num_buckets);
+        loop {
+            let mut evicted_in_round = false;
+            for offset in 0..num_buckets {
+                if self.total_weight.load(Relaxed).saturating_add(weight) <= self.max_weight {
+                    return evicted;
+                }
+                let victim = if offset == 0 {
+                    self.evict(&mut locked_entry.locker, locked_entry.data_block_mut)
+                } else if let Some(current_array) = self.array.load(Acquire, guard).as_ref() {
+                    let index = (locked_entry.index + offset) % current_array.num_buckets();
+                    if let Ok(Some(mut locker)) =
+                        Locker::try_lock(current_array.bucket_mut(index), guard)
+                    {
+                        self.evict(&mut locker, current_array.data_block_mut(index))
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                };
+                if let Some((k, v)) = victim {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(weight, "evicted an entry to make room for a heavy entry");
+                    self.num_evictions.fetch_add(1, Relaxed);
+                    evicted_in_round = true;
+                    self.unweigh(&v);
+                    if evicted.is_none() {
+                        evicted.replace((k, v.take()));
+                    }
+                }
+            }
+            if !evicted_in_round {
+                return evicted;
+            }
+        }
+    }
+
+    /// Evicts an entry from the locked bucket according to the [`EvictionPolicy`].
+    fn evict(
+        &self,
+        locker: &mut Locker<K, Evictable<V>, CACHE>,
+        data_block: &mut DataBlock<K, Evictable<V>, BUCKET_LEN>,
+    ) -> Option<(K, Evictable<V>)> {
+        if self.eviction_policy == EvictionPolicy::Lfu {
+            locker.evict_lfu(data_block)
+        } else {
+            locker.evict_lru(data_block)
+        }
+    }
+
+    /// Subtracts the weight of a removed entry from the total weight.
+    fn unweigh(&self, val: &Evictable<V>) {
+        if self.weigher.is_some() {
+            self.total_weight.fetch_sub(val.weight(), Relaxed);
+        }
+    }
+
     /// Clears the old array asynchronously.
     async fn cleanse_old_array_async(&self, current_array: &BucketArray<K, Evictable<V>, CACHE>) {
         while current_array.has_old_array() {
@@ -1125,6 +1451,32 @@ where
     pub fn with_capacity(minimum_capacity: usize, maximum_capacity: usize) -> Self {
         Self::with_capacity_and_hasher(minimum_capacity, maximum_capacity, RandomState::new())
     }
+
+    /// Creates an empty [`HashCache`] bounded by the total weight of entries.
+    ///
+    /// See [`HashCache::with_weigher_and_hasher`] for the details of the eviction policy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, Vec<u8>> =
+    ///     HashCache::with_weigher(256, 1024, |_, v: &Vec<u8>| v.len());
+    ///
+    /// assert!(hashcache.put(1, vec![0; 1000]).is_ok());
+    /// assert!(hashcache.put(2, vec![0; 100]).unwrap().is_some());
+    /// assert!(!hashcache.contains(&1));
+    /// assert_eq!(hashcache.weight(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_weigher<W>(maximum_capacity: usize, max_weight: usize, weigher: W) -> Self
+    where
+        W: Fn(&K, &V) -> usize + Send + Sync + RefUnwindSafe + UnwindSafe + 'static,
+    {
+        Self::with_weigher_and_hasher(maximum_capacity, max_weight, weigher, RandomState::new())
+    }
 }
 
 impl<K, V, H> Default for HashCache<K, V, H>
@@ -1210,6 +1562,10 @@ where
         value.reset_link();
     }
     #[inline]
+    fn dispose(&self, entry: (K, Evictable<V>)) {
+        self.unweigh(&entry.1);
+    }
+    #[inline]
     fn bucket_array(&self) -> &AtomicShared<BucketArray<K, Evictable<V>, CACHE>> {
         &self.array
     }
@@ -1268,6 +1624,11 @@ where
 
     /// Ensures a value is in the entry by putting the result of the supplied closure if empty.
     ///
+    /// The bucket locked by [`HashCache::entry`] is not released until the returned
+    /// [`OccupiedEntry`] is dropped, therefore the closure is invoked at most once even if multiple
+    /// threads race to fill the same vacant entry; the others wait for the lock and observe the
+    /// value put by the first one.
+    ///
     /// # Examples
     ///
     /// ```
@@ -1485,6 +1846,7 @@ where
                 )
                 .unwrap_unchecked()
         };
+        self.hashcache.unweigh(&v);
         if self.locked_entry.locker.num_entries() <= 1 || self.locked_entry.locker.need_rebuild() {
             let guard = Guard::new();
             let hashcache = self.hashcache;
@@ -1578,6 +1940,33 @@ where
     /// ```
     #[inline]
     pub fn put(&mut self, val: V) -> V {
+        if let Some(weigher) = self.hashcache.weigher.as_ref() {
+            let weight = weigher(self.key(), &val);
+            let locked_entry = &mut self.locked_entry;
+            let (old_val, old_weight) = locked_entry
+                .entry_ptr
+                .get_mut(locked_entry.data_block_mut, &mut locked_entry.locker)
+                .1
+                .replace(val, weight);
+            self.hashcache.total_weight.fetch_sub(old_weight, Relaxed);
+            if weight <= self.hashcache.max_weight {
+                // The entry is hidden so that it is not evicted to make room for itself.
+                locked_entry
+                    .locker
+                    .hide(locked_entry.data_block_mut, &locked_entry.entry_ptr);
+                let guard = self.hashcache.prolonged_guard_ref(&Guard::new());
+                drop(self.hashcache.evict_overweight(locked_entry, weight, guard));
+                locked_entry
+                    .locker
+                    .reveal(locked_entry.data_block_mut, &locked_entry.entry_ptr);
+            } else {
+                locked_entry
+                    .locker
+                    .update_lru_head(locked_entry.data_block_mut, &locked_entry.entry_ptr);
+            }
+            self.hashcache.total_weight.fetch_add(weight, Relaxed);
+            return old_val;
+        }
         replace(self.get_mut(), val)
     }
 
@@ -1678,32 +2067,17 @@ where
     /// ```
     #[inline]
     pub fn put_entry(mut self, val: V) -> (EvictedEntry<K, V>, OccupiedEntry<'h, K, V, H>) {
-        let evicted = self
-            .locked_entry
-            .locker
-            .evict_lru_head(self.locked_entry.data_block_mut)
-            .map(|(k, v)| (k, v.take()));
-
-        let entry_ptr = self.locked_entry.locker.insert_with(
-            self.locked_entry.data_block_mut,
-            BucketArray::<K, V, CACHE>::partial_hash(self.hash),
-            || (self.key, Evictable::new(val)),
+        let evicted = self.hashcache.put_locked(
+            &mut self.locked_entry,
+            self.hash,
+            self.key,
+            val,
             self.hashcache.prolonged_guard_ref(&Guard::new()),
         );
-        self.locked_entry
-            .locker
-            .update_lru_tail(self.locked_entry.data_block_mut, &entry_ptr);
-
         let occupied = OccupiedEntry {
             hashcache: self.hashcache,
-            locked_entry: LockedEntry {
-                index: self.locked_entry.index,
-                data_block_mut: self.locked_entry.data_block_mut,
-                locker: self.locked_entry.locker,
-                entry_ptr,
-            },
+            locked_entry: self.locked_entry,
         };
-
         (evicted, occupied)
     }
 }
@@ -1,9 +1,10 @@
 //! [`HashIndex`] is a read-optimized concurrent and asynchronous hash map.
 
-use super::ebr::{AtomicShared, Guard, Shared};
+use super::ebr::{AtomicShared, Guard, Shared, Tag};
+use super::exit_guard::ExitGuard;
 use super::hash_table::bucket::{Bucket, EntryPtr, Locker, OPTIMISTIC};
 use super::hash_table::bucket_array::BucketArray;
-use super::hash_table::{HashTable, LockedEntry};
+use super::hash_table::{for_each_bucket_async, HashTable, LockedEntry};
 use super::wait_queue::AsyncWait;
 use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
@@ -15,7 +16,7 @@ use std::panic::UnwindSafe;
 use std::pin::Pin;
 use std::ptr;
 use std::sync::atomic::AtomicUsize;
-use std::sync::atomic::Ordering::{Acquire, Relaxed};
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
 
 /// Scalable concurrent hash index.
 ///
@@ -49,6 +50,7 @@ where
 {
     array: AtomicShared<BucketArray<K, V, OPTIMISTIC>>,
     minimum_capacity: AtomicUsize,
+    num_entries: AtomicUsize,
     build_hasher: H,
 }
 
@@ -142,13 +144,19 @@ where
         Self {
             array: AtomicShared::null(),
             minimum_capacity: AtomicUsize::new(0),
+            num_entries: AtomicUsize::new(0),
             build_hasher,
         }
     }
 
     /// Creates an empty [`HashIndex`] with the specified capacity and [`BuildHasher`].
     ///
-    /// The actual capacity is equal to or greater than the specified capacity.
+    /// The actual capacity is equal to or greater than the specified capacity, except that it is
+    /// clamped to the maximum capacity of `2^(usize::BITS - 1)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the memory required for the capacity cannot be allocated or its size overflows.
     ///
     /// # Examples
     ///
@@ -169,7 +177,7 @@ where
         } else {
             let array = unsafe {
                 Shared::new_unchecked(BucketArray::<K, V, OPTIMISTIC>::new(
-                    capacity,
+                    capacity.min(1_usize << (usize::BITS - 1)),
                     AtomicShared::null(),
                 ))
             };
@@ -182,6 +190,7 @@ where
         Self {
             array,
             minimum_capacity,
+            num_entries: AtomicUsize::new(0),
             build_hasher,
         }
     }
@@ -404,7 +413,11 @@ where
     pub fn insert(&self, key: K, val: V) -> Result<(), (K, V)> {
         let guard = Guard::new();
         let hash = self.hash(&key);
+
+        // The counter is incremented in advance so that it never falls below the actual number.
+        self.num_entries.fetch_add(1, Relaxed);
         if let Ok(Some((k, v))) = self.insert_entry(key, val, hash, &mut (), &guard) {
+            self.num_entries.fetch_sub(1, Relaxed);
             Err((k, v))
         } else {
             Ok(())
@@ -433,10 +446,15 @@ where
         loop {
             let mut async_wait = AsyncWait::default();
             let mut async_wait_pinned = Pin::new(&mut async_wait);
+            self.num_entries.fetch_add(1, Relaxed);
             match self.insert_entry(key, val, hash, &mut async_wait_pinned, &Guard::new()) {
-                Ok(Some(returned)) => return Err(returned),
+                Ok(Some(returned)) => {
+                    self.num_entries.fetch_sub(1, Relaxed);
+                    return Err(returned);
+                }
                 Ok(None) => return Ok(()),
                 Err(returned) => {
+                    self.num_entries.fetch_sub(1, Relaxed);
                     key = returned.0;
                     val = returned.1;
                 }
@@ -524,16 +542,21 @@ where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.remove_entry(
-            key,
-            self.hash(key),
-            |v: &mut V| condition(v),
-            |r| r.is_some(),
-            &mut (),
-            &Guard::new(),
-        )
-        .ok()
-        .map_or(false, |r| r)
+        let removed = self
+            .remove_entry(
+                key,
+                self.hash(key),
+                |v: &mut V| condition(v),
+                |r| r.is_some(),
+                &mut (),
+                &Guard::new(),
+            )
+            .ok()
+            .map_or(false, |r| r);
+        if removed {
+            self.num_entries.fetch_sub(1, Relaxed);
+        }
+        removed
     }
 
     /// Removes a key-value pair if the key exists and the given condition is met.
@@ -572,7 +595,12 @@ where
                 &mut async_wait_pinned,
                 &Guard::new(),
             ) {
-                Ok(r) => return r,
+                Ok(r) => {
+                    if r {
+                        self.num_entries.fetch_sub(1, Relaxed);
+                    }
+                    return r;
+                }
                 Err(c) => condition = c,
             };
             async_wait_pinned.await;
@@ -721,6 +749,77 @@ where
             .map(|(k, v)| reader(k, v))
     }
 
+    /// Returns a clone of the value associated with the key without acquiring locks.
+    ///
+    /// Returns `None` if the key does not exist. The returned value is not affected by subsequent
+    /// modifications.
+    ///
+    /// This method is not linearizable since the entry can be removed while being read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndex;
+    ///
+    /// let hashindex: HashIndex<u64, String> = HashIndex::default();
+    ///
+    /// assert!(hashindex.get_cloned(&1).is_none());
+    /// assert!(hashindex.insert(1, String::from("one")).is_ok());
+    /// assert_eq!(hashindex.get_cloned(&1).unwrap(), "one");
+    /// ```
+    #[inline]
+    pub fn get_cloned<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.peek_with(key, |_, v| v.clone())
+    }
+
+    /// Peeks a key-value pair without acquiring locks, passing references that can survive as
+    /// long as the supplied [`Guard`] is alive.
+    ///
+    /// Returns `None` if the key does not exist. Unlike [`HashIndex::peek_with`], the references
+    /// handed to the reader are bound to the lifetime of `guard`, therefore the reader can return
+    /// them to the caller. The key-value pair is not dropped until the [`Guard`] is dropped even
+    /// if it is removed from the [`HashIndex`] in the meantime.
+    ///
+    /// This method is not linearizable since the entry can be removed while being read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::ebr::Guard;
+    /// use scc::HashIndex;
+    ///
+    /// let hashindex: HashIndex<u64, String> = HashIndex::default();
+    ///
+    /// assert!(hashindex.insert(1, String::from("one")).is_ok());
+    ///
+    /// let guard = Guard::new();
+    /// let (key_ref, value_ref) = hashindex.peek_with_guard(&1, &guard, |k, v| (k, v)).unwrap();
+    /// assert!(hashindex.remove(&1));
+    ///
+    /// assert_eq!(*key_ref, 1);
+    /// assert_eq!(value_ref, "one");
+    /// ```
+    #[inline]
+    pub fn peek_with_guard<'g, Q, R, F: FnOnce(&'g K, &'g V) -> R>(
+        &self,
+        key: &Q,
+        guard: &'g Guard,
+        reader: F,
+    ) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.read_entry(key, self.hash(key), &mut (), guard)
+            .ok()
+            .flatten()
+            .map(|(k, v)| reader(k, v))
+    }
+
     /// Returns `true` if the [`HashIndex`] contains a value for the specified key.
     ///
     /// # Examples
@@ -740,14 +839,50 @@ where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.peek_with(key, |_, _| ()).is_some()
+        self.contains_key(key, &Guard::new())
+    }
+
+    /// Returns `true` if the [`HashIndex`] contains a value for the specified key without
+    /// acquiring locks.
+    ///
+    /// Unlike [`HashIndex::contains`], it uses the supplied [`Guard`], which makes repeated
+    /// membership tests under the same [`Guard`] cheaper. The value is never read, and removed
+    /// entries are not taken into account.
+    ///
+    /// This method is not linearizable since the entry can be removed while being read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::ebr::Guard;
+    /// use scc::HashIndex;
+    ///
+    /// let hashindex: HashIndex<u64, u32> = HashIndex::default();
+    /// let guard = Guard::new();
+    ///
+    /// assert!(!hashindex.contains_key(&1, &guard));
+    /// assert!(hashindex.insert(1, 0).is_ok());
+    /// assert!(hashindex.contains_key(&1, &guard));
+    /// ```
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q, guard: &Guard) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        matches!(
+            self.read_entry(key, self.hash(key), &mut (), guard),
+            Ok(Some(_))
+        )
     }
 
     /// Retains the entries specified by the predicate.
     ///
     /// Entries that have existed since the invocation of the method are guaranteed to be visited
-    /// if they are not removed, however the same entry can be visited more than once if the
-    /// [`HashIndex`] gets resized by another thread.
+    /// exactly once if they are not removed, even if the [`HashIndex`] gets resized by another
+    /// thread, therefore the predicate is invoked at most once for each entry. Removed entries
+    /// are only marked as such, and dropped later by [`ebr`](crate::ebr), so readers are never
+    /// blocked.
     ///
     /// # Examples
     ///
@@ -768,14 +903,24 @@ where
     /// ```
     #[inline]
     pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut pred: F) {
-        self.retain_entries(|k, v| pred(k, v));
+        let mut num_removed = 0;
+        self.retain_entries(|k, v| {
+            if pred(k, v) {
+                return true;
+            }
+            num_removed += 1;
+            false
+        });
+        self.num_entries.fetch_sub(num_removed, Relaxed);
     }
 
     /// Retains the entries specified by the predicate.
     ///
     /// Entries that have existed since the invocation of the method are guaranteed to be visited
-    /// if they are not removed, however the same entry can be visited more than once if the
-    /// [`HashIndex`] gets resized by another thread.
+    /// exactly once if they are not removed, even if the [`HashIndex`] gets resized by another
+    /// thread, therefore the predicate is invoked at most once for each entry. Removed entries
+    /// are only marked as such, and dropped later by [`ebr`](crate::ebr), so readers are never
+    /// blocked.
     ///
     /// It is an asynchronous method returning an `impl Future` for the caller to await.
     ///
@@ -791,69 +936,89 @@ where
     /// ```
     #[inline]
     pub async fn retain_async<F: FnMut(&K, &V) -> bool>(&self, mut pred: F) {
-        let mut removed = false;
-        let mut current_array_holder = self.array.get_shared(Acquire, &Guard::new());
-        while let Some(current_array) = current_array_holder.take() {
-            self.cleanse_old_array_async(&current_array).await;
-            for index in 0..current_array.num_buckets() {
-                loop {
-                    let mut async_wait = AsyncWait::default();
-                    let mut async_wait_pinned = Pin::new(&mut async_wait);
-                    {
-                        let guard = Guard::new();
-                        let bucket = current_array.bucket_mut(index);
-                        if let Ok(locker) =
-                            Locker::try_lock_or_wait(bucket, &mut async_wait_pinned, &guard)
-                        {
-                            if let Some(mut locker) = locker {
-                                let data_block_mut = current_array.data_block_mut(index);
-                                let mut entry_ptr = EntryPtr::new(&guard);
-                                while entry_ptr.next(&locker, &guard) {
-                                    let (k, v) = entry_ptr.get(data_block_mut);
-                                    if !pred(k, v) {
-                                        locker.erase(data_block_mut, &entry_ptr);
-                                        removed = true;
-                                    }
-                                }
-                            }
-                            break;
-                        };
+        let mut num_removed = 0;
+        for_each_bucket_async(
+            self,
+            |current_array, index, skip_below, async_wait, guard| {
+                let bucket = current_array.bucket_mut(index);
+                let Some(mut locker) = Locker::try_lock_or_wait(bucket, async_wait, guard)? else {
+                    // The `Bucket` has been killed.
+                    return Ok(None);
+                };
+                let data_block_mut = current_array.data_block_mut(index);
+                let mut entry_ptr = EntryPtr::new(guard);
+                while entry_ptr.next(&locker, guard) {
+                    let (k, v) = entry_ptr.get(data_block_mut);
+                    if skip_below != 0 && self.hash(k) < skip_below {
+                        continue;
+                    }
+                    if !pred(k, v) {
+                        locker.erase(data_block_mut, &entry_ptr);
+                        num_removed += 1;
                     }
-                    async_wait_pinned.await;
-                }
-            }
-
-            if let Some(new_current_array) = self.array.get_shared(Acquire, &Guard::new()) {
-                if new_current_array.as_ptr() == current_array.as_ptr() {
-                    break;
                 }
-                current_array_holder.replace(new_current_array);
-                continue;
-            }
-            break;
-        }
+                Ok(Some(false))
+            },
+        )
+        .await;
 
-        if removed {
+        if num_removed != 0 {
+            self.num_entries.fetch_sub(num_removed, Relaxed);
             self.try_resize(0, &Guard::new());
         }
     }
 
     /// Clears the [`HashIndex`] by removing all key-value pairs.
     ///
+    /// The bucket array is detached from the [`HashIndex`] at once, and a new one is allocated on
+    /// the next insertion. Readers can keep reading the entries they have obtained before the
+    /// [`HashIndex`] was cleared as long as they hold the [`Guard`].
+    ///
     /// # Examples
     ///
     /// ```
+    /// use scc::ebr::Guard;
     /// use scc::HashIndex;
     ///
     /// let hashindex: HashIndex<u64, u32> = HashIndex::default();
     ///
     /// assert!(hashindex.insert(1, 0).is_ok());
+    ///
+    /// let guard = Guard::new();
+    /// let val = hashindex.peek(&1, &guard).unwrap();
     /// hashindex.clear();
     ///
     /// assert!(!hashindex.contains(&1));
+    /// assert_eq!(*val, 0);
     /// ```
     pub fn clear(&self) {
-        self.retain(|_, _| false);
+        let guard = Guard::new();
+        let mut current_array_ptr = self.array.load(Acquire, &guard);
+        while let Some(current_array) = current_array_ptr.as_ref() {
+            self.clear_old_array(current_array, &guard);
+            match self.array.compare_exchange(
+                current_array_ptr.without_tag(),
+                (None, Tag::None),
+                AcqRel,
+                Acquire,
+                &guard,
+            ) {
+                Ok((detached_array, _)) => {
+                    let mut num_removed = 0;
+                    for index in 0..current_array.num_buckets() {
+                        let bucket = current_array.bucket_mut(index);
+                        if let Some(mut locker) = Locker::lock(bucket, &guard) {
+                            num_removed += locker.num_entries();
+                            locker.kill(&guard);
+                        }
+                    }
+                    self.num_entries.fetch_sub(num_removed, Relaxed);
+                    drop(detached_array);
+                    break;
+                }
+                Err((_, actual)) => current_array_ptr = actual,
+            }
+        }
     }
 
     /// Clears the [`HashIndex`] by removing all key-value pairs.
@@ -871,14 +1036,74 @@ where
     /// let future_retain = hashindex.clear_async();
     /// ```
     pub async fn clear_async(&self) {
-        self.retain_async(|_, _| false).await;
+        let mut current_array_holder = self.array.get_shared(Acquire, &Guard::new());
+        while let Some(current_array) = current_array_holder.take() {
+            self.cleanse_old_array_async(&current_array).await;
+            let detached = {
+                let guard = Guard::new();
+                let current_array_ptr = current_array.get_guarded_ptr(&guard);
+                match self.array.compare_exchange(
+                    current_array_ptr,
+                    (None, Tag::None),
+                    AcqRel,
+                    Acquire,
+                    &guard,
+                ) {
+                    Ok(_) => true,
+                    Err((_, actual)) => {
+                        current_array_holder = actual.get_shared();
+                        false
+                    }
+                }
+            };
+            if !detached {
+                continue;
+            }
+
+            // If the future is dropped, the remaining buckets of the detached array are killed
+            // synchronously so that the counter does not retain their entries.
+            let mut exit_guard = ExitGuard::new(0, |next_index| {
+                let guard = Guard::new();
+                let mut num_removed = 0;
+                for index in next_index..current_array.num_buckets() {
+                    let bucket = current_array.bucket_mut(index);
+                    if let Some(mut locker) = Locker::lock(bucket, &guard) {
+                        num_removed += locker.num_entries();
+                        locker.kill(&guard);
+                    }
+                }
+                self.num_entries.fetch_sub(num_removed, Relaxed);
+            });
+            for index in 0..current_array.num_buckets() {
+                loop {
+                    let mut async_wait = AsyncWait::default();
+                    let mut async_wait_pinned = Pin::new(&mut async_wait);
+                    {
+                        let guard = Guard::new();
+                        let bucket = current_array.bucket_mut(index);
+                        if let Ok(locker) =
+                            Locker::try_lock_or_wait(bucket, &mut async_wait_pinned, &guard)
+                        {
+                            if let Some(mut locker) = locker {
+                                self.num_entries.fetch_sub(locker.num_entries(), Relaxed);
+                                locker.kill(&guard);
+                            }
+                            break;
+                        };
+                    }
+                    async_wait_pinned.await;
+                }
+                *exit_guard = index + 1;
+            }
+            break;
+        }
     }
 
     /// Returns the number of entries in the [`HashIndex`].
     ///
-    /// It reads the entire metadata area of the bucket array to calculate the number of valid
-    /// entries, making its time complexity `O(N)`. Furthermore, it may overcount entries if an old
-    /// bucket array has yet to be dropped.
+    /// It reads a counter that is updated on insertion and removal, making its time complexity
+    /// `O(1)`. The counter is exact when no other thread is modifying the [`HashIndex`], otherwise
+    /// it may momentarily overcount entries being inserted or removed.
     ///
     /// # Examples
     ///
@@ -892,7 +1117,7 @@ where
     /// ```
     #[inline]
     pub fn len(&self) -> usize {
-        self.num_entries(&Guard::new())
+        self.num_entries.load(Relaxed)
     }
 
     /// Returns `true` if the [`HashIndex`] is empty.
@@ -1093,7 +1318,12 @@ where
 
     /// Creates an empty [`HashIndex`] with the specified capacity.
     ///
-    /// The actual capacity is equal to or greater than the specified capacity.
+    /// The actual capacity is equal to or greater than the specified capacity, except that it is
+    /// clamped to the maximum capacity of `2^(usize::BITS - 1)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the memory required for the capacity cannot be allocated or its size overflows.
     ///
     /// # Examples
     ///
@@ -1168,6 +1398,46 @@ where
     }
 }
 
+impl<K, V, H> IntoIterator for HashIndex<K, V, H>
+where
+    K: 'static + Clone + Eq + Hash,
+    V: 'static + Clone,
+    H: BuildHasher,
+{
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    /// Consumes the [`HashIndex`], returning all its entries.
+    ///
+    /// Entries are cloned rather than moved out since references returned by
+    /// [`HashIndex::peek`] are bound to a [`Guard`], and may outlive the [`HashIndex`]; the
+    /// original entries are dropped when they become globally unreachable. Removed entries are
+    /// not yielded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndex;
+    ///
+    /// let hashindex: HashIndex<u64, u32> = HashIndex::default();
+    ///
+    /// assert!(hashindex.insert(1, 0).is_ok());
+    /// assert!(hashindex.insert(2, 1).is_ok());
+    /// assert!(hashindex.remove(&2));
+    ///
+    /// let entries: Vec<(u64, u32)> = hashindex.into_iter().collect();
+    /// assert_eq!(entries, vec![(1, 0)]);
+    /// ```
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        let guard = Guard::new();
+        self.iter(&guard)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
 impl<K, V, H> PartialEq for HashIndex<K, V, H>
 where
     K: 'static + Clone + Eq + Hash,
@@ -1409,6 +1679,7 @@ where
             self.locked_entry.data_block_mut,
             &self.locked_entry.entry_ptr,
         );
+        self.hashindex.num_entries.fetch_sub(1, Relaxed);
         if self.locked_entry.locker.num_entries() <= 1 || self.locked_entry.locker.need_rebuild() {
             let guard = Guard::new();
             let hashindex = self.hashindex;
@@ -1675,6 +1946,7 @@ where
     #[inline]
     pub fn insert_entry(mut self, val: V) -> OccupiedEntry<'h, K, V, H> {
         let guard = Guard::new();
+        self.hashindex.num_entries.fetch_add(1, Relaxed);
         let entry_ptr = self.locked_entry.locker.insert_with(
             self.locked_entry.data_block_mut,
             BucketArray::<K, V, OPTIMISTIC>::partial_hash(self.hash),
@@ -3,8 +3,10 @@
 use super::ebr::{AtomicShared, Guard, Shared, Tag};
 use super::hash_table::bucket::{EntryPtr, Locker, Reader, SEQUENTIAL};
 use super::hash_table::bucket_array::BucketArray;
-use super::hash_table::{HashTable, LockedEntry};
+use super::hash_table::{for_each_bucket_async, mix, HashTable, LockedEntry, ResizePolicy};
 use super::wait_queue::AsyncWait;
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
 use std::fmt::{self, Debug};
@@ -14,6 +16,7 @@ use std::ops::{Deref, RangeInclusive};
 use std::pin::Pin;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::{Acquire, Relaxed};
+use std::thread;
 
 /// Scalable concurrent hash map.
 ///
@@ -39,6 +42,12 @@ use std::sync::atomic::Ordering::{Acquire, Relaxed};
 /// * The number of entries managed by a single bucket without a linked list: 32.
 /// * The expected maximum linked list length when a resize is triggered: log(capacity) / 8.
 ///
+/// ## Stored keys
+///
+/// Methods handing out a key, e.g., [`HashMap::read`] and [`HashMap::remove`], hand out the key
+/// stored in the [`HashMap`] rather than the supplied one; the two may differ if the [`Eq`]
+/// implementation of the key type ignores some of its fields.
+///
 /// ## Locking behavior
 ///
 /// ### Bucket access
@@ -70,6 +79,8 @@ where
 {
     array: AtomicShared<BucketArray<K, V, SEQUENTIAL>>,
     minimum_capacity: AtomicUsize,
+    mix_hash: bool,
+    resize_policy: ResizePolicy,
     build_hasher: H,
 }
 
@@ -108,6 +119,33 @@ where
     locked_entry: LockedEntry<'h, K, V, SEQUENTIAL>,
 }
 
+/// [`EntryRef`] represents a single entry in a [`HashMap`] looked up by a borrowed key.
+pub enum EntryRef<'h, 'q, K, Q, V, H = RandomState>
+where
+    K: Eq + Hash,
+    Q: ?Sized,
+    H: BuildHasher,
+{
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'h, K, V, H>),
+
+    /// A vacant entry.
+    Vacant(VacantEntryRef<'h, 'q, K, Q, V, H>),
+}
+
+/// [`VacantEntryRef`] is a view into a vacant entry in a [`HashMap`] holding a borrowed key.
+pub struct VacantEntryRef<'h, 'q, K, Q, V, H = RandomState>
+where
+    K: Eq + Hash,
+    Q: ?Sized,
+    H: BuildHasher,
+{
+    hashmap: &'h HashMap<K, V, H>,
+    key: &'q Q,
+    hash: u64,
+    locked_entry: LockedEntry<'h, K, V, SEQUENTIAL>,
+}
+
 /// [`Reserve`] keeps the capacity of the associated [`HashMap`] higher than a certain level.
 ///
 /// The [`HashMap`] does not shrink the capacity below the reserved capacity.
@@ -140,13 +178,20 @@ where
         Self {
             array: AtomicShared::null(),
             minimum_capacity: AtomicUsize::new(0),
+            mix_hash: false,
+            resize_policy: ResizePolicy::DEFAULT,
             build_hasher,
         }
     }
 
     /// Creates an empty [`HashMap`] with the specified capacity and [`BuildHasher`].
     ///
-    /// The actual capacity is equal to or greater than the specified capacity.
+    /// The actual capacity is equal to or greater than the specified capacity, except that it is
+    /// clamped to the maximum capacity of `2^(usize::BITS - 1)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the memory required for the capacity cannot be allocated or its size overflows.
     ///
     /// # Examples
     ///
@@ -167,7 +212,7 @@ where
         } else {
             let array = unsafe {
                 Shared::new_unchecked(BucketArray::<K, V, SEQUENTIAL>::new(
-                    capacity,
+                    capacity.min(1_usize << (usize::BITS - 1)),
                     AtomicShared::null(),
                 ))
             };
@@ -180,10 +225,167 @@ where
         Self {
             array,
             minimum_capacity,
+            mix_hash: false,
+            resize_policy: ResizePolicy::DEFAULT,
             build_hasher,
         }
     }
 
+    /// Enables or disables mixing the bits of hash values computed by the [`BuildHasher`].
+    ///
+    /// The [`HashMap`] uses the upper bits of a hash value to locate the bucket and the lower bits
+    /// to filter entries in the bucket, therefore a weak hasher, e.g., an identity hasher for
+    /// integer keys, leads to severe clustering. Enabling mixing applies a strong bit-mixing
+    /// finalizer to every hash value at the cost of a few extra instructions per operation.
+    ///
+    /// Existing entries are relocated if the option is changed on a non-empty [`HashMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use std::hash::{BuildHasherDefault, Hasher};
+    ///
+    /// #[derive(Default)]
+    /// struct IdentityHasher(u64);
+    /// impl Hasher for IdentityHasher {
+    ///     fn finish(&self) -> u64 { self.0 }
+    ///     fn write(&mut self, bytes: &[u8]) {
+    ///         for b in bytes {
+    ///             self.0 = (self.0 << 8) | u64::from(*b);
+    ///         }
+    ///     }
+    ///     fn write_u64(&mut self, i: u64) { self.0 = i; }
+    /// }
+    ///
+    /// let hashmap: HashMap<u64, u32, BuildHasherDefault<IdentityHasher>> =
+    ///     HashMap::with_capacity_and_hasher(1024, BuildHasherDefault::default()).with_mixing(true);
+    ///
+    /// assert_ne!(hashmap.bucket_index(&1), hashmap.bucket_index(&2));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_mixing(mut self, enabled: bool) -> Self {
+        if self.mix_hash != enabled {
+            let mut entries = Vec::new();
+            while let Some(entry) = self.first_entry() {
+                entries.push(entry.remove_entry());
+            }
+            self.mix_hash = enabled;
+            for (k, v) in entries {
+                let _result = self.insert(k, v);
+            }
+        }
+        self
+    }
+
+    /// Sets the load factor thresholds for resizing the [`HashMap`].
+    ///
+    /// The [`HashMap`] grows if the estimated load factor reaches `grow_threshold`, and shrinks if
+    /// it drops to `shrink_threshold`; the default values are `7/8` and `1/16`. The load factor is
+    /// estimated by sampling a few buckets, therefore the thresholds are not precisely observed.
+    ///
+    /// Growing the [`HashMap`] at least doubles its capacity, which leaves the load factor at about
+    /// half of `grow_threshold`, therefore `shrink_threshold` must be less than that to prevent
+    /// the [`HashMap`] from repeatedly growing and shrinking.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `0 < shrink_threshold < grow_threshold / 2` or `grow_threshold < 1` does not hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default().with_load_factor_thresholds(0.5, 0.125);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_load_factor_thresholds(
+        mut self,
+        grow_threshold: f32,
+        shrink_threshold: f32,
+    ) -> Self {
+        self.resize_policy = self
+            .resize_policy
+            .with_thresholds(grow_threshold, shrink_threshold);
+        self
+    }
+
+    /// Sets the maximum factor by which the capacity of the [`HashMap`] can grow at once.
+    ///
+    /// The default value is `32`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_growth_factor` is not a power of two greater than `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default().with_max_growth_factor(4);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_max_growth_factor(mut self, max_growth_factor: usize) -> Self {
+        self.resize_policy = self.resize_policy.with_max_growth_factor(max_growth_factor);
+        self
+    }
+
+    /// Sets the maximum number of linked buckets that a bucket can have before the [`HashMap`] is
+    /// forced to grow.
+    ///
+    /// A bucket stores up to `32` entries, and entries that do not fit in the bucket are stored in
+    /// a linked list of overflow buckets of `8` entries each. If an insertion needs more overflow
+    /// buckets than `max_link_depth`, the [`HashMap`] grows regardless of its load factor provided
+    /// that growing distributes the entries in the bucket over different buckets; this keeps the
+    /// search cost bounded when the hash values are not uniformly distributed, at the expense of
+    /// memory. The linked list is not limited by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default().with_max_link_depth(2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_max_link_depth(mut self, max_link_depth: usize) -> Self {
+        self.resize_policy = self.resize_policy.with_max_link_depth(max_link_depth);
+        self
+    }
+
+    /// Disables resizing the [`HashMap`].
+    ///
+    /// Once the bucket array is allocated, either on construction with a non-zero capacity or on
+    /// the first insertion, it is never reallocated, and therefore no insertion or removal incurs
+    /// the latency of resizing. Entries that do not fit in a bucket are stored in linked buckets,
+    /// making lookups slower as the number of entries exceeds the capacity. [`HashMap::reserve`]
+    /// and [`HashMap::with_max_link_depth`] have no effect on the capacity of the [`HashMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::with_capacity(64).with_resize_disabled();
+    ///
+    /// for k in 0..256 {
+    ///     assert!(hashmap.insert(k, 0).is_ok());
+    /// }
+    /// assert_eq!(hashmap.capacity(), 64);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_resize_disabled(mut self) -> Self {
+        self.resize_policy = self.resize_policy.with_resize_disabled();
+        self
+    }
+
     /// Temporarily increases the minimum capacity of the [`HashMap`].
     ///
     /// A [`Reserve`] is returned if the [`HashMap`] could increase the minimum capacity while the
@@ -318,6 +520,142 @@ where
         }
     }
 
+    /// Gets the entry associated with the given key, or inserts the value constructed by the
+    /// supplied fallible closure if the key does not exist.
+    ///
+    /// The closure is invoked while the bucket is locked, and it is never invoked if the key
+    /// exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by the closure without modifying the [`HashMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.get_or_try_insert_with(1, || "x".parse::<u32>()).is_err());
+    /// assert!(!hashmap.contains(&1));
+    /// assert_eq!(*hashmap.get_or_try_insert_with(1, || "7".parse::<u32>()).unwrap().get(), 7);
+    /// assert_eq!(*hashmap.get_or_try_insert_with(1, || "x".parse::<u32>()).unwrap().get(), 7);
+    /// ```
+    #[inline]
+    pub fn get_or_try_insert_with<E, F: FnOnce() -> Result<V, E>>(
+        &self,
+        key: K,
+        constructor: F,
+    ) -> Result<OccupiedEntry<'_, K, V, H>, E> {
+        match self.entry(key) {
+            Entry::Occupied(o) => Ok(o),
+            Entry::Vacant(v) => Ok(v.insert_entry(constructor()?)),
+        }
+    }
+
+    /// Gets the entry associated with the borrowed key in the map for in-place manipulation.
+    ///
+    /// The owned key is only constructed from the borrowed key through [`From`] when a value is
+    /// inserted into a vacant entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<String, u32> = HashMap::default();
+    ///
+    /// for word in "the quick fox jumps over the lazy dog".split(' ') {
+    ///     hashmap.entry_ref(word).and_modify(|counter| *counter += 1).or_insert(1);
+    /// }
+    ///
+    /// assert_eq!(hashmap.read("the", |_, v| *v), Some(2));
+    /// assert_eq!(hashmap.read("fox", |_, v| *v), Some(1));
+    /// ```
+    #[inline]
+    pub fn entry_ref<'q, Q>(&self, key: &'q Q) -> EntryRef<'_, 'q, K, Q, V, H>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let guard = Guard::new();
+        let hash = self.hash(key);
+        let locked_entry = unsafe {
+            self.reserve_entry(key, hash, &mut (), self.prolonged_guard_ref(&guard))
+                .ok()
+                .unwrap_unchecked()
+        };
+        if locked_entry.entry_ptr.is_valid() {
+            EntryRef::Occupied(OccupiedEntry {
+                hashmap: self,
+                locked_entry,
+            })
+        } else {
+            EntryRef::Vacant(VacantEntryRef {
+                hashmap: self,
+                key,
+                hash,
+                locked_entry,
+            })
+        }
+    }
+
+    /// Reads an arbitrary entry.
+    ///
+    /// Buckets are searched from a start position derived from the current thread, so that
+    /// threads picking entries to process do not contend on the same bucket; the position does not
+    /// depend on the quality of the [`BuildHasher`]. The reader is invoked
+    /// with the first entry found while the bucket is shared-locked.
+    ///
+    /// Returns `None` if the [`HashMap`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.read_any(|k, _| *k).is_none());
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    ///
+    /// let key = hashmap.read_any(|k, _| *k).unwrap();
+    /// assert!(key == 1 || key == 2);
+    /// ```
+    #[inline]
+    pub fn read_any<R, F: FnOnce(&K, &V) -> R>(&self, reader: F) -> Option<R> {
+        let guard = Guard::new();
+        // The hash value is mixed since the `BuildHasher` may not spread the bits evenly.
+        let seed = mix(self.hasher().hash_one(thread::current().id()));
+        let mut current_array_ptr = self.array.load(Acquire, &guard);
+        'array: while let Some(current_array) = current_array_ptr.as_ref() {
+            self.clear_old_array(current_array, &guard);
+            let num_buckets = current_array.num_buckets();
+            let start_index = current_array.calculate_bucket_index(seed);
+            for offset in 0..num_buckets {
+                let index = (start_index + offset) % num_buckets;
+                if let Some(locker) = Reader::lock(current_array.bucket(index), &guard) {
+                    let mut entry_ptr = EntryPtr::new(&guard);
+                    if entry_ptr.next(*locker, &guard) {
+                        let (k, v) = entry_ptr.get(current_array.data_block(index));
+                        return Some(reader(k, v));
+                    }
+                } else {
+                    // The `Bucket` is either empty or killed.
+                    let new_current_array_ptr = self.array.load(Acquire, &guard);
+                    if current_array_ptr.without_tag() != new_current_array_ptr.without_tag() {
+                        current_array_ptr = new_current_array_ptr;
+                        continue 'array;
+                    }
+                }
+            }
+            break;
+        }
+        None
+    }
+
     /// Gets the first occupied entry for in-place manipulation.
     ///
     /// The returned [`OccupiedEntry`] in combination with [`OccupiedEntry::next`] or
@@ -439,6 +777,110 @@ where
         }
     }
 
+    /// Inserts a key-value pair into the [`HashMap`], or reads the existing value.
+    ///
+    /// If the key exists, `on_conflict` is invoked with the existing value while the bucket is
+    /// locked, and the supplied key-value pair is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns the result of `on_conflict` if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.try_insert(1, 0, |v| *v).is_ok());
+    /// assert_eq!(hashmap.try_insert(1, 1, |v| *v).unwrap_err(), 0);
+    /// ```
+    #[inline]
+    pub fn try_insert<R, F: FnOnce(&V) -> R>(
+        &self,
+        key: K,
+        val: V,
+        on_conflict: F,
+    ) -> Result<(), R> {
+        let guard = Guard::new();
+        let hash = self.hash(&key);
+        let LockedEntry {
+            mut locker,
+            data_block_mut,
+            entry_ptr,
+            index: _,
+        } = unsafe {
+            self.reserve_entry(&key, hash, &mut (), &guard)
+                .ok()
+                .unwrap_unchecked()
+        };
+        if entry_ptr.is_valid() {
+            return Err(on_conflict(&entry_ptr.get(data_block_mut).1));
+        }
+        locker.insert_with(
+            data_block_mut,
+            BucketArray::<K, V, SEQUENTIAL>::partial_hash(hash),
+            || (key, val),
+            &guard,
+        );
+        Ok(())
+    }
+
+    /// Inserts a key-value pair into the [`HashMap`], or reads the existing value.
+    ///
+    /// If the key exists, `on_conflict` is invoked with the existing value while the bucket is
+    /// locked, and the supplied key-value pair is dropped. It is an asynchronous method returning
+    /// an `impl Future` for the caller to await.
+    ///
+    /// # Errors
+    ///
+    /// Returns the result of `on_conflict` if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let future_try_insert = hashmap.try_insert_async(11, 17, |v| *v);
+    /// ```
+    #[inline]
+    pub async fn try_insert_async<R, F: FnOnce(&V) -> R>(
+        &self,
+        key: K,
+        val: V,
+        on_conflict: F,
+    ) -> Result<(), R> {
+        let hash = self.hash(&key);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            {
+                let guard = Guard::new();
+                if let Ok(LockedEntry {
+                    mut locker,
+                    data_block_mut,
+                    entry_ptr,
+                    index: _,
+                }) = self.reserve_entry(&key, hash, &mut async_wait_pinned, &guard)
+                {
+                    if entry_ptr.is_valid() {
+                        return Err(on_conflict(&entry_ptr.get(data_block_mut).1));
+                    }
+                    locker.insert_with(
+                        data_block_mut,
+                        BucketArray::<K, V, SEQUENTIAL>::partial_hash(hash),
+                        || (key, val),
+                        &guard,
+                    );
+                    return Ok(());
+                };
+            }
+            async_wait_pinned.await;
+        }
+    }
+
     /// Updates an existing key-value pair in-place.
     ///
     /// Returns `None` if the key does not exist.
@@ -519,9 +961,9 @@ where
         }
     }
 
-    /// Removes a key-value pair if the key exists.
+    /// Replaces the value associated with the key, and returns the old value.
     ///
-    /// Returns `None` if the key does not exist.
+    /// Returns `None` if the key does not exist, and `val` is not inserted in that case.
     ///
     /// # Examples
     ///
@@ -530,24 +972,25 @@ where
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// assert!(hashmap.remove(&1).is_none());
+    /// assert!(hashmap.swap(&1, 1).is_none());
+    /// assert!(!hashmap.contains(&1));
     /// assert!(hashmap.insert(1, 0).is_ok());
-    /// assert_eq!(hashmap.remove(&1).unwrap(), (1, 0));
-    /// assert_eq!(hashmap.capacity(), 0);
+    /// assert_eq!(hashmap.swap(&1, 1), Some(0));
+    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 1);
     /// ```
     #[inline]
-    pub fn remove<Q>(&self, key: &Q) -> Option<(K, V)>
+    pub fn swap<Q>(&self, key: &Q, val: V) -> Option<V>
     where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.remove_if(key, |_| true)
+        self.update(key, |_, v| replace(v, val))
     }
 
-    /// Removes a key-value pair if the key exists.
+    /// Replaces the value associated with the key, and returns the old value.
     ///
-    /// Returns `None` if the key does not exist. It is an asynchronous method returning an
-    /// `impl Future` for the caller to await.
+    /// Returns `None` if the key does not exist, and `val` is not inserted in that case. It is an
+    /// asynchronous method returning an `impl Future` for the caller to await.
     ///
     /// # Examples
     ///
@@ -555,21 +998,24 @@ where
     /// use scc::HashMap;
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
-    /// let future_insert = hashmap.insert_async(11, 17);
-    /// let future_remove = hashmap.remove_async(&11);
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// let future_swap = hashmap.swap_async(&1, 1);
     /// ```
     #[inline]
-    pub async fn remove_async<Q>(&self, key: &Q) -> Option<(K, V)>
+    pub async fn swap_async<Q>(&self, key: &Q, val: V) -> Option<V>
     where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.remove_if_async(key, |_| true).await
+        self.update_async(key, |_, v| replace(v, val)).await
     }
 
-    /// Removes a key-value pair if the key exists and the given condition is met.
+    /// Modifies the value associated with the key in-place, or inserts a new value.
     ///
-    /// Returns `None` if the key does not exist or the condition was not met.
+    /// If the key exists, `modify` is invoked with the existing value; otherwise, the result of
+    /// `default` is inserted. Only one of the two closures is invoked, and it is invoked while the
+    /// bucket is locked.
     ///
     /// # Examples
     ///
@@ -578,8 +1024,261 @@ where
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// assert!(hashmap.insert(1, 0).is_ok());
-    /// assert!(hashmap.remove_if(&1, |v| { *v += 1; false }).is_none());
+    /// hashmap.modify_or_insert(1, |v| *v += 1, || 0);
+    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 0);
+    /// hashmap.modify_or_insert(1, |v| *v += 1, || 0);
+    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 1);
+    /// ```
+    #[inline]
+    pub fn modify_or_insert<F: FnOnce(&mut V), G: FnOnce() -> V>(
+        &self,
+        key: K,
+        modify: F,
+        default: G,
+    ) {
+        let guard = Guard::new();
+        let hash = self.hash(&key);
+        let LockedEntry {
+            mut locker,
+            data_block_mut,
+            mut entry_ptr,
+            index: _,
+        } = unsafe {
+            self.reserve_entry(&key, hash, &mut (), &guard)
+                .ok()
+                .unwrap_unchecked()
+        };
+        if entry_ptr.is_valid() {
+            modify(&mut entry_ptr.get_mut(data_block_mut, &mut locker).1);
+        } else {
+            locker.insert_with(
+                data_block_mut,
+                BucketArray::<K, V, SEQUENTIAL>::partial_hash(hash),
+                || (key, default()),
+                &guard,
+            );
+        }
+    }
+
+    /// Modifies the value associated with the key in-place, or inserts a new value.
+    ///
+    /// If the key exists, `modify` is invoked with the existing value; otherwise, the result of
+    /// `default` is inserted. It is an asynchronous method returning an `impl Future` for the
+    /// caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let future_modify_or_insert = hashmap.modify_or_insert_async(1, |v| *v += 1, || 0);
+    /// ```
+    #[inline]
+    pub async fn modify_or_insert_async<F: FnOnce(&mut V), G: FnOnce() -> V>(
+        &self,
+        key: K,
+        modify: F,
+        default: G,
+    ) {
+        let hash = self.hash(&key);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            {
+                let guard = Guard::new();
+                if let Ok(LockedEntry {
+                    mut locker,
+                    data_block_mut,
+                    mut entry_ptr,
+                    index: _,
+                }) = self.reserve_entry(&key, hash, &mut async_wait_pinned, &guard)
+                {
+                    if entry_ptr.is_valid() {
+                        modify(&mut entry_ptr.get_mut(data_block_mut, &mut locker).1);
+                    } else {
+                        locker.insert_with(
+                            data_block_mut,
+                            BucketArray::<K, V, SEQUENTIAL>::partial_hash(hash),
+                            || (key, default()),
+                            &guard,
+                        );
+                    }
+                    return;
+                };
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Computes a new value for the key from the current one.
+    ///
+    /// `f` is invoked with the current value, or `None` if the key does not exist, while the
+    /// bucket is locked. If `f` returns `Some`, the value is inserted or overwritten; otherwise,
+    /// the key is removed if it exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.compute(1, |v| Some(v.map_or(0, |v| v + 1)));
+    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 0);
+    /// hashmap.compute(1, |v| Some(v.map_or(0, |v| v + 1)));
+    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 1);
+    /// hashmap.compute(1, |_| None);
+    /// assert!(!hashmap.contains(&1));
+    /// ```
+    #[inline]
+    pub fn compute<F: FnOnce(Option<&V>) -> Option<V>>(&self, key: K, f: F) {
+        match self.entry(key) {
+            Entry::Occupied(mut o) => {
+                if let Some(val) = f(Some(o.get())) {
+                    o.insert(val);
+                } else {
+                    drop(o.remove_entry());
+                }
+            }
+            Entry::Vacant(v) => {
+                if let Some(val) = f(None) {
+                    v.insert_entry(val);
+                }
+            }
+        }
+    }
+
+    /// Computes a new value for the key from the current one.
+    ///
+    /// `f` is invoked with the current value, or `None` if the key does not exist, while the
+    /// bucket is locked. If `f` returns `Some`, the value is inserted or overwritten; otherwise,
+    /// the key is removed if it exists. It is an asynchronous method returning an `impl Future`
+    /// for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let future_compute = hashmap.compute_async(1, |v| Some(v.map_or(0, |v| v + 1)));
+    /// ```
+    #[inline]
+    pub async fn compute_async<F: FnOnce(Option<&V>) -> Option<V>>(&self, key: K, f: F) {
+        match self.entry_async(key).await {
+            Entry::Occupied(mut o) => {
+                if let Some(val) = f(Some(o.get())) {
+                    o.insert(val);
+                } else {
+                    drop(o.remove_entry());
+                }
+            }
+            Entry::Vacant(v) => {
+                if let Some(val) = f(None) {
+                    v.insert_entry(val);
+                }
+            }
+        }
+    }
+
+    /// Removes a key-value pair if the key exists.
+    ///
+    /// Returns `None` if the key does not exist. The returned key is the
+    /// [stored one](HashMap#stored-keys).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.remove(&1).is_none());
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert_eq!(hashmap.remove(&1).unwrap(), (1, 0));
+    /// assert_eq!(hashmap.capacity(), 0);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.remove_if(key, |_| true)
+    }
+
+    /// Removes a key-value pair if the key exists.
+    ///
+    /// Returns `None` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await. The returned key is the
+    /// [stored one](HashMap#stored-keys).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let future_insert = hashmap.insert_async(11, 17);
+    /// let future_remove = hashmap.remove_async(&11);
+    /// ```
+    #[inline]
+    pub async fn remove_async<Q>(&self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.remove_if_async(key, |_| true).await
+    }
+
+    /// Removes the key-value pairs associated with the supplied keys.
+    ///
+    /// Returns the number of removed key-value pairs; keys that do not exist or are listed more
+    /// than once are not counted. The keys are removed one by one, therefore other threads may
+    /// observe a partially removed state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 0).is_ok());
+    ///
+    /// assert_eq!(hashmap.remove_all(&[1, 3, 1]), 1);
+    /// assert_eq!(hashmap.len(), 1);
+    /// ```
+    #[inline]
+    pub fn remove_all<'q, Q, I: IntoIterator<Item = &'q Q>>(&self, keys: I) -> usize
+    where
+        K: Borrow<Q>,
+        Q: 'q + Eq + Hash + ?Sized,
+    {
+        keys.into_iter()
+            .filter(|key| self.remove(*key).is_some())
+            .count()
+    }
+
+    /// Removes a key-value pair if the key exists and the given condition is met.
+    ///
+    /// The condition is evaluated while the bucket containing the key is exclusively locked,
+    /// therefore the value cannot be modified or removed by others until the method returns.
+    ///
+    /// Returns `None` if the key does not exist or the condition was not met; the two cases are
+    /// indistinguishable from the return value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.remove_if(&1, |v| { *v += 1; false }).is_none());
     /// assert_eq!(hashmap.remove_if(&1, |v| *v == 1).unwrap(), (1, 1));
     /// assert_eq!(hashmap.capacity(), 0);
     /// ```
@@ -603,8 +1302,11 @@ where
 
     /// Removes a key-value pair if the key exists and the given condition is met.
     ///
-    /// Returns `None` if the key does not exist or the condition was not met. It is an
-    /// asynchronous method returning an `impl Future` for the caller to await.
+    /// The condition is evaluated while the bucket containing the key is exclusively locked.
+    ///
+    /// Returns `None` if the key does not exist or the condition was not met; the two cases are
+    /// indistinguishable from the return value. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
     ///
     /// # Examples
     ///
@@ -725,7 +1427,8 @@ where
 
     /// Reads a key-value pair.
     ///
-    /// Returns `None` if the key does not exist.
+    /// Returns `None` if the key does not exist. The key passed to `reader` is the
+    /// [stored one](HashMap#stored-keys).
     ///
     /// # Examples
     ///
@@ -781,6 +1484,56 @@ where
         }
     }
 
+    /// Returns a clone of the value associated with the key.
+    ///
+    /// Returns `None` if the key does not exist. The value is cloned while the bucket is
+    /// read-locked, therefore the returned value is not affected by subsequent modifications.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, String> = HashMap::default();
+    ///
+    /// assert!(hashmap.get_cloned(&1).is_none());
+    /// assert!(hashmap.insert(1, String::from("one")).is_ok());
+    /// assert_eq!(hashmap.get_cloned(&1).unwrap(), "one");
+    /// ```
+    #[inline]
+    pub fn get_cloned<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        V: Clone,
+    {
+        self.read(key, |_, v| v.clone())
+    }
+
+    /// Returns a clone of the value associated with the key.
+    ///
+    /// Returns `None` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let future_insert = hashmap.insert_async(11, 17);
+    /// let future_get_cloned = hashmap.get_cloned_async(&11);
+    /// ```
+    #[inline]
+    pub async fn get_cloned_async<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        V: Clone,
+    {
+        self.read_async(key, |_, v| v.clone()).await
+    }
+
     /// Returns `true` if the [`HashMap`] contains a value for the specified key.
     ///
     /// # Examples
@@ -800,7 +1553,10 @@ where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.read(key, |_, _| ()).is_some()
+        self.read_entry(key, self.hash(key), &mut (), &Guard::new())
+            .ok()
+            .flatten()
+            .is_some()
     }
 
     /// Returns `true` if the [`HashMap`] contains a value for the specified key.
@@ -822,14 +1578,23 @@ where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.read_async(key, |_, _| ()).await.is_some()
+        let hash = self.hash(key);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if let Ok(result) = self.read_entry(key, hash, &mut async_wait_pinned, &Guard::new()) {
+                return result.is_some();
+            }
+            async_wait_pinned.await;
+        }
     }
 
     /// Scans all the entries.
     ///
     /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
-    /// visited if they are not removed, however the same key-value pair can be visited more than
-    /// once if the [`HashMap`] gets resized by another thread.
+    /// visited exactly once if they are not removed, even if the [`HashMap`] gets resized by
+    /// another thread. Key-value pairs inserted or removed during the scan may or may not be
+    /// visited.
     ///
     /// # Examples
     ///
@@ -856,8 +1621,9 @@ where
     /// Scans all the entries.
     ///
     /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
-    /// visited if they are not removed, however the same key-value pair can be visited more than
-    /// once if the [`HashMap`] gets resized by another task.
+    /// visited exactly once if they are not removed, even if the [`HashMap`] gets resized by
+    /// another task. Key-value pairs inserted or removed during the scan may or may not be
+    /// visited.
     ///
     /// # Examples
     ///
@@ -878,42 +1644,68 @@ where
         .await;
     }
 
-    /// Searches for any entry that satisfies the given predicate.
+    /// Folds all the entries into an accumulator.
     ///
     /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
-    /// visited if they are not removed, however the same key-value pair can be visited more than
-    /// once if the [`HashMap`] gets resized by another thread.
-    ///
-    /// Returns `true` as soon as an entry satisfying the predicate is found.
+    /// folded exactly once if they are not removed, even if the [`HashMap`] gets resized by
+    /// another thread. Key-value pairs inserted or removed during the operation may or may not be
+    /// folded, therefore the result is not linearizable.
     ///
     /// # Examples
     ///
     /// ```
     /// use scc::HashMap;
     ///
-    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let hashmap: HashMap<usize, usize> = HashMap::default();
     ///
     /// assert!(hashmap.insert(1, 0).is_ok());
     /// assert!(hashmap.insert(2, 1).is_ok());
-    /// assert!(hashmap.insert(3, 2).is_ok());
     ///
-    /// assert!(hashmap.any(|k, v| *k == 1 && *v == 0));
-    /// assert!(!hashmap.any(|k, v| *k == 2 && *v == 0));
+    /// assert_eq!(hashmap.fold(0, |sum, k, v| sum + *k + *v), 4);
     /// ```
     #[inline]
-    pub fn any<P: FnMut(&K, &V) -> bool>(&self, pred: P) -> bool {
-        self.any_entry(pred)
+    pub fn fold<A, F: FnMut(A, &K, &V) -> A>(&self, init: A, mut f: F) -> A {
+        let mut acc = Some(init);
+        self.scan(|k, v| {
+            acc = acc.take().map(|a| f(a, k, v));
+        });
+        // `acc` is only `None` while `f` is running, and `scan` unwinds if `f` panics.
+        acc.unwrap_or_else(|| unreachable!())
     }
 
-    /// Searches for any entry that satisfies the given predicate.
+    /// Folds all the entries into an accumulator.
     ///
     /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
-    /// visited if they are not removed, however the same key-value pair can be visited more than
-    /// once if the [`HashMap`] gets resized by another task.
+    /// folded exactly once if they are not removed, even if the [`HashMap`] gets resized by
+    /// another task. Key-value pairs inserted or removed during the operation may or may not be
+    /// folded, therefore the result is not linearizable. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
     ///
-    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    /// # Examples
     ///
-    /// Returns `true` as soon as an entry satisfying the predicate is found.
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<usize, usize> = HashMap::default();
+    ///
+    /// let future_insert = hashmap.insert_async(1, 0);
+    /// let future_fold = hashmap.fold_async(0, |sum, k, v| sum + *k + *v);
+    /// ```
+    #[inline]
+    pub async fn fold_async<A, F: FnMut(A, &K, &V) -> A>(&self, init: A, mut f: F) -> A {
+        let mut acc = Some(init);
+        self.scan_async(|k, v| {
+            acc = acc.take().map(|a| f(a, k, v));
+        })
+        .await;
+        // `acc` is only `None` while `f` is running.
+        acc.unwrap_or_else(|| unreachable!())
+    }
+
+    /// Returns a snapshot of the keys in the [`HashMap`].
+    ///
+    /// The snapshot is not linearizable; keys inserted or removed by another thread during the
+    /// method call may or may not be included.
     ///
     /// # Examples
     ///
@@ -922,53 +1714,194 @@ where
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// let future_insert = hashmap.insert_async(1, 0);
-    /// let future_any = hashmap.any_async(|k, _| *k == 1);
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    ///
+    /// let mut keys = hashmap.keys_snapshot();
+    /// keys.sort_unstable();
+    /// assert_eq!(keys, [1, 2]);
     /// ```
     #[inline]
-    pub async fn any_async<P: FnMut(&K, &V) -> bool>(&self, mut pred: P) -> bool {
-        let mut current_array_holder = self.array.get_shared(Acquire, &Guard::new());
-        while let Some(current_array) = current_array_holder.take() {
-            self.cleanse_old_array_async(&current_array).await;
-            for index in 0..current_array.num_buckets() {
-                loop {
-                    let mut async_wait = AsyncWait::default();
-                    let mut async_wait_pinned = Pin::new(&mut async_wait);
-                    {
-                        let guard = Guard::new();
-                        let bucket = current_array.bucket(index);
-                        if let Ok(reader) =
-                            Reader::try_lock_or_wait(bucket, &mut async_wait_pinned, &guard)
-                        {
-                            if let Some(reader) = reader {
-                                let data_block = current_array.data_block(index);
-                                let mut entry_ptr = EntryPtr::new(&guard);
-                                while entry_ptr.next(*reader, &guard) {
-                                    let (k, v) = entry_ptr.get(data_block);
-                                    if pred(k, v) {
-                                        // Found one entry satisfying the predicate.
-                                        return true;
-                                    }
-                                }
-                            }
-                            break;
-                        };
+    pub fn keys_snapshot(&self) -> Vec<K>
+    where
+        K: Clone,
+    {
+        let mut keys = Vec::with_capacity(self.len());
+        self.scan(|k, _| keys.push(k.clone()));
+        keys
+    }
+
+    /// Returns a snapshot of the values in the [`HashMap`].
+    ///
+    /// The snapshot is not linearizable; values inserted or removed by another thread during the
+    /// method call may or may not be included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    ///
+    /// let mut values = hashmap.values_snapshot();
+    /// values.sort_unstable();
+    /// assert_eq!(values, [0, 1]);
+    /// ```
+    #[inline]
+    pub fn values_snapshot(&self) -> Vec<V>
+    where
+        V: Clone,
+    {
+        let mut values = Vec::with_capacity(self.len());
+        self.scan(|_, v| values.push(v.clone()));
+        values
+    }
+
+    /// Returns a snapshot of the key-value pairs in the [`HashMap`].
+    ///
+    /// The snapshot is not linearizable; key-value pairs inserted or removed by another thread
+    /// during the method call may or may not be included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    ///
+    /// let mut entries = hashmap.entries_snapshot();
+    /// entries.sort_unstable();
+    /// assert_eq!(entries, [(1, 0), (2, 1)]);
+    /// ```
+    #[inline]
+    pub fn entries_snapshot(&self) -> Vec<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut entries = Vec::with_capacity(self.len());
+        self.scan(|k, v| entries.push((k.clone(), v.clone())));
+        entries
+    }
+
+    /// Searches for any entry that satisfies the given predicate.
+    ///
+    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
+    /// visited exactly once if they are not removed, even if the [`HashMap`] gets resized by
+    /// another thread. Key-value pairs inserted or removed during the scan may or may not be
+    /// visited.
+    ///
+    /// Returns `true` as soon as an entry satisfying the predicate is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    /// assert!(hashmap.insert(3, 2).is_ok());
+    ///
+    /// assert!(hashmap.any(|k, v| *k == 1 && *v == 0));
+    /// assert!(!hashmap.any(|k, v| *k == 2 && *v == 0));
+    /// ```
+    #[inline]
+    pub fn any<P: FnMut(&K, &V) -> bool>(&self, pred: P) -> bool {
+        self.any_entry(pred)
+    }
+
+    /// Searches for any entry that satisfies the given predicate.
+    ///
+    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
+    /// visited exactly once if they are not removed, even if the [`HashMap`] gets resized by
+    /// another task. Key-value pairs inserted or removed during the scan may or may not be
+    /// visited.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// Returns `true` as soon as an entry satisfying the predicate is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// let future_insert = hashmap.insert_async(1, 0);
+    /// let future_any = hashmap.any_async(|k, _| *k == 1);
+    /// ```
+    #[inline]
+    pub async fn any_async<P: FnMut(&K, &V) -> bool>(&self, mut pred: P) -> bool {
+        for_each_bucket_async(
+            self,
+            |current_array, index, skip_below, async_wait, guard| {
+                let bucket = current_array.bucket(index);
+                let Some(reader) = Reader::try_lock_or_wait(bucket, async_wait, guard)? else {
+                    return Ok(None);
+                };
+                let data_block = current_array.data_block(index);
+                let mut entry_ptr = EntryPtr::new(guard);
+                while entry_ptr.next(*reader, guard) {
+                    let (k, v) = entry_ptr.get(data_block);
+                    if skip_below != 0 && self.hash(k) < skip_below {
+                        continue;
+                    }
+                    if pred(k, v) {
+                        // Found one entry satisfying the predicate.
+                        return Ok(Some(true));
                     }
-                    async_wait_pinned.await;
                 }
-            }
+                Ok(Some(false))
+            },
+        )
+        .await
+    }
 
-            if let Some(new_current_array) = self.array.get_shared(Acquire, &Guard::new()) {
-                if new_current_array.as_ptr() == current_array.as_ptr() {
-                    break;
-                }
-                current_array_holder.replace(new_current_array);
-                continue;
+    /// Visits entries until the supplied closure returns an error.
+    ///
+    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
+    /// visited exactly once if they are not removed, even if the [`HashMap`] gets resized by
+    /// another thread. Key-value pairs inserted or removed during the scan may or may not be
+    /// visited. The order in which entries are visited is unspecified, and therefore so is the set
+    /// of entries visited before the closure returns an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by the closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    ///
+    /// assert_eq!(hashmap.try_for_each(|k, v| if *k == 2 { Err(*v) } else { Ok(()) }), Err(1));
+    /// assert_eq!(hashmap.try_for_each(|k, _| if *k == 3 { Err(()) } else { Ok(()) }), Ok(()));
+    /// ```
+    #[inline]
+    pub fn try_for_each<E, F: FnMut(&K, &V) -> Result<(), E>>(&self, mut f: F) -> Result<(), E> {
+        let mut result = Ok(());
+        self.any(|k, v| {
+            if let Err(e) = f(k, v) {
+                result = Err(e);
+                return true;
             }
-            break;
-        }
-
-        false
+            false
+        });
+        result
     }
 
     /// Retains the entries specified by the predicate.
@@ -976,8 +1909,8 @@ where
     /// This method allows the predicate closure to modify the value field.
     ///
     /// Entries that have existed since the invocation of the method are guaranteed to be visited
-    /// if they are not removed, however the same entry can be visited more than once if the
-    /// [`HashMap`] gets resized by another thread.
+    /// if they are not removed, and each entry is visited at most once even if the [`HashMap`] gets
+    /// resized by another thread.
     ///
     /// # Examples
     ///
@@ -1070,14 +2003,191 @@ where
         }
     }
 
+    /// Retains the entries specified by the predicate using the [`rayon`] thread pool.
+    ///
+    /// This method allows the predicate closure to modify the value field.
+    ///
+    /// Buckets are partitioned across the worker threads after entries in the old bucket array
+    /// are relocated, and the [`HashMap`] is shrunk at most once after all the buckets have been
+    /// processed. If the [`HashMap`] gets resized by another thread in the meantime, the entries
+    /// that were relocated before being visited are processed afterwards in the calling thread,
+    /// and each entry is visited only once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// for k in 0..64 {
+    ///     assert!(hashmap.insert(k, 0).is_ok());
+    /// }
+    ///
+    /// hashmap.par_retain(|k, _| k % 2 == 0);
+    ///
+    /// assert_eq!(hashmap.len(), 32);
+    /// assert!(hashmap.contains(&0));
+    /// assert!(!hashmap.contains(&1));
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_retain<F: Fn(&K, &mut V) -> bool + Sync>(&self, pred: F)
+    where
+        K: Send + Sync,
+        V: Send + Sync,
+        H: Sync,
+    {
+        let guard = Guard::new();
+        let Some(current_array) = self.array.load(Acquire, &guard).as_ref() else {
+            return;
+        };
+        self.clear_old_array(current_array, &guard);
+
+        let (removed, mut killed) = (0..current_array.num_buckets())
+            .into_par_iter()
+            .map_init(Guard::new, |guard, index| {
+                let Some(mut locker) = Locker::lock(current_array.bucket_mut(index), guard) else {
+                    // The `Bucket` has been killed by a resize in progress.
+                    return (false, vec![index]);
+                };
+                let mut removed = false;
+                let data_block_mut = current_array.data_block_mut(index);
+                let mut entry_ptr = EntryPtr::new(guard);
+                while entry_ptr.next(&locker, guard) {
+                    let (k, v) = entry_ptr.get_mut(data_block_mut, &mut locker);
+                    if !pred(k, v) {
+                        locker.erase(data_block_mut, &entry_ptr);
+                        removed = true;
+                    }
+                }
+                (removed, Vec::new())
+            })
+            .reduce(
+                || (false, Vec::new()),
+                |(removed_a, mut killed_a), (removed_b, killed_b)| {
+                    killed_a.extend(killed_b);
+                    (removed_a || removed_b, killed_a)
+                },
+            );
+
+        if !killed.is_empty() {
+            // Only the entries that were in the killed `Bucket` instances have yet to be visited.
+            killed.sort_unstable();
+            self.retain_entries(|k, v| {
+                let index = current_array.calculate_bucket_index(self.hash(k));
+                killed.binary_search(&index).is_err() || pred(k, v)
+            });
+        }
+        if removed {
+            self.try_resize(0, &guard);
+        }
+    }
+
+    /// Inserts the key-value pairs produced by the parallel iterator using the [`rayon`] thread
+    /// pool.
+    ///
+    /// If the length of the iterator is known in advance, the capacity is reserved for all the
+    /// entries before insertion starts so that the worker threads do not have to resize the
+    /// [`HashMap`] along the way. Key-value pairs whose keys already exist in the [`HashMap`] are
+    /// dropped, and if the iterator produces the same key more than once, which one of them is
+    /// inserted is nondeterministic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rayon::iter::{IntoParallelIterator, ParallelIterator};
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.par_extend((0..64).into_par_iter().map(|k| (k, 0)));
+    ///
+    /// assert_eq!(hashmap.len(), 64);
+    /// assert!(hashmap.contains(&63));
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_extend<I: IntoParallelIterator<Item = (K, V)>>(&self, iter: I)
+    where
+        K: Send + Sync,
+        V: Send + Sync,
+        H: Sync,
+    {
+        let iter = iter.into_par_iter();
+        let reserved = iter.opt_len().and_then(|len| self.reserve(len));
+        iter.for_each(|(k, v)| {
+            let _: Result<(), (K, V)> = self.insert(k, v);
+        });
+        drop(reserved);
+    }
+
+    /// Removes the entries specified by the predicate and returns them.
+    ///
+    /// Unlike [`HashMap::retain`], the removed entries are handed back to the caller instead of
+    /// being dropped.
+    ///
+    /// Entries that have existed since the invocation of the method are guaranteed to be visited,
+    /// and each entry is visited at most once even if the [`HashMap`] gets resized by another
+    /// thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    /// assert!(hashmap.insert(3, 2).is_ok());
+    ///
+    /// let mut extracted = hashmap.extract_if(|k, _| *k != 1);
+    /// extracted.sort_unstable();
+    /// assert_eq!(extracted, vec![(2, 1), (3, 2)]);
+    /// assert_eq!(hashmap.len(), 1);
+    /// ```
+    #[inline]
+    pub fn extract_if<F: FnMut(&K, &V) -> bool>(&self, pred: F) -> Vec<(K, V)> {
+        self.extract_entries(pred)
+    }
+
+    /// Replaces every value with the value returned by `transform`.
+    ///
+    /// Entries that have existed since the invocation of the method are guaranteed to be visited
+    /// exactly once if they are not removed, even if the [`HashMap`] gets resized by another
+    /// thread. If `transform` panics, the entry being transformed is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, String> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, String::from("a")).is_ok());
+    /// assert!(hashmap.insert(2, String::from("b")).is_ok());
+    ///
+    /// hashmap.transform_values(|k, mut v| {
+    ///     v.push_str(&k.to_string());
+    ///     v
+    /// });
+    /// assert_eq!(hashmap.read(&1, |_, v| v.clone()).unwrap(), "a1");
+    /// assert_eq!(hashmap.read(&2, |_, v| v.clone()).unwrap(), "b2");
+    /// ```
+    #[inline]
+    pub fn transform_values<F: FnMut(&K, V) -> V>(&self, transform: F) {
+        self.transform_entries(transform);
+    }
+
     /// Prunes the entries specified by the predicate.
     ///
     /// If the value is consumed by the predicate, in other words, if the predicate returns `None`,
     /// the entry is removed, otherwise the entry is retained.
     ///
     /// Entries that have existed since the invocation of the method are guaranteed to be visited
-    /// if they are not removed, however the same entry can be visited more than once if the
-    /// [`HashMap`] gets resized by another thread.
+    /// if they are not removed, and each entry is visited at most once even if the [`HashMap`]
+    /// gets resized by another thread.
     ///
     /// # Examples
     ///
@@ -1205,6 +2315,52 @@ where
         self.retain_async(|_, _| false).await;
     }
 
+    /// Clears `target` and copies all the entries of the [`HashMap`] into it.
+    ///
+    /// The bucket array of `target` is kept while it is being cleared and refilled, and it is only
+    /// enlarged if it cannot accommodate the entries of the [`HashMap`]. Entries inserted into or
+    /// removed from the [`HashMap`] during the operation may or may not be copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let target: HashMap<u64, u32> = HashMap::with_capacity(1024);
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(target.insert(2, 0).is_ok());
+    /// hashmap.clone_to(&target);
+    ///
+    /// assert_eq!(target.read(&1, |_, v| *v), Some(0));
+    /// assert!(!target.contains(&2));
+    /// assert_eq!(target.capacity(), 1024);
+    /// ```
+    #[inline]
+    pub fn clone_to(&self, target: &Self)
+    where
+        K: Clone,
+        V: Clone,
+    {
+        if std::ptr::eq(self, target) {
+            return;
+        }
+
+        // Raise the minimum capacity of `target` so that its bucket array is not shrunk.
+        let required_capacity = target.capacity().max(self.len());
+        let additional = target.reserve_capacity(
+            required_capacity.saturating_sub(target.minimum_capacity.load(Relaxed)),
+        );
+        target.clear();
+        self.scan(|k, v| {
+            let _result = target.insert(k.clone(), v.clone());
+        });
+        if additional != 0 {
+            target.minimum_capacity.fetch_sub(additional, Relaxed);
+        }
+    }
+
     /// Returns the number of entries in the [`HashMap`].
     ///
     /// It reads the entire metadata area of the bucket array to calculate the number of valid
@@ -1265,7 +2421,171 @@ where
         self.num_slots(&Guard::new())
     }
 
-    /// Returns the current capacity range of the [`HashMap`].
+    /// Returns the estimated number of bytes allocated for the [`HashMap`].
+    ///
+    /// The estimate covers the bucket arrays, including an old bucket array that is being
+    /// resized, and the linked buckets attached to them; memory owned by keys and values outside
+    /// of the [`HashMap`], e.g., the contents of a [`String`], is not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// assert_eq!(hashmap.memory_usage(), 0);
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.memory_usage() > 0);
+    /// ```
+    #[inline]
+    pub fn memory_usage(&self) -> usize {
+        HashTable::memory_usage(self, &Guard::new())
+    }
+
+    /// Returns the ratio of the number of entries to the capacity of the [`HashMap`].
+    ///
+    /// Both numbers are read from the same snapshot of the bucket array, however the result is
+    /// approximate if the [`HashMap`] is being modified or resized by another thread. A load
+    /// factor greater than `1.0` is normal whenever buckets overflow into linked buckets, since
+    /// the entries in linked buckets are not included in the capacity; entries that have yet to
+    /// be relocated from an old bucket array are also counted. Returns `0.0` if no memory is
+    /// allocated for the [`HashMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::with_capacity(64);
+    /// assert_eq!(hashmap.load_factor(), 0.0);
+    ///
+    /// for i in 0..16 {
+    ///     assert!(hashmap.insert(i, 0).is_ok());
+    /// }
+    /// assert_eq!(hashmap.load_factor(), 0.25);
+    /// ```
+    #[allow(clippy::cast_precision_loss)]
+    #[inline]
+    pub fn load_factor(&self) -> f32 {
+        let guard = Guard::new();
+        let num_slots = self.num_slots(&guard);
+        if num_slots == 0 {
+            return 0.0;
+        }
+        self.num_entries(&guard) as f32 / num_slots as f32
+    }
+
+    /// Returns the number of entries in each bucket of the [`HashMap`].
+    ///
+    /// This is a diagnostic method for detecting clustering caused by a poor [`BuildHasher`]: the
+    /// `i`-th element of the returned [`Vec`] is the number of entries in the `i`-th bucket of the
+    /// current bucket array, including those in linked buckets. Entries that have yet to be
+    /// relocated from an old bucket array are not counted, and the result is approximate if the
+    /// [`HashMap`] is being modified by another thread. Returns an empty [`Vec`] if no memory is
+    /// allocated for the [`HashMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// assert!(hashmap.bucket_occupancy().is_empty());
+    ///
+    /// for i in 0..16 {
+    ///     assert!(hashmap.insert(i, 0).is_ok());
+    /// }
+    /// assert_eq!(hashmap.bucket_occupancy().iter().sum::<usize>(), 16);
+    /// ```
+    #[inline]
+    pub fn bucket_occupancy(&self) -> Vec<usize> {
+        HashTable::bucket_occupancy(self, &Guard::new())
+    }
+
+    /// Returns the current capacity range of the [`HashMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert_eq!(hashmap.capacity_range(), 0..=(1_usize << (usize::BITS - 1)));
+    ///
+    /// let reserved = hashmap.reserve(1000);
+    /// assert_eq!(hashmap.capacity_range(), 1000..=(1_usize << (usize::BITS - 1)));
+    /// ```
+    #[inline]
+    pub fn capacity_range(&self) -> RangeInclusive<usize> {
+        self.minimum_capacity.load(Relaxed)..=self.maximum_capacity()
+    }
+
+    /// Returns a reference to the [`BuildHasher`] of the [`HashMap`].
+    ///
+    /// Hash values produced by the [`BuildHasher`] are used as they are unless mixing is enabled
+    /// by [`HashMap::with_mixing`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    /// use std::hash::BuildHasher;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::with_hasher(RandomState::new());
+    /// let hash = hashmap.hasher().hash_one(11_u64);
+    /// assert_eq!(hash, hashmap.hasher().hash_one(11_u64));
+    /// ```
+    #[inline]
+    pub fn hasher(&self) -> &H {
+        &self.build_hasher
+    }
+
+    /// Rebuilds the [`HashMap`] with a different [`BuildHasher`].
+    ///
+    /// A new [`HashMap`] using `new_hasher` is created with enough capacity reserved for the
+    /// entries in `self`, and a clone of each entry is inserted into it. The hash mixing and
+    /// resizing settings of `self` are carried over.
+    ///
+    /// This method is not linearizable: entries inserted into or removed from `self` during the
+    /// operation may or may not be reflected in the returned [`HashMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    ///
+    /// let rehashed: HashMap<u64, u32, RandomState> = hashmap.rehash_with(RandomState::new());
+    /// assert_eq!(rehashed.read(&1, |_, v| *v), Some(0));
+    /// ```
+    #[inline]
+    pub fn rehash_with<H2: BuildHasher>(&self, new_hasher: H2) -> HashMap<K, V, H2>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut rehashed = HashMap::with_hasher(new_hasher).with_mixing(self.mix_hash);
+        rehashed.resize_policy = self.resize_policy;
+        let reserved = rehashed.reserve(self.len());
+        self.scan(|k, v| {
+            let _result = rehashed.insert(k.clone(), v.clone());
+        });
+        drop(reserved);
+        rehashed
+    }
+
+    /// Moves all the entries of `other` into `self`, combining the values of the same key with
+    /// `resolve`.
+    ///
+    /// `resolve` is invoked with the key, the existing value, and the incoming value if `self`
+    /// already contains the key, and the returned value replaces the existing one. Capacity for
+    /// the entries of `other` is reserved before they are inserted.
     ///
     /// # Examples
     ///
@@ -1273,15 +2593,33 @@ where
     /// use scc::HashMap;
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
-    ///
-    /// assert_eq!(hashmap.capacity_range(), 0..=(1_usize << (usize::BITS - 1)));
-    ///
-    /// let reserved = hashmap.reserve(1000);
-    /// assert_eq!(hashmap.capacity_range(), 1000..=(1_usize << (usize::BITS - 1)));
-    /// ```
-    #[inline]
-    pub fn capacity_range(&self) -> RangeInclusive<usize> {
-        self.minimum_capacity.load(Relaxed)..=self.maximum_capacity()
+    /// let other: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 1).is_ok());
+    /// assert!(other.insert(1, 2).is_ok());
+    /// assert!(other.insert(2, 3).is_ok());
+    ///
+    /// hashmap.merge(other, |_, existing, incoming| existing + incoming);
+    /// assert_eq!(hashmap.read(&1, |_, v| *v), Some(3));
+    /// assert_eq!(hashmap.read(&2, |_, v| *v), Some(3));
+    /// ```
+    #[allow(clippy::needless_pass_by_value)]
+    #[inline]
+    pub fn merge<F: FnMut(&K, &V, V) -> V>(&self, other: HashMap<K, V, H>, mut resolve: F) {
+        let incoming = other.drain_entries();
+        let reserved = self.reserve(incoming.len());
+        for (k, v) in incoming {
+            match self.entry(k) {
+                Entry::Occupied(mut o) => {
+                    let resolved = resolve(o.key(), o.get(), v);
+                    *o.get_mut() = resolved;
+                }
+                Entry::Vacant(vacant) => {
+                    vacant.insert_entry(v);
+                }
+            }
+        }
+        drop(reserved);
     }
 
     /// Returns the index of the bucket that may contain the key.
@@ -1350,7 +2688,12 @@ where
 
     /// Creates an empty [`HashMap`] with the specified capacity.
     ///
-    /// The actual capacity is equal to or greater than the specified capacity.
+    /// The actual capacity is equal to or greater than the specified capacity, except that it is
+    /// clamped to the maximum capacity of `2^(usize::BITS - 1)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the memory required for the capacity cannot be allocated or its size overflows.
     ///
     /// # Examples
     ///
@@ -1377,7 +2720,9 @@ where
 {
     #[inline]
     fn clone(&self) -> Self {
-        let self_clone = Self::with_capacity_and_hasher(self.capacity(), self.hasher().clone());
+        let mut self_clone = Self::with_capacity_and_hasher(self.capacity(), self.hasher().clone())
+            .with_mixing(self.mix_hash);
+        self_clone.resize_policy = self.resize_policy;
         self.scan(|k, v| {
             let _reuslt = self_clone.insert(k.clone(), v.clone());
         });
@@ -1458,6 +2803,14 @@ where
         &self.build_hasher
     }
     #[inline]
+    fn mix_hash(&self) -> bool {
+        self.mix_hash
+    }
+    #[inline]
+    fn resize_policy(&self) -> ResizePolicy {
+        self.resize_policy
+    }
+    #[inline]
     fn try_clone(_: &(K, V)) -> Option<(K, V)> {
         None
     }
@@ -1477,6 +2830,40 @@ where
     }
 }
 
+impl<K, V, H> IntoIterator for HashMap<K, V, H>
+where
+    K: Eq + Hash,
+    H: BuildHasher,
+{
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    /// Consumes the [`HashMap`], moving all its entries out.
+    ///
+    /// Entries in an old bucket array are relocated into the current one before being moved out,
+    /// therefore each entry is yielded exactly once. Entries that are not consumed are dropped
+    /// along with the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    ///
+    /// let mut entries: Vec<(u64, u32)> = hashmap.into_iter().collect();
+    /// entries.sort_unstable();
+    /// assert_eq!(entries, vec![(1, 0), (2, 1)]);
+    /// ```
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.drain_entries().into_iter()
+    }
+}
+
 impl<K, V, H> PartialEq for HashMap<K, V, H>
 where
     K: Eq + Hash,
@@ -1645,6 +3032,9 @@ where
 {
     /// Ensures a value is in the entry by inserting the default value if empty.
     ///
+    /// The default value is only constructed if the entry is vacant, and the bucket locked by
+    /// [`HashMap::entry`] is not released until the returned [`OccupiedEntry`] is dropped.
+    ///
     /// # Examples
     ///
     /// ```
@@ -1653,6 +3043,9 @@ where
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
     /// hashmap.entry(11).or_default();
     /// assert_eq!(hashmap.read(&11, |_, v| *v), Some(0));
+    ///
+    /// *hashmap.entry(11).or_default().get_mut() += 1;
+    /// assert_eq!(hashmap.read(&11, |_, v| *v), Some(1));
     /// ```
     #[inline]
     pub fn or_default(self) -> OccupiedEntry<'h, K, V, H> {
@@ -1828,6 +3221,64 @@ where
         replace(self.get_mut(), val)
     }
 
+    /// Replaces the key of the entry with the given key, and returns the old key.
+    ///
+    /// The given key must be equal to the old key and have the same hash value; this is useful
+    /// for keys carrying additional data that is ignored by [`Eq`] and [`Hash`], e.g., a version.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the given key is not equal to the old key or their hash values
+    /// differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::Entry;
+    /// use std::hash::{Hash, Hasher};
+    ///
+    /// struct Versioned(u64, u32);
+    /// impl PartialEq for Versioned {
+    ///     fn eq(&self, other: &Self) -> bool {
+    ///         self.0 == other.0
+    ///     }
+    /// }
+    /// impl Eq for Versioned {}
+    /// impl Hash for Versioned {
+    ///     fn hash<H: Hasher>(&self, state: &mut H) {
+    ///         self.0.hash(state);
+    ///     }
+    /// }
+    ///
+    /// let hashmap: HashMap<Versioned, u32> = HashMap::default();
+    ///
+    /// hashmap.entry(Versioned(37, 0)).or_insert(11);
+    ///
+    /// if let Entry::Occupied(mut o) = hashmap.entry(Versioned(37, 1)) {
+    ///     assert_eq!(o.replace_key(Versioned(37, 1)).1, 0);
+    ///     assert_eq!(o.key().1, 1);
+    /// }
+    ///
+    /// assert_eq!(hashmap.read(&Versioned(37, 2), |k, _| k.1), Some(1));
+    /// ```
+    #[inline]
+    pub fn replace_key(&mut self, key: K) -> K {
+        debug_assert!(*self.key() == key);
+        debug_assert_eq!(self.hashmap.hash(self.key()), self.hashmap.hash(&key));
+        replace(
+            &mut self
+                .locked_entry
+                .entry_ptr
+                .get_mut(
+                    self.locked_entry.data_block_mut,
+                    &mut self.locked_entry.locker,
+                )
+                .0,
+            key,
+        )
+    }
+
     /// Takes the value out of the entry, and returns it.
     ///
     /// # Examples
@@ -2027,6 +3478,245 @@ where
     }
 }
 
+impl<'h, 'q, K, Q, V, H> EntryRef<'h, 'q, K, Q, V, H>
+where
+    K: Borrow<Q> + Eq + From<&'q Q> + Hash,
+    Q: ?Sized,
+    H: BuildHasher,
+{
+    /// Ensures a value is in the entry by inserting the supplied instance if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<String, u32> = HashMap::default();
+    ///
+    /// hashmap.entry_ref("a").or_insert(7);
+    /// assert_eq!(hashmap.read("a", |_, v| *v), Some(7));
+    /// ```
+    #[inline]
+    pub fn or_insert(self, val: V) -> OccupiedEntry<'h, K, V, H> {
+        self.or_insert_with(|| val)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the supplied closure if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<String, u32> = HashMap::default();
+    ///
+    /// hashmap.entry_ref("b").or_insert_with(|| 5);
+    /// assert_eq!(hashmap.read("b", |_, v| *v), Some(5));
+    /// ```
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, constructor: F) -> OccupiedEntry<'h, K, V, H> {
+        match self {
+            Self::Occupied(o) => o,
+            Self::Vacant(v) => v.insert_entry(constructor()),
+        }
+    }
+
+    /// Returns a reference to the key of this entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<String, u32> = HashMap::default();
+    /// assert_eq!(hashmap.entry_ref("c").key(), "c");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn key(&self) -> &Q {
+        match self {
+            Self::Occupied(o) => o.key().borrow(),
+            Self::Vacant(v) => v.key(),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<String, u32> = HashMap::default();
+    ///
+    /// hashmap.entry_ref("d").and_modify(|v| { *v += 1 }).or_insert(47);
+    /// assert_eq!(hashmap.read("d", |_, v| *v), Some(47));
+    ///
+    /// hashmap.entry_ref("d").and_modify(|v| { *v += 1 }).or_insert(3);
+    /// assert_eq!(hashmap.read("d", |_, v| *v), Some(48));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            Self::Occupied(mut o) => {
+                f(o.get_mut());
+                Self::Occupied(o)
+            }
+            Self::Vacant(_) => self,
+        }
+    }
+
+    /// Sets the value of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<String, u32> = HashMap::default();
+    /// let entry = hashmap.entry_ref("e").insert_entry(17);
+    /// assert_eq!(entry.key(), "e");
+    /// ```
+    #[inline]
+    pub fn insert_entry(self, val: V) -> OccupiedEntry<'h, K, V, H> {
+        match self {
+            Self::Occupied(mut o) => {
+                o.insert(val);
+                o
+            }
+            Self::Vacant(v) => v.insert_entry(val),
+        }
+    }
+}
+
+impl<'h, 'q, K, Q, V, H> EntryRef<'h, 'q, K, Q, V, H>
+where
+    K: Borrow<Q> + Eq + From<&'q Q> + Hash,
+    Q: ?Sized,
+    V: Default,
+    H: BuildHasher,
+{
+    /// Ensures a value is in the entry by inserting the default value if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<String, u32> = HashMap::default();
+    /// assert_eq!(*hashmap.entry_ref("f").or_default().get(), 0);
+    /// assert_eq!(hashmap.read("f", |_, v| *v), Some(0));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn or_default(self) -> OccupiedEntry<'h, K, V, H> {
+        match self {
+            Self::Occupied(o) => o,
+            Self::Vacant(v) => v.insert_entry(Default::default()),
+        }
+    }
+}
+
+impl<K, Q, V, H> Debug for EntryRef<'_, '_, K, Q, V, H>
+where
+    K: Debug + Eq + Hash,
+    Q: Debug + ?Sized,
+    V: Debug,
+    H: BuildHasher,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Vacant(v) => f.debug_tuple("EntryRef").field(v).finish(),
+            Self::Occupied(o) => f.debug_tuple("EntryRef").field(o).finish(),
+        }
+    }
+}
+
+impl<'h, 'q, K, Q, V, H> VacantEntryRef<'h, 'q, K, Q, V, H>
+where
+    K: Eq + Hash,
+    Q: ?Sized,
+    H: BuildHasher,
+{
+    /// Gets a reference to the borrowed key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::EntryRef;
+    ///
+    /// let hashmap: HashMap<String, u32> = HashMap::default();
+    ///
+    /// if let EntryRef::Vacant(v) = hashmap.entry_ref("g") {
+    ///     assert_eq!(v.key(), "g");
+    /// };
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn key(&self) -> &'q Q {
+        self.key
+    }
+
+    /// Sets the value of the entry with the owned key constructed from the borrowed key, and
+    /// returns an [`OccupiedEntry`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::EntryRef;
+    ///
+    /// let hashmap: HashMap<String, u32> = HashMap::default();
+    ///
+    /// if let EntryRef::Vacant(v) = hashmap.entry_ref("h") {
+    ///     v.insert_entry(29);
+    /// }
+    ///
+    /// assert_eq!(hashmap.read("h", |_, v| *v), Some(29));
+    /// ```
+    #[inline]
+    pub fn insert_entry(mut self, val: V) -> OccupiedEntry<'h, K, V, H>
+    where
+        K: From<&'q Q>,
+    {
+        let guard = Guard::new();
+        let entry_ptr = self.locked_entry.locker.insert_with(
+            self.locked_entry.data_block_mut,
+            BucketArray::<K, V, SEQUENTIAL>::partial_hash(self.hash),
+            || (K::from(self.key), val),
+            self.hashmap.prolonged_guard_ref(&guard),
+        );
+        OccupiedEntry {
+            hashmap: self.hashmap,
+            locked_entry: LockedEntry {
+                index: self.locked_entry.index,
+                data_block_mut: self.locked_entry.data_block_mut,
+                locker: self.locked_entry.locker,
+                entry_ptr,
+            },
+        }
+    }
+}
+
+impl<K, Q, V, H> Debug for VacantEntryRef<'_, '_, K, Q, V, H>
+where
+    K: Debug + Eq + Hash,
+    Q: Debug + ?Sized,
+    V: Debug,
+    H: BuildHasher,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntryRef").field(&self.key).finish()
+    }
+}
+
 impl<'h, K, V, H> Reserve<'h, K, V, H>
 where
     K: Eq + Hash,
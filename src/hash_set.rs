@@ -1,11 +1,15 @@
 //! [`HashSet`] is a concurrent and asynchronous hash set.
 
+use super::exit_guard::ExitGuard;
+use super::hash_table::HashTable;
 use super::HashMap;
 use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
 use std::fmt::{self, Debug};
 use std::hash::{BuildHasher, Hash};
 use std::ops::RangeInclusive;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
 
 /// Scalable concurrent hash set.
 ///
@@ -16,6 +20,7 @@ where
     H: BuildHasher,
 {
     map: HashMap<K, (), H>,
+    num_entries: AtomicUsize,
 }
 
 /// [`Reserve`] keeps the capacity of the associated [`HashSet`] higher than a certain level.
@@ -42,6 +47,7 @@ where
     pub fn with_hasher(build_hasher: H) -> Self {
         Self {
             map: HashMap::with_hasher(build_hasher),
+            num_entries: AtomicUsize::new(0),
         }
     }
 
@@ -65,6 +71,7 @@ where
     pub fn with_capacity_and_hasher(capacity: usize, build_hasher: H) -> Self {
         Self {
             map: HashMap::with_capacity_and_hasher(capacity, build_hasher),
+            num_entries: AtomicUsize::new(0),
         }
     }
 
@@ -126,7 +133,10 @@ where
     /// ```
     #[inline]
     pub fn insert(&self, key: K) -> Result<(), K> {
+        // The counter is incremented in advance so that it never falls below the actual number.
+        self.num_entries.fetch_add(1, Relaxed);
         if let Err((k, ())) = self.map.insert(key, ()) {
+            self.num_entries.fetch_sub(1, Relaxed);
             return Err(k);
         }
         Ok(())
@@ -151,7 +161,16 @@ where
     /// ```
     #[inline]
     pub async fn insert_async(&self, key: K) -> Result<(), K> {
-        self.map.insert_async(key, ()).await.map_err(|(k, ())| k)
+        // The counter is restored if the key exists or the future is dropped before completion.
+        self.num_entries.fetch_add(1, Relaxed);
+        let mut exit_guard = ExitGuard::new(true, |rollback| {
+            if rollback {
+                self.num_entries.fetch_sub(1, Relaxed);
+            }
+        });
+        let result = self.map.insert_async(key, ()).await;
+        *exit_guard = result.is_err();
+        result.map_err(|(k, ())| k)
     }
 
     /// Removes a key if the key exists.
@@ -176,7 +195,7 @@ where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.map.remove(key).map(|(k, ())| k)
+        self.map.remove(key).map(|(k, ())| self.removed(k))
     }
 
     /// Removes a key if the key exists.
@@ -202,7 +221,7 @@ where
         self.map
             .remove_if_async(key, |()| true)
             .await
-            .map(|(k, ())| k)
+            .map(|(k, ())| self.removed(k))
     }
 
     /// Removes a key if the key exists and the given condition is met.
@@ -227,7 +246,9 @@ where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.map.remove_if(key, |()| condition()).map(|(k, ())| k)
+        self.map
+            .remove_if(key, |()| condition())
+            .map(|(k, ())| self.removed(k))
     }
 
     /// Removes a key if the key exists and the given condition is met.
@@ -253,12 +274,13 @@ where
         self.map
             .remove_if_async(key, |()| condition())
             .await
-            .map(|(k, ())| k)
+            .map(|(k, ())| self.removed(k))
     }
 
     /// Reads a key.
     ///
-    /// Returns `None` if the key does not exist.
+    /// Returns `None` if the key does not exist. The key passed to `reader` is the
+    /// [stored one](HashMap#stored-keys).
     ///
     /// # Examples
     ///
@@ -448,8 +470,8 @@ where
     /// Retains keys that satisfy the given predicate.
     ///
     /// Keys that have existed since the invocation of the method are guaranteed to be visited if
-    /// they are not removed, however the same key can be visited more than once if the [`HashSet`]
-    /// gets resized by another thread.
+    /// they are not removed, and each key is visited at most once even if the [`HashSet`] gets
+    /// resized by another thread.
     ///
     /// # Examples
     ///
@@ -470,7 +492,15 @@ where
     /// ```
     #[inline]
     pub fn retain<F: FnMut(&K) -> bool>(&self, mut filter: F) {
-        self.map.retain(|k, ()| filter(k));
+        let mut num_removed = 0;
+        self.map.retain(|k, ()| {
+            if filter(k) {
+                return true;
+            }
+            num_removed += 1;
+            false
+        });
+        self.num_entries.fetch_sub(num_removed, Relaxed);
     }
 
     /// Retains keys that satisfy the given predicate.
@@ -493,7 +523,16 @@ where
     /// ```
     #[inline]
     pub async fn retain_async<F: FnMut(&K) -> bool>(&self, mut filter: F) {
-        self.map.retain_async(|k, ()| filter(k)).await;
+        // The counter is updated for each key so that it stays correct if the future is dropped.
+        self.map
+            .retain_async(|k, ()| {
+                if filter(k) {
+                    return true;
+                }
+                self.num_entries.fetch_sub(1, Relaxed);
+                false
+            })
+            .await;
     }
 
     /// Clears the [`HashSet`] by removing all keys.
@@ -512,7 +551,7 @@ where
     /// ```
     #[inline]
     pub fn clear(&self) {
-        self.map.clear();
+        self.retain(|_| false);
     }
 
     /// Clears the [`HashSet`] by removing all keys.
@@ -531,14 +570,46 @@ where
     /// ```
     #[inline]
     pub async fn clear_async(&self) {
-        self.map.clear_async().await;
+        self.retain_async(|_| false).await;
+    }
+
+    /// Removes all the keys from the [`HashSet`] and returns them.
+    ///
+    /// Each key is returned at most once even if the [`HashSet`] gets resized by another thread,
+    /// however keys inserted by other threads during the operation may or may not be removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let hashset: HashSet<u64> = HashSet::default();
+    ///
+    /// assert!(hashset.insert(1).is_ok());
+    /// assert!(hashset.insert(2).is_ok());
+    ///
+    /// let mut keys = hashset.drain();
+    /// keys.sort_unstable();
+    /// assert_eq!(keys, vec![1, 2]);
+    /// assert!(hashset.is_empty());
+    /// ```
+    #[inline]
+    pub fn drain(&self) -> Vec<K> {
+        let keys: Vec<K> = self
+            .map
+            .drain_entries()
+            .into_iter()
+            .map(|(k, ())| k)
+            .collect();
+        self.num_entries.fetch_sub(keys.len(), Relaxed);
+        keys
     }
 
     /// Returns the number of entries in the [`HashSet`].
     ///
-    /// It reads the entire metadata area of the bucket array to calculate the number of valid
-    /// entries, making its time complexity `O(N)`. Furthermore, it may overcount entries if an old
-    /// bucket array has yet to be dropped.
+    /// It reads a counter that is updated on insertion and removal, making its time complexity
+    /// `O(1)`. The counter is exact when no other thread is modifying the [`HashSet`], otherwise
+    /// it may momentarily overcount entries being inserted or removed.
     ///
     /// # Examples
     ///
@@ -552,7 +623,7 @@ where
     /// ```
     #[inline]
     pub fn len(&self) -> usize {
-        self.map.len()
+        self.num_entries.load(Relaxed)
     }
 
     /// Returns `true` if the [`HashSet`] is empty.
@@ -570,7 +641,7 @@ where
     /// ```
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.map.is_empty()
+        self.len() == 0
     }
 
     /// Returns the capacity of the [`HashSet`].
@@ -636,6 +707,13 @@ where
     {
         self.map.bucket_index(key)
     }
+
+    /// Decrements the entry counter for the removed key.
+    #[inline]
+    fn removed(&self, key: K) -> K {
+        self.num_entries.fetch_sub(1, Relaxed);
+        key
+    }
 }
 
 impl<K, H> Clone for HashSet<K, H>
@@ -645,9 +723,9 @@ where
 {
     #[inline]
     fn clone(&self) -> Self {
-        Self {
-            map: self.map.clone(),
-        }
+        let map = self.map.clone();
+        let num_entries = AtomicUsize::new(map.len());
+        Self { map, num_entries }
     }
 }
 
@@ -704,6 +782,7 @@ impl<K: Eq + Hash> HashSet<K, RandomState> {
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             map: HashMap::with_capacity(capacity),
+            num_entries: AtomicUsize::new(0),
         }
     }
 }
@@ -731,7 +810,71 @@ where
     fn default() -> Self {
         Self {
             map: HashMap::default(),
+            num_entries: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<K, H, const N: usize> From<[K; N]> for HashSet<K, H>
+where
+    K: Eq + Hash,
+    H: BuildHasher + Default,
+{
+    /// Creates a [`HashSet`] from an array.
+    ///
+    /// Duplicate keys are inserted only once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let hashset: HashSet<u64> = HashSet::from([1, 2, 2, 3]);
+    ///
+    /// assert_eq!(hashset.len(), 3);
+    /// assert!(hashset.contains(&2));
+    /// ```
+    #[inline]
+    fn from(keys: [K; N]) -> Self {
+        let hashset = Self::default();
+        let reserved = hashset.reserve(N);
+        for key in keys {
+            let _result = hashset.insert(key);
+        }
+        drop(reserved);
+        hashset
+    }
+}
+
+impl<K, H> From<&[K]> for HashSet<K, H>
+where
+    K: Clone + Eq + Hash,
+    H: BuildHasher + Default,
+{
+    /// Creates a [`HashSet`] from a slice by cloning the keys.
+    ///
+    /// Duplicate keys are inserted only once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let keys = [1, 2, 2, 3];
+    /// let hashset: HashSet<u64> = HashSet::from(&keys[..]);
+    ///
+    /// assert_eq!(hashset.len(), 3);
+    /// assert!(hashset.contains(&2));
+    /// ```
+    #[inline]
+    fn from(keys: &[K]) -> Self {
+        let hashset = Self::default();
+        let reserved = hashset.reserve(keys.len());
+        for key in keys {
+            let _result = hashset.insert(key.clone());
         }
+        drop(reserved);
+        hashset
     }
 }
 
@@ -4,7 +4,9 @@ pub mod bucket_array;
 use crate::ebr::{AtomicShared, Guard, Ptr, Shared, Tag};
 use crate::exit_guard::ExitGuard;
 use crate::wait_queue::{AsyncWait, DeriveAsyncWait};
-use bucket::{DataBlock, EntryPtr, Locker, Reader, BUCKET_LEN, CACHE, OPTIMISTIC};
+use bucket::{
+    DataBlock, EntryPtr, Locker, Reader, BUCKET_LEN, CACHE, LINKED_BUCKET_LEN, OPTIMISTIC,
+};
 use bucket_array::BucketArray;
 use std::borrow::Borrow;
 use std::hash::{BuildHasher, Hash};
@@ -25,12 +27,29 @@ where
         K: Borrow<Q>,
         Q: Hash + ?Sized,
     {
-        self.hasher().hash_one(key)
+        let hash = self.hasher().hash_one(key);
+        if self.mix_hash() {
+            mix(hash)
+        } else {
+            hash
+        }
     }
 
     /// Returns a reference to its [`BuildHasher`].
     fn hasher(&self) -> &H;
 
+    /// Returns `true` if hash values need to be mixed before use.
+    #[inline]
+    fn mix_hash(&self) -> bool {
+        false
+    }
+
+    /// Returns the [`ResizePolicy`].
+    #[inline]
+    fn resize_policy(&self) -> ResizePolicy {
+        ResizePolicy::DEFAULT
+    }
+
     /// Tries to clone the instances pointed by `entry`.
     ///
     /// It does not clone unless `TYPE` is `OPTIMISTIC` thus `K` and `V` both being `Clone`.
@@ -41,6 +60,12 @@ where
     /// It does not clone unless `TYPE` is `OPTIMISTIC` thus `K` and `V` both being `Clone`.
     fn try_reset(value: &mut V);
 
+    /// Drops an entry that cannot be relocated to the new bucket array.
+    #[inline]
+    fn dispose(&self, entry: (K, V)) {
+        drop(entry);
+    }
+
     /// Returns a reference to the [`BucketArray`] pointer.
     fn bucket_array(&self) -> &AtomicShared<BucketArray<K, V, TYPE>>;
 
@@ -72,8 +97,9 @@ where
     fn reserve_capacity(&self, additional_capacity: usize) -> usize {
         let mut current_minimum_capacity = self.minimum_capacity().load(Relaxed);
         loop {
-            let Some(new_minimum_capacity) =
-                current_minimum_capacity.checked_add(additional_capacity)
+            let Some(new_minimum_capacity) = current_minimum_capacity
+                .checked_add(additional_capacity)
+                .filter(|c| *c <= self.maximum_capacity())
             else {
                 return 0;
             };
@@ -179,6 +205,33 @@ where
         }
     }
 
+    /// Returns the estimated number of bytes allocated for the bucket arrays.
+    #[inline]
+    fn memory_usage(&self, guard: &Guard) -> usize {
+        if let Some(current_array) = self.bucket_array().load(Acquire, guard).as_ref() {
+            let old_array_ptr = current_array.old_array(guard);
+            let old_array_usage = old_array_ptr
+                .as_ref()
+                .map_or(0, |old_array| old_array.memory_usage(guard));
+            current_array.memory_usage(guard) + old_array_usage
+        } else {
+            0
+        }
+    }
+
+    /// Returns the number of entries in each bucket of the current bucket array.
+    #[inline]
+    fn bucket_occupancy(&self, guard: &Guard) -> Vec<usize> {
+        self.bucket_array()
+            .load(Acquire, guard)
+            .as_ref()
+            .map_or_else(Vec::new, |current_array| {
+                (0..current_array.num_buckets())
+                    .map(|i| current_array.bucket(i).num_entries())
+                    .collect()
+            })
+    }
+
     /// Estimates the number of entries by sampling the specified number of buckets.
     #[inline]
     fn sample(
@@ -483,69 +536,155 @@ where
         Ok(post_processor(None))
     }
 
-    /// Finds any entry that satisfies the specified predicate.
+    /// Visits each [`Bucket`] in the current bucket array, and in a new one if the bucket array is
+    /// replaced in the meantime.
+    ///
+    /// `visit` is invoked with a bucket array, the index of a [`Bucket`] in it, and a hash value
+    /// below which entries in the [`Bucket`] must be skipped as they have already been visited; it
+    /// returns `None` if the [`Bucket`] could not be locked, and `Some(true)` to stop the
+    /// traversal. Returns `true` if the traversal was stopped.
     #[inline]
-    fn any_entry<P: FnMut(&K, &V) -> bool>(&self, mut pred: P) -> bool {
-        let guard = Guard::new();
-        let mut current_array_ptr = self.bucket_array().load(Acquire, &guard);
-        while let Some(current_array) = current_array_ptr.as_ref() {
-            self.clear_old_array(current_array, &guard);
-            for index in 0..current_array.num_buckets() {
-                let bucket = current_array.bucket(index);
-                if let Some(locker) = Reader::lock(bucket, &guard) {
-                    let data_block = current_array.data_block(index);
-                    let mut entry_ptr = EntryPtr::new(&guard);
-                    while entry_ptr.next(*locker, &guard) {
-                        let (k, v) = entry_ptr.get(data_block);
-                        if pred(k, v) {
-                            return true;
+    fn for_each_bucket<'g, F>(&self, guard: &'g Guard, mut visit: F) -> bool
+    where
+        K: 'g,
+        V: 'g,
+        F: FnMut(&'g BucketArray<K, V, TYPE>, usize, u64) -> Option<bool>,
+    {
+        // Entries having a hash value less than `visited` have been visited; `Bucket` instances
+        // are sorted by hash values, and each entry is relocated to the `Bucket` corresponding to
+        // the hash value when the array is resized.
+        let mut visited = 0;
+        let mut current_array_ptr = self.bucket_array().load(Acquire, guard);
+        'array: while let Some(current_array) = current_array_ptr.as_ref() {
+            self.clear_old_array(current_array, guard);
+            let start_index = current_array.calculate_bucket_index(visited);
+            for index in start_index..current_array.num_buckets() {
+                let skip_below = if index == start_index { visited } else { 0 };
+                match visit(current_array, index, skip_below) {
+                    Some(true) => return true,
+                    Some(false) => (),
+                    None => {
+                        // The `Bucket` is either empty or killed: a killed `Bucket` indicates that
+                        // the array has been replaced with a new one.
+                        let new_current_array_ptr = self.bucket_array().load(Acquire, guard);
+                        if current_array_ptr.without_tag() != new_current_array_ptr.without_tag() {
+                            visited = visited.max(current_array.min_hash(index));
+                            current_array_ptr = new_current_array_ptr;
+                            continue 'array;
                         }
                     }
                 }
             }
-
-            let new_current_array_ptr = self.bucket_array().load(Acquire, &guard);
-            if current_array_ptr.without_tag() == new_current_array_ptr.without_tag() {
-                break;
-            }
-            current_array_ptr = new_current_array_ptr;
+            break;
         }
         false
     }
 
+    /// Finds any entry that satisfies the specified predicate.
+    #[inline]
+    fn any_entry<P: FnMut(&K, &V) -> bool>(&self, mut pred: P) -> bool {
+        let guard = Guard::new();
+        self.for_each_bucket(&guard, |current_array, index, skip_below| {
+            let locker = Reader::lock(current_array.bucket(index), &guard)?;
+            let data_block = current_array.data_block(index);
+            let mut entry_ptr = EntryPtr::new(&guard);
+            while entry_ptr.next(*locker, &guard) {
+                let (k, v) = entry_ptr.get(data_block);
+                if skip_below != 0 && self.hash(k) < skip_below {
+                    continue;
+                }
+                if pred(k, v) {
+                    return Some(true);
+                }
+            }
+            Some(false)
+        })
+    }
+
     /// Retains entries that satisfy the specified predicate.
     #[inline]
     fn retain_entries<F: FnMut(&K, &mut V) -> bool>(&self, mut pred: F) {
         let guard = Guard::new();
         let mut removed = false;
-        let mut current_array_ptr = self.bucket_array().load(Acquire, &guard);
-        while let Some(current_array) = current_array_ptr.as_ref() {
-            self.clear_old_array(current_array, &guard);
-            for index in 0..current_array.num_buckets() {
-                let bucket = current_array.bucket_mut(index);
-                if let Some(mut locker) = Locker::lock(bucket, &guard) {
-                    let data_block_mut = current_array.data_block_mut(index);
-                    let mut entry_ptr = EntryPtr::new(&guard);
-                    while entry_ptr.next(&locker, &guard) {
-                        let (k, v) = entry_ptr.get_mut(data_block_mut, &mut locker);
-                        if !pred(k, v) {
-                            locker.erase(data_block_mut, &entry_ptr);
-                            removed = true;
-                        }
-                    }
+        self.for_each_bucket(&guard, |current_array, index, skip_below| {
+            let mut locker = Locker::lock(current_array.bucket_mut(index), &guard)?;
+            let data_block_mut = current_array.data_block_mut(index);
+            let mut entry_ptr = EntryPtr::new(&guard);
+            while entry_ptr.next(&locker, &guard) {
+                let (k, v) = entry_ptr.get_mut(data_block_mut, &mut locker);
+                if skip_below != 0 && self.hash(k) < skip_below {
+                    continue;
+                }
+                if !pred(k, v) {
+                    locker.erase(data_block_mut, &entry_ptr);
+                    removed = true;
                 }
             }
+            Some(false)
+        });
 
-            let new_current_array_ptr = self.bucket_array().load(Acquire, &guard);
-            if current_array_ptr.without_tag() == new_current_array_ptr.without_tag() {
-                break;
-            }
-            current_array_ptr = new_current_array_ptr;
+        if removed {
+            self.try_resize(0, &guard);
         }
+    }
 
-        if removed {
+    /// Removes all the entries and returns them.
+    #[inline]
+    fn drain_entries(&self) -> Vec<(K, V)> {
+        self.extract_entries(|_, _| true)
+    }
+
+    /// Removes entries satisfying the predicate and returns them.
+    ///
+    /// Each entry is returned at most once even if the container is resized by another thread.
+    #[inline]
+    fn extract_entries<F: FnMut(&K, &V) -> bool>(&self, mut pred: F) -> Vec<(K, V)> {
+        let guard = Guard::new();
+        let mut drained = Vec::new();
+        self.for_each_bucket(&guard, |current_array, index, skip_below| {
+            let mut locker = Locker::lock(current_array.bucket_mut(index), &guard)?;
+            let data_block_mut = current_array.data_block_mut(index);
+            let mut entry_ptr = EntryPtr::new(&guard);
+            while entry_ptr.next(&locker, &guard) {
+                let (k, v) = entry_ptr.get(data_block_mut);
+                if skip_below != 0 && self.hash(k) < skip_below {
+                    continue;
+                }
+                if !pred(k, v) {
+                    continue;
+                }
+                if let Some(entry) = locker.erase(data_block_mut, &entry_ptr) {
+                    drained.push(entry);
+                }
+            }
+            Some(false)
+        });
+
+        if !drained.is_empty() {
             self.try_resize(0, &guard);
         }
+        drained
+    }
+
+    /// Replaces every value with the value returned by `transform`.
+    ///
+    /// Each entry is visited exactly once even if the container is resized by another thread.
+    #[inline]
+    fn transform_entries<F: FnMut(&K, V) -> V>(&self, mut transform: F) {
+        let mut transform = |k: &K, v: V| Some(transform(k, v));
+        let guard = Guard::new();
+        self.for_each_bucket(&guard, |current_array, index, skip_below| {
+            let mut locker = Locker::lock(current_array.bucket_mut(index), &guard)?;
+            let data_block_mut = current_array.data_block_mut(index);
+            let mut entry_ptr = EntryPtr::new(&guard);
+            while entry_ptr.next(&locker, &guard) {
+                if skip_below != 0 && self.hash(&entry_ptr.get(data_block_mut).0) < skip_below {
+                    continue;
+                }
+                locker.keep_or_consume(data_block_mut, &entry_ptr, &mut transform);
+            }
+            Some(false)
+        });
     }
 
     /// Prunes entries satisfying the predicate.
@@ -553,28 +692,20 @@ where
     fn prune_entries<F: FnMut(&K, V) -> Option<V>>(&self, mut pred: F) {
         let guard = Guard::new();
         let mut removed = false;
-        let mut current_array_ptr = self.bucket_array().load(Acquire, &guard);
-        while let Some(current_array) = current_array_ptr.as_ref() {
-            self.clear_old_array(current_array, &guard);
-            for index in 0..current_array.num_buckets() {
-                let bucket = current_array.bucket_mut(index);
-                if let Some(mut locker) = Locker::lock(bucket, &guard) {
-                    let data_block_mut = current_array.data_block_mut(index);
-                    let mut entry_ptr = EntryPtr::new(&guard);
-                    while entry_ptr.next(&locker, &guard) {
-                        if locker.keep_or_consume(data_block_mut, &entry_ptr, &mut pred) {
-                            removed = true;
-                        }
-                    }
+        self.for_each_bucket(&guard, |current_array, index, skip_below| {
+            let mut locker = Locker::lock(current_array.bucket_mut(index), &guard)?;
+            let data_block_mut = current_array.data_block_mut(index);
+            let mut entry_ptr = EntryPtr::new(&guard);
+            while entry_ptr.next(&locker, &guard) {
+                if skip_below != 0 && self.hash(&entry_ptr.get(data_block_mut).0) < skip_below {
+                    continue;
+                }
+                if locker.keep_or_consume(data_block_mut, &entry_ptr, &mut pred) {
+                    removed = true;
                 }
             }
-
-            let new_current_array_ptr = self.bucket_array().load(Acquire, &guard);
-            if current_array_ptr.without_tag() == new_current_array_ptr.without_tag() {
-                break;
-            }
-            current_array_ptr = new_current_array_ptr;
-        }
+            Some(false)
+        });
 
         if removed {
             self.try_resize(0, &guard);
@@ -611,6 +742,7 @@ where
         //    of `self.array`, thus competing with each other.
         //  2. The thread reads the latest version of `self.array`.
         //    If the array is deprecated while inserting the key, it falls into case 1.
+        let mut grow_attempted = false;
         loop {
             let current_array = self.get_current_array(guard);
             let resizable = if let Some(old_array) = current_array.old_array(guard).as_ref() {
@@ -627,7 +759,7 @@ where
             if resizable
                 && (TYPE != CACHE || current_array.num_entries() < self.maximum_capacity())
                 && current_array.within_sampling_range(index)
-                && bucket.num_entries() >= BUCKET_LEN - 1
+                && bucket.num_entries() >= self.resize_policy().enlarge_trigger()
             {
                 self.try_enlarge(current_array, index, bucket.num_entries(), guard);
             }
@@ -645,6 +777,23 @@ where
                     BucketArray::<K, V, TYPE>::partial_hash(hash),
                     guard,
                 );
+                if resizable
+                    && !grow_attempted
+                    && TYPE != CACHE
+                    && !entry_ptr.is_valid()
+                    && self
+                        .resize_policy()
+                        .link_depth_exceeded(locker.num_entries())
+                    && self.split_on_growth(current_array, &locker, data_block_mut, guard)
+                {
+                    // The linked list of the bucket is too long, and growing the array shortens it.
+                    // The array is grown after the lock is released, and then the key is looked
+                    // up again.
+                    drop(locker);
+                    grow_attempted = true;
+                    self.try_resize_or_grow(index, true, guard);
+                    continue;
+                }
                 return Ok(LockedEntry {
                     locker,
                     data_block_mut,
@@ -777,7 +926,7 @@ where
                     //
                     // No panic handling required as relocation of a bucket is infallible if
                     // `TYPE = CACHE`
-                    drop(old_locker.extract(old_data_block_mut, &mut entry_ptr, guard));
+                    self.dispose(old_locker.extract(old_data_block_mut, &mut entry_ptr, guard));
                     continue;
                 }
 
@@ -932,7 +1081,7 @@ where
         guard: &Guard,
     ) {
         let sample_size = current_array.sample_size();
-        let threshold = sample_size * (BUCKET_LEN / 8) * 7;
+        let threshold = self.resize_policy().grow_at(sample_size * BUCKET_LEN);
         if num_entries > threshold
             || (1..sample_size).any(|i| {
                 num_entries += current_array
@@ -960,7 +1109,7 @@ where
             || TYPE == OPTIMISTIC
         {
             let sample_size = current_array.sample_size();
-            let shrink_threshold = sample_size * BUCKET_LEN / 16;
+            let shrink_threshold = self.resize_policy().shrink_at(sample_size * BUCKET_LEN);
             let rebuild_threshold = sample_size / 2;
             let mut num_entries = 0;
             let mut num_buckets_to_rebuild = 0;
@@ -988,8 +1137,45 @@ where
         }
     }
 
+    /// Returns `true` if entries in the locked bucket are distributed over different buckets when
+    /// the array is doubled.
+    #[inline]
+    fn split_on_growth(
+        &self,
+        current_array: &BucketArray<K, V, TYPE>,
+        locker: &Locker<K, V, TYPE>,
+        data_block: &DataBlock<K, V, BUCKET_LEN>,
+        guard: &Guard,
+    ) -> bool {
+        let mut first_hash = None;
+        let mut entry_ptr = EntryPtr::new(guard);
+        while entry_ptr.next(locker, guard) {
+            let hash = self.hash(&entry_ptr.get(data_block).0);
+            if let Some(first_hash) = first_hash {
+                if current_array.split_on_growth(first_hash, hash) {
+                    return true;
+                }
+            } else {
+                first_hash.replace(hash);
+            }
+        }
+        false
+    }
+
     /// Tries to resize the array.
+    #[inline]
     fn try_resize(&self, sampling_index: usize, guard: &Guard) {
+        self.try_resize_or_grow(sampling_index, false, guard);
+    }
+
+    /// Tries to resize the array, or to grow the array regardless of the load factor if
+    /// `force_grow` is `true`.
+    #[allow(clippy::too_many_lines)]
+    fn try_resize_or_grow(&self, sampling_index: usize, force_grow: bool, guard: &Guard) {
+        if self.resize_policy().resize_disabled {
+            return;
+        }
+
         let current_array_ptr = self.bucket_array().load(Acquire, guard);
         if current_array_ptr.tag() != Tag::None {
             // Another thread is currently allocating a new bucket array.
@@ -1002,40 +1188,48 @@ where
                 return;
             }
 
-            // The resizing policies are as follows.
-            //  - `The estimated load factor >= 7/8`, then the hash table grows up to `32x`.
-            //  - `The estimated load factor <= 1/16`, then the hash table shrinks to fit.
+            // The resizing policies are as follows; see `ResizePolicy` for the default values.
+            //  - `The estimated load factor >= grow_threshold`, then the hash table grows up to
+            //    `max_growth_factor`.
+            //  - `The estimated load factor <= shrink_threshold`, then the hash table shrinks to
+            //    fit.
+            let resize_policy = self.resize_policy();
             let minimum_capacity = self.minimum_capacity().load(Relaxed);
             let capacity = current_array.num_entries();
             let sample_size = current_array.full_sample_size();
             let estimated_num_entries = Self::sample(current_array, sampling_index, sample_size);
-            let new_capacity = if estimated_num_entries >= (capacity / 8) * 7 {
-                if capacity == self.maximum_capacity() {
-                    // Do not resize if the capacity cannot be increased.
-                    capacity
-                } else {
-                    let mut new_capacity = capacity;
-                    while new_capacity <= (estimated_num_entries / 8) * 15 {
-                        // Double `new_capacity` until the expected load factor is below 0.5.
-                        if new_capacity == self.maximum_capacity() {
-                            break;
-                        }
-                        if new_capacity / capacity == 32 {
-                            break;
+            let new_capacity =
+                if force_grow || estimated_num_entries >= resize_policy.grow_at(capacity) {
+                    if capacity == self.maximum_capacity() {
+                        // Do not resize if the capacity cannot be increased.
+                        capacity
+                    } else {
+                        let mut new_capacity = capacity;
+                        while new_capacity == capacity
+                            || new_capacity <= resize_policy.grow_target(estimated_num_entries)
+                        {
+                            // Double `new_capacity` until the expected load factor is sufficiently
+                            // below the grow threshold.
+                            if new_capacity == self.maximum_capacity() {
+                                break;
+                            }
+                            if new_capacity / capacity == resize_policy.max_growth_factor {
+                                break;
+                            }
+                            new_capacity *= 2;
                         }
-                        new_capacity *= 2;
+                        new_capacity
                     }
-                    new_capacity
-                }
-            } else if estimated_num_entries <= capacity / 16 {
-                // Shrink to fit.
-                estimated_num_entries
-                    .max(minimum_capacity)
-                    .max(BucketArray::<K, V, TYPE>::minimum_capacity())
-                    .next_power_of_two()
-            } else {
-                capacity
-            };
+                } else if estimated_num_entries <= resize_policy.shrink_at(capacity) {
+                    // Shrink to fit.
+                    estimated_num_entries
+                        .max(minimum_capacity)
+                        .max(BucketArray::<K, V, TYPE>::minimum_capacity())
+                        .min(self.maximum_capacity())
+                        .next_power_of_two()
+                } else {
+                    capacity
+                };
 
             let try_resize = new_capacity != capacity;
             let try_drop_table = estimated_num_entries == 0 && minimum_capacity == 0;
@@ -1102,12 +1296,24 @@ where
                     }
                 });
                 if try_resize || try_rebuild {
-                    mutex_guard.replace(unsafe {
-                        Shared::new_unchecked(BucketArray::<K, V, TYPE>::new(
+                    let mut new_capacity = new_capacity;
+                    loop {
+                        if let Ok(new_array) = BucketArray::<K, V, TYPE>::try_new(
                             new_capacity,
                             self.bucket_array().clone(Relaxed, guard),
-                        ))
-                    });
+                        ) {
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!(capacity, new_capacity, "reallocating buckets");
+                            mutex_guard.replace(unsafe { Shared::new_unchecked(new_array) });
+                            break;
+                        }
+                        if new_capacity / 2 <= capacity {
+                            // Keep using the current array if memory is insufficient.
+                            break;
+                        }
+                        // Retry growing with a smaller capacity.
+                        new_capacity /= 2;
+                    }
                 }
             }
         }
@@ -1120,6 +1326,255 @@ where
     }
 }
 
+/// [`ResizePolicy`] determines when and how much a hash table is resized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ResizePolicy {
+    /// The hash table grows if the estimated load factor reaches the value.
+    grow_threshold: f32,
+
+    /// The hash table shrinks if the estimated load factor drops to the value.
+    shrink_threshold: f32,
+
+    /// The maximum ratio of the new capacity to the current capacity when growing.
+    max_growth_factor: usize,
+
+    /// The maximum number of linked buckets attached to a bucket before the hash table is forced
+    /// to grow.
+    max_link_depth: usize,
+
+    /// The bucket array is never reallocated once allocated.
+    resize_disabled: bool,
+}
+
+impl ResizePolicy {
+    /// The default [`ResizePolicy`]: grows at `7/8` up to `32x`, and shrinks at `1/16`.
+    pub(crate) const DEFAULT: Self = Self {
+        grow_threshold: 0.875,
+        shrink_threshold: 0.0625,
+        max_growth_factor: 32,
+        max_link_depth: usize::MAX,
+        resize_disabled: false,
+    };
+
+    /// Returns a new [`ResizePolicy`] with the load factor thresholds replaced.
+    ///
+    /// A bucket array grows at least twice, therefore `shrink_threshold` must be less than half of
+    /// `grow_threshold`, otherwise a newly grown bucket array would immediately be shrunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `0 < shrink_threshold < grow_threshold / 2` or `grow_threshold < 1` does not hold.
+    pub(crate) fn with_thresholds(self, grow_threshold: f32, shrink_threshold: f32) -> Self {
+        assert!(
+            shrink_threshold > 0.0
+                && shrink_threshold < grow_threshold / 2.0
+                && grow_threshold < 1.0,
+            "0 < shrink_threshold < grow_threshold / 2 and grow_threshold < 1 must hold"
+        );
+        Self {
+            grow_threshold,
+            shrink_threshold,
+            ..self
+        }
+    }
+
+    /// Returns a new [`ResizePolicy`] with the maximum growth factor replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_growth_factor` is not a power of two greater than `1`.
+    pub(crate) fn with_max_growth_factor(self, max_growth_factor: usize) -> Self {
+        assert!(
+            max_growth_factor > 1 && max_growth_factor.is_power_of_two(),
+            "max_growth_factor must be a power of two greater than 1"
+        );
+        Self {
+            max_growth_factor,
+            ..self
+        }
+    }
+
+    /// Returns a new [`ResizePolicy`] with the maximum link depth replaced.
+    pub(crate) fn with_max_link_depth(self, max_link_depth: usize) -> Self {
+        Self {
+            max_link_depth,
+            ..self
+        }
+    }
+
+    /// Returns a new [`ResizePolicy`] that disables resizing.
+    pub(crate) fn with_resize_disabled(self) -> Self {
+        Self {
+            resize_disabled: true,
+            ..self
+        }
+    }
+
+    /// Returns `true` if inserting an entry into a `Bucket` containing the specified number of
+    /// entries needs more linked buckets than allowed.
+    fn link_depth_exceeded(self, num_entries: usize) -> bool {
+        num_entries
+            >= self
+                .max_link_depth
+                .saturating_mul(LINKED_BUCKET_LEN)
+                .saturating_add(BUCKET_LEN)
+    }
+
+    /// Returns the number of entries in a `Bucket` which triggers sampling for growth.
+    ///
+    /// It is `BUCKET_LEN - 1` for the default grow threshold.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn enlarge_trigger(self) -> usize {
+        let trigger = (BUCKET_LEN as f32 * (self.grow_threshold + 0.125)).ceil() as usize;
+        trigger.clamp(1, BUCKET_LEN) - 1
+    }
+
+    /// Returns the number of entries in the given capacity at which the hash table grows.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn grow_at(self, capacity: usize) -> usize {
+        (capacity as f64 * f64::from(self.grow_threshold)) as usize
+    }
+
+    /// Returns the capacity that the hash table has to exceed when growing.
+    ///
+    /// It is `15/8` of the number of entries for the default grow threshold, making the expected
+    /// load factor below `0.5`, and proportionally larger for a lower grow threshold.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn grow_target(self, num_entries: usize) -> usize {
+        let target = (num_entries / 8) * 15;
+        if self.grow_threshold == Self::DEFAULT.grow_threshold {
+            return target;
+        }
+        let ratio = f64::from(Self::DEFAULT.grow_threshold) / f64::from(self.grow_threshold);
+        (target as f64 * ratio) as usize
+    }
+
+    /// Returns the number of entries in the given capacity at which the hash table shrinks.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn shrink_at(self, capacity: usize) -> usize {
+        (capacity as f64 * f64::from(self.shrink_threshold)) as usize
+    }
+}
+
+impl Default for ResizePolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Mixes the bits of the hash value.
+///
+/// It is the `rrxmrrxmsx_0` finalizer from
+/// <https://mostlymangling.blogspot.com/2019/01/better-stronger-mixer-and-test-procedure.html>
+/// which makes every bit of the output depend on every bit of the input, so that both the bucket
+/// index derived from the upper bits and the partial hash derived from the lower bits are well
+/// distributed even if the supplied hasher is weak.
+#[inline]
+pub(crate) const fn mix(mut hash: u64) -> u64 {
+    hash ^= hash.rotate_right(25) ^ hash.rotate_right(50);
+    hash = hash.wrapping_mul(0xA24B_AED4_963E_E407);
+    hash ^= hash.rotate_right(24) ^ hash.rotate_right(49);
+    hash = hash.wrapping_mul(0x9FB2_1C65_1E98_DF25);
+    hash ^ (hash >> 28)
+}
+
+/// Visits each [`Bucket`] asynchronously - see [`HashTable::for_each_bucket`].
+///
+/// `visit` returns an error if the [`Bucket`] is locked, and it is invoked again after
+/// `async_wait` is awakened.
+pub(super) async fn for_each_bucket_async<K, V, H, T, F, const TYPE: char>(
+    hash_table: &T,
+    mut visit: F,
+) -> bool
+where
+    K: Eq + Hash,
+    H: BuildHasher,
+    T: HashTable<K, V, H, TYPE>,
+    F: for<'g> FnMut(
+        &'g BucketArray<K, V, TYPE>,
+        usize,
+        u64,
+        &mut AsyncWait,
+        &'g Guard,
+    ) -> Result<Option<bool>, ()>,
+{
+    // Entries having a hash value less than `visited` have been visited - see
+    // `HashTable::for_each_bucket`.
+    let mut visited = 0;
+    let mut current_array_holder = hash_table.bucket_array().get_shared(Acquire, &Guard::new());
+    'array: while let Some(current_array) = current_array_holder.take() {
+        while current_array.has_old_array() {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if hash_table.incremental_rehash::<_, _, false>(
+                current_array.as_ref(),
+                &mut async_wait_pinned,
+                &Guard::new(),
+            ) == Ok(true)
+            {
+                break;
+            }
+            async_wait_pinned.await;
+        }
+        let start_index = current_array.calculate_bucket_index(visited);
+        for index in start_index..current_array.num_buckets() {
+            let skip_below = if index == start_index { visited } else { 0 };
+            loop {
+                let mut async_wait = AsyncWait::default();
+                let mut async_wait_pinned = Pin::new(&mut async_wait);
+                {
+                    let guard = Guard::new();
+                    if let Ok(result) = visit(
+                        &current_array,
+                        index,
+                        skip_below,
+                        &mut async_wait_pinned,
+                        &guard,
+                    ) {
+                        match result {
+                            Some(true) => return true,
+                            Some(false) => (),
+                            None => {
+                                if let Some(new_current_array) =
+                                    hash_table.bucket_array().get_shared(Acquire, &guard)
+                                {
+                                    if new_current_array.as_ptr() != current_array.as_ptr() {
+                                        // The `Bucket` has been killed.
+                                        visited = visited.max(current_array.min_hash(index));
+                                        current_array_holder.replace(new_current_array);
+                                        continue 'array;
+                                    }
+                                }
+                            }
+                        }
+                        break;
+                    }
+                }
+                async_wait_pinned.await;
+            }
+        }
+        break;
+    }
+    false
+}
+
 /// [`LockedEntry`] comprises pieces of data that are required for exclusive access to an entry.
 pub(super) struct LockedEntry<'h, K: Eq + Hash, V, const TYPE: char> {
     /// The [`Locker`] holding the exclusive lock on the bucket.
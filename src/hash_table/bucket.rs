@@ -2,7 +2,7 @@ use crate::ebr::{AtomicShared, Guard, Ptr, Shared, Tag};
 use crate::wait_queue::{AsyncWait, WaitQueue};
 use std::borrow::Borrow;
 use std::fmt::{self, Debug};
-use std::mem::{needs_drop, MaybeUninit};
+use std::mem::{self, needs_drop, size_of, MaybeUninit};
 use std::ops::{Deref, DerefMut};
 use std::ptr;
 use std::sync::atomic::AtomicPtr;
@@ -65,11 +65,17 @@ pub struct Evictable<V> {
     /// The actual value.
     value: V,
 
+    /// The weight of the entry computed when the value was put.
+    weight: usize,
+
     /// A link to the previous recently used entry.
     prev: u8,
 
     /// A link to the  next recently used entry.
     next: u8,
+
+    /// The saturating number of accesses to the entry.
+    frequency: u8,
 }
 
 /// [`Metadata`] is a collection of metadata fields of [`Bucket`] and [`LinkedBucket`].
@@ -92,7 +98,7 @@ pub(crate) struct Metadata<K: Eq, V, const LEN: usize> {
 }
 
 /// The size of the linked data block.
-const LINKED_BUCKET_LEN: usize = BUCKET_LEN / 4;
+pub(crate) const LINKED_BUCKET_LEN: usize = BUCKET_LEN / 4;
 
 /// State bits.
 const KILLED: u32 = 1_u32 << 31;
@@ -108,6 +114,19 @@ impl<K: Eq, V, const TYPE: char> Bucket<K, V, TYPE> {
         self.num_entries as usize
     }
 
+    /// Returns the number of bytes allocated for the [`LinkedBucket`] instances attached to the
+    /// [`Bucket`].
+    #[inline]
+    pub(crate) fn linked_memory_usage(&self, guard: &Guard) -> usize {
+        let mut num_linked_buckets = 0;
+        let mut link_ptr = self.metadata.link.load(Acquire, guard);
+        while let Some(link) = link_ptr.as_ref() {
+            num_linked_buckets += 1;
+            link_ptr = link.metadata.link.load(Acquire, guard);
+        }
+        num_linked_buckets * size_of::<LinkedBucket<K, V, LINKED_BUCKET_LEN>>()
+    }
+
     /// Returns `true` if the [`Bucket`] needs to be rebuilt.
     ///
     /// If `LOCK_FREE == true`, removed entries are not dropped, still occupying the slots,
@@ -842,38 +861,114 @@ impl<'g, K: Eq, V, const TYPE: char> Locker<'g, K, V, TYPE> {
 
 impl<'g, K: Eq, V> Locker<'g, K, Evictable<V>, CACHE> {
     /// Evicts the least recently used entry if the [`Bucket`] is full.
-    #[allow(clippy::cast_possible_truncation)]
     pub(crate) fn evict_lru_head(
         &mut self,
         data_block: &mut DataBlock<K, Evictable<V>, BUCKET_LEN>,
+    ) -> Option<(K, Evictable<V>)> {
+        if self.num_entries() == BUCKET_LEN {
+            return self.evict_lru(data_block);
+        }
+        None
+    }
+
+    /// Evicts the least recently used entry.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn evict_lru(
+        &mut self,
+        data_block: &mut DataBlock<K, Evictable<V>, BUCKET_LEN>,
     ) -> Option<(K, Evictable<V>)> {
         debug_assert!(self.metadata.link.is_null(Relaxed));
 
+        if self.num_entries() == 0 {
+            return None;
+        }
+
+        self.num_entries -= 1;
+        if self.metadata.removed_bitmap_or_lru_tail == 0 {
+            // Evict the first occupied entry.
+            let index = self.metadata.occupied_bitmap.trailing_zeros() as usize;
+            debug_assert!(index < BUCKET_LEN);
+            self.metadata.occupied_bitmap &= !(1_u32 << index);
+            return Some(unsafe { data_block[index].as_mut_ptr().read() });
+        }
+
+        let head_index = self.metadata.removed_bitmap_or_lru_tail as usize - 1;
+        let (_, head) = unsafe { &mut *data_block[head_index].as_mut_ptr() };
+        let lru_index = head.prev as usize - 1;
+        let (k, v) = unsafe { data_block[lru_index].as_mut_ptr().read() };
+        let (_, new_lru) = unsafe { &mut *data_block[v.prev as usize - 1].as_mut_ptr() };
+        new_lru.next = self.metadata.removed_bitmap_or_lru_tail as u8;
+        head.prev = v.prev;
+        self.metadata.occupied_bitmap &= !(1_u32 << lru_index);
+
+        if self.metadata.removed_bitmap_or_lru_tail as usize == head.prev as usize {
+            self.metadata.removed_bitmap_or_lru_tail = 0;
+        }
+
+        Some((k, v))
+    }
+
+    /// Evicts the least frequently used entry if the [`Bucket`] is full.
+    pub(crate) fn evict_lfu_head(
+        &mut self,
+        data_block: &mut DataBlock<K, Evictable<V>, BUCKET_LEN>,
+    ) -> Option<(K, Evictable<V>)> {
         if self.num_entries() == BUCKET_LEN {
-            self.num_entries -= 1;
-            if self.metadata.removed_bitmap_or_lru_tail == 0 {
-                // Evict the first occupied entry.
-                debug_assert_ne!(self.metadata.occupied_bitmap & 1_u32, 0);
-                self.metadata.occupied_bitmap &= !1_u32;
-                return Some(unsafe { data_block[0].as_mut_ptr().read() });
-            }
+            return self.evict_lfu(data_block);
+        }
+        None
+    }
+
+    /// Evicts the least frequently used entry.
+    ///
+    /// The least recently used one is evicted among those that are accessed least frequently.
+    pub(crate) fn evict_lfu(
+        &mut self,
+        data_block: &mut DataBlock<K, Evictable<V>, BUCKET_LEN>,
+    ) -> Option<(K, Evictable<V>)> {
+        debug_assert!(self.metadata.link.is_null(Relaxed));
+
+        if self.num_entries() == 0 {
+            return None;
+        }
 
+        // Entries not in the linked list are considered older than any other entries.
+        let mut victim = BUCKET_LEN;
+        let mut min_frequency = u16::MAX;
+        let mut bitmap = self.metadata.occupied_bitmap;
+        while bitmap != 0 {
+            let index = bitmap.trailing_zeros() as usize;
+            bitmap &= !(1_u32 << index);
+            let (_, evictable) = unsafe { &*data_block[index].as_ptr() };
+            if evictable.prev == 0 && u16::from(evictable.frequency) < min_frequency {
+                victim = index;
+                min_frequency = u16::from(evictable.frequency);
+            }
+        }
+        if self.metadata.removed_bitmap_or_lru_tail != 0 {
+            // Traverse the linked list from the least recently used entry.
             let head_index = self.metadata.removed_bitmap_or_lru_tail as usize - 1;
-            let (_, head) = unsafe { &mut *data_block[head_index].as_mut_ptr() };
-            let lru_index = head.prev as usize - 1;
-            let (k, v) = unsafe { data_block[lru_index].as_mut_ptr().read() };
-            let (_, new_lru) = unsafe { &mut *data_block[v.prev as usize - 1].as_mut_ptr() };
-            new_lru.next = self.metadata.removed_bitmap_or_lru_tail as u8;
-            head.prev = v.prev;
-            self.metadata.occupied_bitmap &= !(1_u32 << lru_index);
-
-            if self.metadata.removed_bitmap_or_lru_tail as usize == head.prev as usize {
-                self.metadata.removed_bitmap_or_lru_tail = 0;
+            let mut index = unsafe { (*data_block[head_index].as_ptr()).1.prev as usize - 1 };
+            loop {
+                let (_, evictable) = unsafe { &*data_block[index].as_ptr() };
+                if u16::from(evictable.frequency) < min_frequency {
+                    victim = index;
+                    min_frequency = u16::from(evictable.frequency);
+                }
+                if index == head_index {
+                    break;
+                }
+                index = evictable.prev as usize - 1;
             }
-
-            return Some((k, v));
         }
-        None
+        debug_assert!(victim < BUCKET_LEN);
+
+        let entry_ptr = EntryPtr {
+            current_link_ptr: Ptr::null(),
+            current_index: victim,
+        };
+        self.update_lru_head(data_block, &entry_ptr);
+        self.evict_lru(data_block)
     }
 
     /// Removes the entry from the LRU linked list.
@@ -919,6 +1014,33 @@ impl<'g, K: Eq, V> Locker<'g, K, Evictable<V>, CACHE> {
         }
     }
 
+    /// Hides the entry from eviction until [`Self::reveal`] is called.
+    ///
+    /// The entry is removed from the LRU linked list, and its slot is temporarily marked vacant;
+    /// no entries must be inserted into the [`Bucket`] before the entry is revealed.
+    pub(crate) fn hide(
+        &mut self,
+        data_block: &mut DataBlock<K, Evictable<V>, BUCKET_LEN>,
+        entry_ptr: &EntryPtr<K, Evictable<V>, CACHE>,
+    ) {
+        let entry_index = entry_ptr.current_index;
+        self.remove_from_lru_list(data_block, entry_ptr);
+        unsafe { (*data_block[entry_index].as_mut_ptr()).1.reset_link() };
+        self.metadata.occupied_bitmap &= !(1_u32 << entry_index);
+        self.num_entries -= 1;
+    }
+
+    /// Reveals the hidden entry, and sets it having been just accessed.
+    pub(crate) fn reveal(
+        &mut self,
+        data_block: &mut DataBlock<K, Evictable<V>, BUCKET_LEN>,
+        entry_ptr: &EntryPtr<K, Evictable<V>, CACHE>,
+    ) {
+        self.metadata.occupied_bitmap |= 1_u32 << entry_ptr.current_index;
+        self.num_entries += 1;
+        self.update_lru_tail(data_block, entry_ptr);
+    }
+
     /// Sets the entry having been just accessed.
     #[allow(clippy::cast_possible_truncation)]
     pub(crate) fn update_lru_tail(
@@ -929,6 +1051,9 @@ impl<'g, K: Eq, V> Locker<'g, K, Evictable<V>, CACHE> {
         debug_assert!(self.metadata.link.is_null(Relaxed));
 
         let entry_index = entry_ptr.current_index;
+        let (_, current) = unsafe { &mut *data_block[entry_index].as_mut_ptr() };
+        current.frequency = current.frequency.saturating_add(1);
+
         if self.metadata.removed_bitmap_or_lru_tail as usize == entry_index + 1 {
             // Already the head of the linked list.
             return;
@@ -973,6 +1098,19 @@ impl<'g, K: Eq, V> Locker<'g, K, Evictable<V>, CACHE> {
         // Update `head`.
         self.metadata.removed_bitmap_or_lru_tail = (entry_index + 1) as u32;
     }
+
+    /// Sets the entry to be the first candidate for eviction.
+    pub(crate) fn update_lru_head(
+        &mut self,
+        data_block: &mut DataBlock<K, Evictable<V>, BUCKET_LEN>,
+        entry_ptr: &EntryPtr<K, Evictable<V>, CACHE>,
+    ) {
+        self.update_lru_tail(data_block, entry_ptr);
+
+        // Rotate the circular linked list to make the entry the least recently used one.
+        let (_, current) = unsafe { &*data_block[entry_ptr.current_index].as_ptr() };
+        self.metadata.removed_bitmap_or_lru_tail = u32::from(current.next);
+    }
 }
 
 impl<'g, K: Eq, V, const TYPE: char> Deref for Locker<'g, K, V, TYPE> {
@@ -1172,11 +1310,13 @@ impl<K: Eq, V, const LEN: usize> Drop for LinkedBucket<K, V, LEN> {
 
 impl<V> Evictable<V> {
     /// Creates a new [`Evictable`].
-    pub(crate) fn new(value: V) -> Self {
+    pub(crate) fn new(value: V, weight: usize) -> Self {
         Self {
             value,
+            weight,
             prev: 0,
             next: 0,
+            frequency: 0,
         }
     }
 
@@ -1186,6 +1326,19 @@ impl<V> Evictable<V> {
         self.next = 0;
     }
 
+    /// Returns the weight of the entry.
+    pub(crate) fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Replaces the value and its weight, and returns the old ones.
+    pub(crate) fn replace(&mut self, value: V, weight: usize) -> (V, usize) {
+        (
+            mem::replace(&mut self.value, value),
+            mem::replace(&mut self.weight, weight),
+        )
+    }
+
     /// Takes the instance of `V` out of [`Evictable`].
     pub(crate) fn take(self) -> V {
         self.value
@@ -1241,7 +1394,7 @@ mod test {
                 let mut locker = Locker::lock(&mut bucket, &guard).unwrap();
                 let evicted = locker.evict_lru_head(&mut data_block);
                 assert_eq!(v >= BUCKET_LEN, evicted.is_some());
-                locker.insert_with(&mut data_block, 0, || (v, Evictable::new(v)), &guard);
+                locker.insert_with(&mut data_block, 0, || (v, Evictable::new(v, 0)), &guard);
                 assert_eq!(locker.metadata.removed_bitmap_or_lru_tail, 0);
             }
         }
@@ -1257,7 +1410,7 @@ mod test {
                 let mut locker = Locker::lock(&mut bucket, &guard).unwrap();
                 let evicted = locker.evict_lru_head(&mut data_block);
                 assert_eq!(v >= BUCKET_LEN, evicted.is_some());
-                let mut entry_ptr = locker.insert_with(&mut data_block, 0, || (v, Evictable::new(v)), &guard);
+                let mut entry_ptr = locker.insert_with(&mut data_block, 0, || (v, Evictable::new(v, 0)), &guard);
                 locker.update_lru_tail(&mut data_block, &entry_ptr);
                 assert_eq!(locker.metadata.removed_bitmap_or_lru_tail as usize, entry_ptr.current_index + 1);
                 if v >= BUCKET_LEN {
@@ -1291,7 +1444,7 @@ mod test {
             for v in 0..xs {
                 let guard = Guard::new();
                 let mut locker = Locker::lock(&mut bucket, &guard).unwrap();
-                let entry_ptr = locker.insert_with(&mut data_block, 0, || (v, Evictable::new(v)), &guard);
+                let entry_ptr = locker.insert_with(&mut data_block, 0, || (v, Evictable::new(v, 0)), &guard);
                 locker.update_lru_tail(&mut data_block, &entry_ptr);
                 let mut iterated = 1;
                 let mut i = unsafe { (*data_block[entry_ptr.current_index].as_ptr()).1.next as usize - 1 };
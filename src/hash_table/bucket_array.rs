@@ -5,6 +5,13 @@ use std::mem::{align_of, needs_drop, size_of};
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::Relaxed;
 
+#[cfg(test)]
+thread_local! {
+    /// Allocation requests larger than the value fail in the current thread.
+    pub(crate) static MAX_ALLOCATION_SIZE: std::cell::Cell<usize> =
+        const { std::cell::Cell::new(usize::MAX) };
+}
+
 /// [`BucketArray`] is a special purpose array to manage [`Bucket`] and [`DataBlock`].
 pub struct BucketArray<K: Eq, V, const TYPE: char> {
     bucket_ptr: *const Bucket<K, V, TYPE>,
@@ -34,7 +41,24 @@ impl<K: Eq, V, const TYPE: char> BucketArray<K, V, TYPE> {
     /// Creates a new [`BucketArray`] of the given capacity.
     ///
     /// `capacity` is the desired number entries, not the number of [`Bucket`] instances.
+    ///
+    /// # Panics
+    ///
+    /// Panics if memory allocation fails.
     pub(crate) fn new(capacity: usize, old_array: AtomicShared<BucketArray<K, V, TYPE>>) -> Self {
+        Self::try_new(capacity, old_array)
+            .unwrap_or_else(|size| panic!("memory allocation failure: {size} bytes"))
+    }
+
+    /// Creates a new [`BucketArray`] of the given capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns the size of the allocation that failed.
+    pub(crate) fn try_new(
+        capacity: usize,
+        old_array: AtomicShared<BucketArray<K, V, TYPE>>,
+    ) -> Result<Self, usize> {
         let log2_array_len = Self::calculate_log2_array_size(capacity);
         assert_ne!(log2_array_len, 0);
 
@@ -42,11 +66,10 @@ impl<K: Eq, V, const TYPE: char> BucketArray<K, V, TYPE> {
         unsafe {
             let (bucket_size, bucket_array_allocation_size, bucket_array_layout) =
                 Self::calculate_memory_layout::<Bucket<K, V, TYPE>>(array_len);
-            let bucket_array_ptr = alloc_zeroed(bucket_array_layout);
-            assert!(
-                !bucket_array_ptr.is_null(),
-                "memory allocation failure: {bucket_array_allocation_size} bytes",
-            );
+            let bucket_array_ptr = Self::allocate(bucket_array_layout, true);
+            if bucket_array_ptr.is_null() {
+                return Err(bucket_array_allocation_size);
+            }
             let bucket_array_ptr_offset = bucket_size.next_power_of_two()
                 - (bucket_array_ptr as usize % bucket_size.next_power_of_two());
             assert!(
@@ -65,22 +88,28 @@ impl<K: Eq, V, const TYPE: char> BucketArray<K, V, TYPE> {
             let bucket_array_ptr_offset = bucket_array_ptr_offset as u16;
 
             let data_block_array_layout = Layout::from_size_align(
-                size_of::<DataBlock<K, V, BUCKET_LEN>>() * array_len,
+                size_of::<DataBlock<K, V, BUCKET_LEN>>()
+                    .checked_mul(array_len)
+                    .expect("capacity overflow"),
                 align_of::<[DataBlock<K, V, BUCKET_LEN>; 0]>(),
             )
-            .unwrap();
-
-            let data_block_array_ptr =
-                alloc(data_block_array_layout).cast::<DataBlock<K, V, BUCKET_LEN>>();
-            assert!(
-                !data_block_array_ptr.is_null(),
-                "memory allocation failure: {} bytes",
-                data_block_array_layout.size(),
-            );
+            .expect("capacity overflow");
+
+            let data_block_array_ptr = Self::allocate(data_block_array_layout, false)
+                .cast::<DataBlock<K, V, BUCKET_LEN>>();
+            if data_block_array_ptr.is_null() {
+                dealloc(
+                    bucket_array_ptr
+                        .cast::<u8>()
+                        .sub(bucket_array_ptr_offset as usize),
+                    bucket_array_layout,
+                );
+                return Err(data_block_array_layout.size());
+            }
 
             let sample_size = u16::from(log2_array_len).next_power_of_two();
 
-            Self {
+            Ok(Self {
                 bucket_ptr: bucket_array_ptr,
                 data_block_ptr: data_block_array_ptr,
                 array_len,
@@ -89,7 +118,7 @@ impl<K: Eq, V, const TYPE: char> BucketArray<K, V, TYPE> {
                 bucket_ptr_offset: bucket_array_ptr_offset,
                 old_array,
                 num_cleared_buckets: AtomicUsize::new(0),
-            }
+            })
         }
     }
 
@@ -159,6 +188,19 @@ impl<K: Eq, V, const TYPE: char> BucketArray<K, V, TYPE> {
         self.array_len * BUCKET_LEN
     }
 
+    /// Returns the estimated number of bytes allocated for the [`BucketArray`] including the
+    /// linked buckets.
+    #[inline]
+    pub(crate) fn memory_usage(&self, guard: &Guard) -> usize {
+        let bucket_array_size =
+            Self::calculate_memory_layout::<Bucket<K, V, TYPE>>(self.array_len).1;
+        let data_block_array_size = size_of::<DataBlock<K, V, BUCKET_LEN>>() * self.array_len;
+        (0..self.array_len).fold(
+            size_of::<Self>() + bucket_array_size + data_block_array_size,
+            |usage, index| usage + self.bucket(index).linked_memory_usage(guard),
+        )
+    }
+
     /// Returns a [`Ptr`] to the old array.
     #[inline]
     pub(crate) fn has_old_array(&self) -> bool {
@@ -193,6 +235,19 @@ impl<K: Eq, V, const TYPE: char> BucketArray<K, V, TYPE> {
         hash.wrapping_shr(self.hash_offset) as usize
     }
 
+    /// Returns `true` if the two hash values mapped to the same [`Bucket`] are mapped to different
+    /// [`Bucket`] instances when the array is doubled.
+    #[inline]
+    pub(crate) const fn split_on_growth(&self, hash0: u64, hash1: u64) -> bool {
+        (hash0 ^ hash1).wrapping_shr(self.hash_offset - 1) != 0
+    }
+
+    /// Returns the smallest hash value that is mapped to the [`Bucket`] at the given index.
+    #[inline]
+    pub(crate) const fn min_hash(&self, index: usize) -> u64 {
+        (index as u64).wrapping_shl(self.hash_offset)
+    }
+
     /// Calculates `log_2` of the array size from the given capacity.
     ///
     /// Returns a non-zero `u8`, even when `capacity < 2 * BUCKET_LEN`.
@@ -209,11 +264,29 @@ impl<K: Eq, V, const TYPE: char> BucketArray<K, V, TYPE> {
         log2_capacity as u8
     }
 
+    /// Allocates memory, returning a null pointer on failure.
+    unsafe fn allocate(layout: Layout, zeroed: bool) -> *mut u8 {
+        #[cfg(test)]
+        if layout.size() > MAX_ALLOCATION_SIZE.with(std::cell::Cell::get) {
+            return std::ptr::null_mut();
+        }
+        if zeroed {
+            alloc_zeroed(layout)
+        } else {
+            alloc(layout)
+        }
+    }
+
     /// Calculates the layout of the memory block for an array of `T`.
     const fn calculate_memory_layout<T: Sized>(array_len: usize) -> (usize, usize, Layout) {
         let size_of_t = size_of::<T>();
         let aligned_size = size_of_t.next_power_of_two();
-        let allocation_size = aligned_size + array_len * size_of_t;
+        let allocation_size = match size_of_t.checked_mul(array_len) {
+            Some(array_size) if array_size <= (usize::MAX >> 1) - aligned_size => {
+                aligned_size + array_size
+            }
+            _ => panic!("capacity overflow"),
+        };
         (size_of_t, allocation_size, unsafe {
             // Intentionally mis-aligned in order to take full advantage of demand paging.
             Layout::from_size_align_unchecked(allocation_size, 1)
@@ -14,6 +14,7 @@ pub mod hash_cache;
 pub use hash_cache::HashCache;
 
 mod linked_list;
+pub use linked_list::Contended;
 pub use linked_list::Entry as LinkedEntry;
 pub use linked_list::LinkedList;
 
@@ -1,7 +1,7 @@
 use super::ebr::{AtomicShared, Guard, Ptr, Shared, Tag};
 use std::fmt::{self, Debug, Display};
 use std::ops::{Deref, DerefMut};
-use std::sync::atomic::Ordering::{self, Relaxed, Release};
+use std::sync::atomic::Ordering::{self, Acquire, Relaxed, Release};
 
 /// [`LinkedList`] is a type trait implementing a lock-free singly linked list.
 pub trait LinkedList: Sized {
@@ -192,6 +192,11 @@ pub trait LinkedList: Sized {
     /// If `mark` is given `true`, it atomically marks an internal flag on `self` when updating
     /// the linked list, otherwise it removes marks.
     ///
+    /// The entry is linked by a single compare-and-swap on `self`, therefore the operation is
+    /// `O(1)` and lock-free regardless of the length of the linked list; [`LinkedList`] does not
+    /// track the last entry, and [`Queue`](crate::Queue) should be used if entries need to be
+    /// appended to the tail in constant time.
+    ///
     /// # Errors
     ///
     /// Returns the supplied [`Shared`] when it finds `self` deleted.
@@ -258,6 +263,65 @@ pub trait LinkedList: Sized {
         Err(entry)
     }
 
+    /// Deletes the first entry after `self` satisfying the predicate.
+    ///
+    /// Returns `true` if an entry was deleted by this call. An entry is first logically deleted by
+    /// marking it with [`Self::delete_self`], and then physically unlinked by
+    /// [`Self::next_ptr`]; only the thread that succeeds in marking the entry reports it as
+    /// deleted, therefore an entry is never deleted more than once even if multiple threads
+    /// target the same entry. The operation is lock-free.
+    ///
+    /// `order` is used when marking the entry deleted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::LinkedList;
+    /// use scc::ebr::{AtomicShared, Guard, Shared};
+    /// use std::sync::atomic::Ordering::{AcqRel, Relaxed};
+    ///
+    /// #[derive(Default)]
+    /// struct L(AtomicShared<L>, usize);
+    /// impl LinkedList for L {
+    ///     fn link_ref(&self) -> &AtomicShared<L> {
+    ///         &self.0
+    ///     }
+    /// }
+    ///
+    /// let guard = Guard::new();
+    ///
+    /// let head: L = L::default();
+    /// assert!(
+    ///     head.push_back(Shared::new(L(AtomicShared::null(), 1)), false, Relaxed, &guard).is_ok());
+    /// assert!(
+    ///     head.push_back(Shared::new(L(AtomicShared::null(), 2)), false, Relaxed, &guard).is_ok());
+    ///
+    /// assert!(head.remove_if(|l| l.1 == 1, AcqRel, &guard));
+    /// assert!(!head.remove_if(|l| l.1 == 1, AcqRel, &guard));
+    /// assert_eq!(head.next_ptr(Relaxed, &guard).as_ref().unwrap().1, 2);
+    /// assert!(head.next_ptr(Relaxed, &guard).as_ref().unwrap().next_ptr(Relaxed, &guard).is_null());
+    /// ```
+    #[inline]
+    fn remove_if<F: FnMut(&Self) -> bool>(
+        &self,
+        mut pred: F,
+        order: Ordering,
+        guard: &Guard,
+    ) -> bool {
+        let mut prev = self;
+        let mut current_ptr = self.next_ptr(Acquire, guard);
+        while let Some(current) = current_ptr.as_ref() {
+            if pred(current) && current.delete_self(order) {
+                // Unlink the entry from the previous one.
+                prev.next_ptr(Acquire, guard);
+                return true;
+            }
+            prev = current;
+            current_ptr = current.next_ptr(Acquire, guard);
+        }
+        false
+    }
+
     /// Returns the closest next valid entry.
     ///
     /// It unlinks deleted entries until it reaches a valid one.
@@ -434,3 +498,8 @@ impl<T: PartialEq> PartialEq for Entry<T> {
         self.instance == other.instance
     }
 }
+
+/// [`Contended`] is returned when an operation on a lock-free container lost a race against
+/// another thread and gave up instead of retrying.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Contended;
@@ -1,8 +1,9 @@
 //! [`Queue`] is a lock-free concurrent first-in-first-out container.
 
 use super::ebr::{AtomicShared, Guard, Ptr, Shared, Tag};
-use super::linked_list::{Entry, LinkedList};
+use super::linked_list::{Contended, Entry, LinkedList};
 use std::fmt::{self, Debug};
+use std::iter::FusedIterator;
 use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
 
 /// [`Queue`] is a lock-free concurrent first-in-first-out container.
@@ -14,6 +15,22 @@ pub struct Queue<T> {
     newest: AtomicShared<Entry<T>>,
 }
 
+/// An iterator that pops entries from a [`Queue`] until it observes the [`Queue`] empty.
+#[derive(Debug)]
+pub struct Drain<'q, T> {
+    queue: &'q Queue<T>,
+    exhausted: bool,
+}
+
+/// An iterator that moves out of a [`Queue`].
+///
+/// Entries are yielded from the oldest to the newest.
+#[derive(Debug)]
+pub struct IntoIter<T> {
+    /// `next` points to the oldest entry that has yet to be yielded.
+    next: Option<Shared<Entry<T>>>,
+}
+
 impl<T: 'static> Queue<T> {
     /// Pushes an instance of `T`.
     ///
@@ -188,6 +205,46 @@ impl<T> Queue<T> {
         }
     }
 
+    /// Tries to pop the oldest entry without retrying.
+    ///
+    /// Returns `Ok(None)` if the [`Queue`] is empty, and `Err(Contended)` if the oldest entry was
+    /// popped by another thread in the meantime, in which case the caller may retry or back off.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another thread popped the oldest entry first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Queue;
+    ///
+    /// let queue: Queue<usize> = Queue::default();
+    /// assert!(matches!(queue.try_pop(), Ok(None)));
+    ///
+    /// queue.push(37);
+    /// assert_eq!(queue.try_pop().map(|e| e.map(|e| **e)), Ok(Some(37)));
+    /// ```
+    #[inline]
+    pub fn try_pop(&self) -> Result<Option<Shared<Entry<T>>>, Contended> {
+        let guard = Guard::new();
+        let mut current = self.oldest.load(Acquire, &guard);
+        while let Some(oldest_entry) = current.as_ref() {
+            if oldest_entry.is_deleted(Relaxed) {
+                current = self.cleanup_oldest(&guard);
+                continue;
+            }
+            if let Some(oldest_entry) = current.get_shared() {
+                if oldest_entry.delete_self(Relaxed) {
+                    self.cleanup_oldest(&guard);
+                    return Ok(Some(oldest_entry));
+                }
+            }
+            return Err(Contended);
+        }
+        Ok(None)
+    }
+
     /// Pops the oldest entry if the entry satisfies the given condition.
     ///
     /// Returns `None` if the [`Queue`] is empty.
@@ -232,6 +289,39 @@ impl<T> Queue<T> {
         Ok(None)
     }
 
+    /// Returns an iterator that pops entries in first-in-first-out order.
+    ///
+    /// The iterator stops as soon as it observes the [`Queue`] empty, and it never yields an
+    /// entry afterwards even if a new entry is pushed in the meantime. Each popped entry is
+    /// reclaimed when the last [`Shared`] pointing to it is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Queue;
+    ///
+    /// let queue: Queue<usize> = Queue::default();
+    ///
+    /// queue.push(37);
+    /// queue.push(3);
+    ///
+    /// let mut drain = queue.drain();
+    /// assert_eq!(drain.next().map(|e| **e), Some(37));
+    /// assert_eq!(drain.next().map(|e| **e), Some(3));
+    /// assert!(drain.next().is_none());
+    ///
+    /// queue.push(1);
+    /// assert!(drain.next().is_none());
+    /// assert_eq!(queue.pop().map(|e| **e), Some(1));
+    /// ```
+    #[inline]
+    pub fn drain(&self) -> Drain<'_, T> {
+        Drain {
+            queue: self,
+            exhausted: false,
+        }
+    }
+
     /// Peeks the oldest entry.
     ///
     /// # Examples
@@ -262,6 +352,33 @@ impl<T> Queue<T> {
         reader(None)
     }
 
+    /// Returns a clone of the oldest entry.
+    ///
+    /// Entries popped by other threads are skipped, and the entry is cloned while it is protected
+    /// by a [`Guard`], therefore the clone is consistent even if the entry is popped concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Queue;
+    ///
+    /// let queue: Queue<usize> = Queue::default();
+    ///
+    /// assert!(queue.peek_clone().is_none());
+    ///
+    /// queue.push(37);
+    /// queue.push(3);
+    ///
+    /// assert_eq!(queue.peek_clone(), Some(37));
+    /// ```
+    #[inline]
+    pub fn peek_clone(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.peek_with(|entry| entry.map(|entry| (**entry).clone()))
+    }
+
     /// Returns `true` if the [`Queue`] is empty.
     ///
     /// # Examples
@@ -396,6 +513,11 @@ impl<T> Queue<T> {
 }
 
 impl<T: Clone> Clone for Queue<T> {
+    /// Clones the entries reachable from the oldest one into a new [`Queue`] in the same order.
+    ///
+    /// The entries are read under a single [`Guard`], however the operation is not linearizable:
+    /// entries pushed or popped by other threads during the operation may or may not be reflected
+    /// in the clone.
     #[inline]
     fn clone(&self) -> Self {
         let self_clone = Self::default();
@@ -434,3 +556,82 @@ impl<T> Default for Queue<T> {
         }
     }
 }
+
+impl<T: 'static> Extend<T> for Queue<T> {
+    /// Pushes the instances in iteration order.
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.push(val);
+        }
+    }
+}
+
+impl<T: 'static> FromIterator<T> for Queue<T> {
+    /// Creates a [`Queue`] from an iterator; the first instance becomes the oldest entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Queue;
+    ///
+    /// let queue: Queue<usize> = (0..3).collect();
+    ///
+    /// assert_eq!(queue.pop().map(|e| **e), Some(0));
+    /// assert_eq!(queue.pop().map(|e| **e), Some(1));
+    /// ```
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = Self::default();
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<T> IntoIterator for Queue<T> {
+    type Item = Shared<Entry<T>>;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        // `self` is owned by the iterator, therefore the whole chain is detached at once.
+        IntoIter {
+            next: self.oldest.swap((None, Tag::None), Acquire).0,
+        }
+    }
+}
+
+impl<T> FusedIterator for Drain<'_, T> {}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = Shared<Entry<T>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exhausted {
+            return None;
+        }
+        let popped = self.queue.pop();
+        self.exhausted = popped.is_none();
+        popped
+    }
+}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = Shared<Entry<T>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(entry) = self.next.take() {
+            let (next, tag) = entry.link_ref().swap((None, Tag::None), Relaxed);
+            self.next = next;
+            // Entries deleted before the chain was detached are skipped.
+            if tag != Tag::Second {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
@@ -1,13 +1,15 @@
 //! This module implements helper types and traits for `serde`.
 
 use super::ebr::Guard;
-use super::{HashCache, HashIndex, HashMap, HashSet, TreeIndex};
+use super::linked_list::{Entry, LinkedList};
+use super::{Bag, HashCache, HashIndex, HashMap, HashSet, Queue, Stack, TreeIndex};
 use serde::de::{Deserialize, MapAccess, SeqAccess, Visitor};
 use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
 use serde::Deserializer;
 use std::fmt;
 use std::hash::{BuildHasher, Hash};
 use std::marker::PhantomData;
+use std::sync::atomic::Ordering::Acquire;
 
 /// Helper type to allow `serde` to access [`HashMap`] entries.
 pub struct HashMapVisitor<K: Eq + Hash, V, H: BuildHasher> {
@@ -420,3 +422,204 @@ where
         map.end()
     }
 }
+
+/// Helper type to allow `serde` to access [`Queue`] entries.
+pub struct QueueVisitor<T: 'static> {
+    marker: PhantomData<fn() -> Queue<T>>,
+}
+
+impl<T> QueueVisitor<T> {
+    fn new() -> Self {
+        QueueVisitor {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'d, T> Visitor<'d> for QueueVisitor<T>
+where
+    T: Deserialize<'d>,
+{
+    type Value = Queue<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Queue")
+    }
+
+    fn visit_seq<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+    where
+        M: SeqAccess<'d>,
+    {
+        let queue = Queue::default();
+        while let Some(val) = access.next_element()? {
+            queue.push(val);
+        }
+        Ok(queue)
+    }
+}
+
+impl<'d, T> Deserialize<'d> for Queue<T>
+where
+    T: 'static + Deserialize<'d>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'d>,
+    {
+        deserializer.deserialize_seq(QueueVisitor::<T>::new())
+    }
+}
+
+impl<T> Serialize for Queue<T>
+where
+    T: 'static + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Entries are serialized from the oldest to the newest.
+        let guard = Guard::new();
+        serialize_linked_entries(self.peek(&guard), serializer, &guard)
+    }
+}
+
+/// Helper type to allow `serde` to access [`Stack`] entries.
+pub struct StackVisitor<T: 'static> {
+    marker: PhantomData<fn() -> Stack<T>>,
+}
+
+impl<T> StackVisitor<T> {
+    fn new() -> Self {
+        StackVisitor {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'d, T> Visitor<'d> for StackVisitor<T>
+where
+    T: Deserialize<'d>,
+{
+    type Value = Stack<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Stack")
+    }
+
+    fn visit_seq<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+    where
+        M: SeqAccess<'d>,
+    {
+        // Elements are serialized from the newest to the oldest.
+        let mut vals = Vec::with_capacity(access.size_hint().unwrap_or(0));
+        while let Some(val) = access.next_element()? {
+            vals.push(val);
+        }
+        let stack = Stack::default();
+        for val in vals.into_iter().rev() {
+            stack.push(val);
+        }
+        Ok(stack)
+    }
+}
+
+impl<'d, T> Deserialize<'d> for Stack<T>
+where
+    T: 'static + Deserialize<'d>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'d>,
+    {
+        deserializer.deserialize_seq(StackVisitor::<T>::new())
+    }
+}
+
+impl<T> Serialize for Stack<T>
+where
+    T: 'static + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Entries are serialized from the newest to the oldest.
+        let guard = Guard::new();
+        serialize_linked_entries(self.peek(&guard), serializer, &guard)
+    }
+}
+
+/// Serializes the entries reachable from `first` in a [`Queue`] or [`Stack`].
+///
+/// The entries are read under a single [`Guard`], and only references to them are collected to
+/// learn the length of the sequence in advance.
+fn serialize_linked_entries<T, S>(
+    first: Option<&Entry<T>>,
+    serializer: S,
+    guard: &Guard,
+) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    let mut entries = Vec::new();
+    let mut current = first;
+    while let Some(entry) = current {
+        entries.push(&**entry);
+        current = entry.next_ptr(Acquire, guard).as_ref();
+    }
+    let mut seq = serializer.serialize_seq(Some(entries.len()))?;
+    for entry in entries {
+        seq.serialize_element(entry)?;
+    }
+    seq.end()
+}
+
+/// Helper type to allow `serde` to access [`Bag`] entries.
+pub struct BagVisitor<T, const ARRAY_LEN: usize> {
+    marker: PhantomData<fn() -> Bag<T, ARRAY_LEN>>,
+}
+
+impl<T, const ARRAY_LEN: usize> BagVisitor<T, ARRAY_LEN> {
+    fn new() -> Self {
+        BagVisitor {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'d, T, const ARRAY_LEN: usize> Visitor<'d> for BagVisitor<T, ARRAY_LEN>
+where
+    T: Deserialize<'d>,
+{
+    type Value = Bag<T, ARRAY_LEN>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Bag")
+    }
+
+    fn visit_seq<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+    where
+        M: SeqAccess<'d>,
+    {
+        let bag = Bag::new();
+        while let Some(val) = access.next_element()? {
+            bag.push(val);
+        }
+        Ok(bag)
+    }
+}
+
+// `Bag` only implements `Deserialize` since its instances cannot be read without being popped.
+impl<'d, T, const ARRAY_LEN: usize> Deserialize<'d> for Bag<T, ARRAY_LEN>
+where
+    T: Deserialize<'d>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'d>,
+    {
+        deserializer.deserialize_seq(BagVisitor::<T, ARRAY_LEN>::new())
+    }
+}
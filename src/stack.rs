@@ -1,8 +1,10 @@
 //! [`Stack`] is a lock-free concurrent last-in-first-out container.
 
 use super::ebr::{AtomicShared, Guard, Ptr, Shared, Tag};
-use super::linked_list::{Entry, LinkedList};
+use super::linked_list::{Contended, Entry, LinkedList};
 use std::fmt::{self, Debug};
+use std::iter::FusedIterator;
+use std::ptr;
 use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
 
 /// [`Stack`] is a lock-free concurrent last-in-first-out container.
@@ -11,6 +13,15 @@ pub struct Stack<T> {
     newest: AtomicShared<Entry<T>>,
 }
 
+/// An iterator that moves out of a [`Stack`].
+///
+/// Entries are yielded from the newest to the oldest.
+#[derive(Debug)]
+pub struct IntoIter<T> {
+    /// `next` points to the newest entry that has yet to be yielded.
+    next: Option<Shared<Entry<T>>>,
+}
+
 impl<T: 'static> Stack<T> {
     /// Pushes an instance of `T`.
     ///
@@ -62,6 +73,63 @@ impl<T: 'static> Stack<T> {
         self.push_if_internal(val, cond, &Guard::new())
     }
 
+    /// Pushes all the instances of `T` in the given iterator at once.
+    ///
+    /// The instances are linked together before the whole chain is attached to the [`Stack`] in a
+    /// single atomic operation, therefore no other entries can be interleaved with them. The last
+    /// instance in the iterator ends up as the newest entry.
+    ///
+    /// Returns a [`Shared`] holding a strong reference to the newest pushed entry, or `None` if
+    /// the iterator is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = Stack::default();
+    ///
+    /// stack.push(1);
+    /// assert_eq!(stack.push_all([2, 3]).map(|e| **e), Some(3));
+    ///
+    /// assert_eq!(stack.pop().map(|e| **e), Some(3));
+    /// assert_eq!(stack.pop().map(|e| **e), Some(2));
+    /// assert_eq!(stack.pop().map(|e| **e), Some(1));
+    /// ```
+    #[inline]
+    pub fn push_all<I: IntoIterator<Item = T>>(&self, iter: I) -> Option<Shared<Entry<T>>> {
+        let mut iter = iter.into_iter();
+        let oldest_entry = Shared::new(Entry::new(iter.next()?));
+        let mut newest_entry = oldest_entry.clone();
+        for val in iter {
+            let new_entry = Shared::new(Entry::new(val));
+            new_entry
+                .next()
+                .swap((Some(newest_entry), Tag::None), Relaxed);
+            newest_entry = new_entry;
+        }
+
+        let guard = Guard::new();
+        let mut newest_ptr = self.cleanup_newest(self.newest.load(Acquire, &guard), &guard);
+        loop {
+            oldest_entry
+                .next()
+                .swap((newest_ptr.get_shared(), Tag::None), Relaxed);
+            match self.newest.compare_exchange(
+                newest_ptr,
+                (Some(newest_entry.clone()), Tag::None),
+                AcqRel,
+                Acquire,
+                &guard,
+            ) {
+                Ok(_) => return Some(newest_entry),
+                Err((_, actual_ptr)) => {
+                    newest_ptr = self.cleanup_newest(actual_ptr, &guard);
+                }
+            }
+        }
+    }
+
     /// Returns a guarded reference to the newest entry.
     ///
     /// Returns `None` if the [`Stack`] is empty. The returned reference can survive as long as the
@@ -178,6 +246,42 @@ impl<T> Stack<T> {
         }
     }
 
+    /// Tries to pop the newest entry without retrying.
+    ///
+    /// Returns `Ok(None)` if the [`Stack`] is empty, and `Err(Contended)` if the newest entry was
+    /// popped by another thread in the meantime, in which case the caller may retry or back off.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another thread popped the newest entry first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = Stack::default();
+    /// assert!(matches!(stack.try_pop(), Ok(None)));
+    ///
+    /// stack.push(37);
+    /// assert_eq!(stack.try_pop().map(|e| e.map(|e| **e)), Ok(Some(37)));
+    /// ```
+    #[inline]
+    pub fn try_pop(&self) -> Result<Option<Shared<Entry<T>>>, Contended> {
+        let guard = Guard::new();
+        let newest_ptr = self.cleanup_newest(self.newest.load(Acquire, &guard), &guard);
+        if newest_ptr.is_null() {
+            return Ok(None);
+        }
+        if let Some(newest_entry) = newest_ptr.get_shared() {
+            if newest_entry.delete_self(Relaxed) {
+                self.cleanup_newest(newest_ptr, &guard);
+                return Ok(Some(newest_entry));
+            }
+        }
+        Err(Contended)
+    }
+
     /// Pops all the entries at once, and passes each one of the popped entries to the supplied
     /// closure.
     ///
@@ -213,6 +317,44 @@ impl<T> Stack<T> {
         }
     }
 
+    /// Exchanges the entries of `self` and `other`.
+    ///
+    /// The entries of each [`Stack`] are detached in a single atomic operation, and then attached
+    /// to the other [`Stack`] in a single atomic operation, therefore no entries are lost or
+    /// duplicated. However, the operation as a whole is not linearizable: other threads may
+    /// observe either [`Stack`] empty in the meantime, and entries pushed by other threads in the
+    /// meantime stay in the [`Stack`] that they were pushed into, below the attached entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let front: Stack<usize> = Stack::default();
+    /// let back: Stack<usize> = Stack::default();
+    ///
+    /// front.push(1);
+    /// back.push(2);
+    /// back.push(3);
+    ///
+    /// front.swap(&back);
+    ///
+    /// assert_eq!(front.pop().map(|e| **e), Some(3));
+    /// assert_eq!(front.pop().map(|e| **e), Some(2));
+    /// assert_eq!(back.pop().map(|e| **e), Some(1));
+    /// assert!(front.is_empty() && back.is_empty());
+    /// ```
+    #[inline]
+    pub fn swap(&self, other: &Self) {
+        if ptr::eq(self, other) {
+            return;
+        }
+        let detached_self = self.pop_all();
+        let detached_other = other.pop_all();
+        other.attach(&detached_self);
+        self.attach(&detached_other);
+    }
+
     /// Pops the newest entry if the entry satisfies the given condition.
     ///
     /// Returns `None` if the [`Stack`] is empty.
@@ -284,6 +426,33 @@ impl<T> Stack<T> {
         )
     }
 
+    /// Returns a clone of the newest entry.
+    ///
+    /// Entries popped by other threads are skipped, and the entry is cloned while it is protected
+    /// by a [`Guard`], therefore the clone is consistent even if the entry is popped concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = Stack::default();
+    ///
+    /// assert!(stack.peek_clone().is_none());
+    ///
+    /// stack.push(37);
+    /// stack.push(3);
+    ///
+    /// assert_eq!(stack.peek_clone(), Some(3));
+    /// ```
+    #[inline]
+    pub fn peek_clone(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.peek_with(|entry| entry.map(|entry| (**entry).clone()))
+    }
+
     /// Returns `true` if the [`Stack`] is empty.
     ///
     /// # Examples
@@ -345,6 +514,56 @@ impl<T> Stack<T> {
         Err(unsafe { new_entry.get_mut().unwrap_unchecked().take_inner() })
     }
 
+    /// Attaches all the entries of a detached [`Stack`] on top of `self`.
+    fn attach(&self, detached: &Self) {
+        let Some(newest_entry) = detached.newest.swap((None, Tag::None), Relaxed).0 else {
+            return;
+        };
+        let guard = Guard::new();
+
+        // Find the oldest entry, including deleted ones, of the detached chain.
+        let mut oldest_ptr = newest_entry.get_guarded_ptr(&guard);
+        while let Some(next_ptr) = oldest_ptr
+            .as_ref()
+            .map(|e| e.next().load(Acquire, &guard))
+            .filter(|p| !p.is_null())
+        {
+            oldest_ptr = next_ptr;
+        }
+        let Some(oldest_entry) = oldest_ptr.as_ref() else {
+            return;
+        };
+
+        let mut newest_ptr = self.cleanup_newest(self.newest.load(Acquire, &guard), &guard);
+        loop {
+            // Link the chain while keeping the deletion mark of the oldest entry.
+            let mut link_ptr = oldest_entry.next().load(Relaxed, &guard);
+            let mut next = newest_ptr.get_shared();
+            while let Err((returned, actual_ptr)) = oldest_entry.next().compare_exchange(
+                link_ptr,
+                (next, link_ptr.tag()),
+                Relaxed,
+                Relaxed,
+                &guard,
+            ) {
+                next = returned;
+                link_ptr = actual_ptr;
+            }
+            match self.newest.compare_exchange(
+                newest_ptr,
+                (Some(newest_entry.clone()), Tag::None),
+                AcqRel,
+                Acquire,
+                &guard,
+            ) {
+                Ok(_) => return,
+                Err((_, actual_ptr)) => {
+                    newest_ptr = self.cleanup_newest(actual_ptr, &guard);
+                }
+            }
+        }
+    }
+
     /// Cleans up logically removed entries that are attached to `newest`.
     fn cleanup_newest<'g>(
         &self,
@@ -374,6 +593,11 @@ impl<T> Stack<T> {
 }
 
 impl<T: Clone> Clone for Stack<T> {
+    /// Clones the entries reachable from the newest one into a new [`Stack`] in the same order.
+    ///
+    /// The entries are read under a single [`Guard`], however the operation is not linearizable:
+    /// entries pushed or popped by other threads during the operation may or may not be reflected
+    /// in the clone.
     #[inline]
     fn clone(&self) -> Self {
         let self_clone = Self::default();
@@ -421,3 +645,65 @@ impl<T> Default for Stack<T> {
         }
     }
 }
+
+impl<T: 'static> Extend<T> for Stack<T> {
+    /// Pushes the instances in iteration order by linking them at once; see
+    /// [`Stack::push_all`].
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.push_all(iter);
+    }
+}
+
+impl<T: 'static> FromIterator<T> for Stack<T> {
+    /// Creates a [`Stack`] from an iterator; the last instance becomes the newest entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = (0..3).collect();
+    ///
+    /// assert_eq!(stack.pop().map(|e| **e), Some(2));
+    /// assert_eq!(stack.pop().map(|e| **e), Some(1));
+    /// ```
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let stack = Self::default();
+        stack.push_all(iter);
+        stack
+    }
+}
+
+impl<T> IntoIterator for Stack<T> {
+    type Item = Shared<Entry<T>>;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        // `self` is owned by the iterator, therefore the whole chain is detached at once.
+        IntoIter {
+            next: self.newest.swap((None, Tag::None), Acquire).0,
+        }
+    }
+}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = Shared<Entry<T>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(entry) = self.next.take() {
+            let (next, tag) = entry.link_ref().swap((None, Tag::None), Relaxed);
+            self.next = next;
+            // Entries deleted before the chain was detached are skipped.
+            if tag != Tag::Second {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
@@ -1,15 +1,17 @@
 #[cfg(test)]
 mod hashmap_test {
     use crate::hash_map::{self, Entry, Reserve};
-    use crate::HashMap;
+    use crate::hash_table::HashTable;
+    use crate::{HashMap, HashSet};
     use proptest::prelude::*;
     use proptest::strategy::{Strategy, ValueTree};
     use proptest::test_runner::TestRunner;
+    use std::collections::hash_map::DefaultHasher;
     use std::collections::BTreeSet;
-    use std::hash::{Hash, Hasher};
+    use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
     use std::panic::UnwindSafe;
     use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
-    use std::sync::atomic::{AtomicU64, AtomicUsize};
+    use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
     use std::sync::{Arc, Barrier};
     use std::thread;
     use tokio::sync::Barrier as AsyncBarrier;
@@ -80,6 +82,250 @@ mod hashmap_test {
         }
     }
 
+    #[test]
+    fn stored_key() {
+        #[derive(Debug)]
+        struct Tagged(usize, &'static str);
+        impl PartialEq for Tagged {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Tagged {}
+        impl Hash for Tagged {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.hash(state);
+            }
+        }
+
+        let hashmap: HashMap<Tagged, usize> = HashMap::default();
+        let hashset: HashSet<Tagged> = HashSet::default();
+        let lookups: [(&str, &dyn Fn(&Tagged) -> Option<&'static str>); 4] = [
+            ("HashMap::read", &|key| hashmap.read(key, |k, _| k.1)),
+            ("HashSet::read", &|key| hashset.read(key, |k| k.1)),
+            ("HashMap::remove", &|key| {
+                hashmap.remove(key).map(|(k, _)| k.1)
+            }),
+            ("HashMap::remove_async", &|key| {
+                futures::executor::block_on(hashmap.remove_async(key)).map(|(k, _)| k.1)
+            }),
+        ];
+        for (method, lookup) in lookups {
+            hashmap.clear();
+            hashset.clear();
+            assert!(hashmap.insert(Tagged(7, "stored"), 11).is_ok());
+            assert!(hashset.insert(Tagged(7, "stored")).is_ok());
+            assert_eq!(lookup(&Tagged(11, "query")), None, "{method}");
+            assert_eq!(lookup(&Tagged(7, "query")), Some("stored"), "{method}");
+        }
+    }
+
+    #[test]
+    fn entry_ref() {
+        static NUM_CONVERSIONS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug, Eq, Hash, PartialEq)]
+        struct Counted(String);
+        impl std::borrow::Borrow<str> for Counted {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+        impl From<&str> for Counted {
+            fn from(key: &str) -> Self {
+                NUM_CONVERSIONS.fetch_add(1, Relaxed);
+                Counted(key.to_owned())
+            }
+        }
+
+        let hashmap: HashMap<Counted, usize> = HashMap::default();
+        for word in "a b a c a b".split(' ') {
+            hashmap.entry_ref(word).and_modify(|v| *v += 1).or_insert(1);
+        }
+        assert_eq!(NUM_CONVERSIONS.load(Relaxed), 3);
+        assert_eq!(hashmap.read("a", |_, v| *v), Some(3));
+        assert_eq!(hashmap.read("b", |_, v| *v), Some(2));
+        assert_eq!(hashmap.read("c", |_, v| *v), Some(1));
+
+        match hashmap.entry_ref("d") {
+            hash_map::EntryRef::Vacant(v) => assert_eq!(v.key(), "d"),
+            hash_map::EntryRef::Occupied(_) => unreachable!(),
+        }
+        assert!(!hashmap.contains("d"));
+        assert_eq!(hashmap.entry_ref("a").key(), "a");
+        assert_eq!(*hashmap.entry_ref("d").or_default().get(), 0);
+        assert_eq!(NUM_CONVERSIONS.load(Relaxed), 4);
+        assert_eq!(hashmap.len(), 4);
+    }
+
+    #[test]
+    fn remove_all() {
+        let hashmap: HashMap<String, usize> = HashMap::default();
+        assert_eq!(hashmap.remove_all(["0"]), 0);
+        for k in 0..1024 {
+            assert!(hashmap.insert(k.to_string(), k).is_ok());
+        }
+        let keys: Vec<String> = (512..1536).map(|k| k.to_string()).collect();
+        assert_eq!(hashmap.remove_all(&keys), 512);
+        assert_eq!(hashmap.remove_all(keys.iter().map(String::as_str)), 0);
+        assert_eq!(hashmap.len(), 512);
+        assert!((0..512).all(|k| hashmap.contains(k.to_string().as_str())));
+        assert_eq!(hashmap.remove_all(["0", "0", "1", "1024"]), 2);
+        assert_eq!(hashmap.len(), 510);
+    }
+
+    #[test]
+    fn get_or_try_insert_with() {
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        assert_eq!(
+            hashmap
+                .get_or_try_insert_with(1, || Err::<usize, _>("error"))
+                .err(),
+            Some("error")
+        );
+        assert!(hashmap.is_empty());
+        assert!(!hashmap.contains(&1));
+
+        // The bucket lock must have been released.
+        assert!(hashmap.insert(1, 10).is_ok());
+        let entry = hashmap
+            .get_or_try_insert_with(1, || -> Result<usize, ()> { unreachable!() })
+            .unwrap();
+        assert_eq!(*entry.get(), 10);
+        drop(entry);
+
+        let entry = hashmap
+            .get_or_try_insert_with(2, || Ok::<_, ()>(20))
+            .unwrap();
+        assert_eq!(*entry.get(), 20);
+        drop(entry);
+        assert_eq!(hashmap.read(&2, |_, v| *v), Some(20));
+        assert_eq!(hashmap.len(), 2);
+    }
+
+    #[test]
+    fn into_iter() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        for k in 0..4096 {
+            assert!(hashmap.insert(k, k * 2).is_ok());
+        }
+        for k in (0..4096).step_by(4) {
+            assert!(hashmap.remove(&k).is_some());
+        }
+        let mut entries: Vec<(usize, usize)> = hashmap.into_iter().collect();
+        entries.sort_unstable();
+        let expected: Vec<(usize, usize)> = (0..4096)
+            .filter(|k| k % 4 != 0)
+            .map(|k| (k, k * 2))
+            .collect();
+        assert_eq!(entries, expected);
+
+        let hashmap: HashMap<usize, R> = HashMap::default();
+        for k in 0..1024 {
+            assert!(hashmap.insert(k, R::new(&INST_CNT)).is_ok());
+        }
+        assert_eq!(INST_CNT.load(Relaxed), 1024);
+        let mut iter = hashmap.into_iter();
+        assert_eq!(iter.by_ref().take(16).count(), 16);
+        assert_eq!(INST_CNT.load(Relaxed), 1008);
+        drop(iter);
+        assert_eq!(INST_CNT.load(Relaxed), 0);
+    }
+
+    #[test]
+    fn clone_to() {
+        let source: HashMap<usize, usize> = HashMap::default();
+        let target: HashMap<usize, usize> = HashMap::default();
+        for k in 0..4096 {
+            assert!(target.insert(k, k).is_ok());
+        }
+        for k in 2048..2304 {
+            assert!(source.insert(k, k * 3).is_ok());
+        }
+        let capacity = target.capacity();
+        source.clone_to(&target);
+        assert_eq!(target.capacity(), capacity);
+        assert_eq!(target.len(), source.len());
+        source.scan(|k, v| assert_eq!(target.read(k, |_, t| *t), Some(*v)));
+        target.scan(|k, v| assert_eq!(source.read(k, |_, s| *s), Some(*v)));
+
+        source.clear();
+        for k in 0..8192 {
+            assert!(source.insert(k, k).is_ok());
+        }
+        source.clone_to(&target);
+        assert!(target.capacity() >= source.len());
+        assert_eq!(target.len(), 8192);
+        assert!((0..8192).all(|k| target.read(&k, |_, v| *v) == Some(k)));
+    }
+
+    #[test]
+    fn try_for_each() {
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        assert_eq!(hashmap.try_for_each(|_, _| Err(())), Ok(()));
+        for k in 0..4096 {
+            assert!(hashmap.insert(k, k * 2).is_ok());
+        }
+
+        let mut num_visited = 0;
+        assert_eq!(
+            hashmap.try_for_each(|k, v| {
+                num_visited += 1;
+                if *k == 1024 {
+                    Err(*v)
+                } else {
+                    Ok(())
+                }
+            }),
+            Err(2048)
+        );
+        assert!((1..=4096).contains(&num_visited));
+
+        let mut num_visited = 0;
+        assert_eq!(
+            hashmap.try_for_each(|_, _| {
+                num_visited += 1;
+                if num_visited == 16 {
+                    Err(())
+                } else {
+                    Ok(())
+                }
+            }),
+            Err(())
+        );
+        assert_eq!(num_visited, 16);
+
+        let mut sum = 0;
+        assert_eq!(
+            hashmap.try_for_each(|k, _| {
+                sum += *k;
+                Ok::<(), ()>(())
+            }),
+            Ok(())
+        );
+        assert_eq!(sum, (0..4096).sum());
+    }
+
+    #[test]
+    fn is_empty() {
+        let hashmap: HashMap<usize, usize> = HashMap::with_capacity(1 << 16);
+        assert!(hashmap.is_empty());
+        assert!(hashmap.insert(usize::MAX, 0).is_ok());
+        assert!(!hashmap.is_empty());
+        hashmap.clear();
+        assert!(hashmap.is_empty());
+
+        for k in 0..4096 {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        hashmap.retain(|k, _| *k == 4095);
+        assert!(!hashmap.is_empty());
+        assert!(hashmap.remove(&4095).is_some());
+        assert!(hashmap.is_empty());
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test]
     async fn insert_drop() {
@@ -300,6 +546,34 @@ mod hashmap_test {
         assert_eq!(hashmap.len(), 0);
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn entry_async_contended() {
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::default());
+
+        let num_tasks = 16;
+        let workload_size = 64;
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        for _ in 0..num_tasks {
+            let barrier_clone = barrier.clone();
+            let hashmap_clone = hashmap.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_clone.wait().await;
+                for _ in 0..workload_size {
+                    let mut entry = hashmap_clone.entry_async(0).await.or_insert(0);
+                    let val = *entry.get();
+                    tokio::task::yield_now().await;
+                    *entry.get_mut() = val + 1;
+                }
+            }));
+        }
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+        assert_eq!(hashmap.read(&0, |_, v| *v), Some(num_tasks * workload_size));
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
     async fn insert_read_remove() {
@@ -337,6 +611,95 @@ mod hashmap_test {
         }
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn contains_resize() {
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::default());
+        for _ in 0..64 {
+            let num_tasks = 8;
+            let workload_size = 256;
+            let mut task_handles = Vec::with_capacity(num_tasks);
+            let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+            for task_id in 0..num_tasks {
+                let barrier_clone = barrier.clone();
+                let hashmap_clone = hashmap.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_clone.wait().await;
+                    let range = (task_id * workload_size)..((task_id + 1) * workload_size);
+                    for id in range.clone() {
+                        assert!(hashmap_clone.insert_async(id, id).await.is_ok());
+                        for prev in range.start..=id {
+                            assert!(hashmap_clone.contains(&prev));
+                        }
+                    }
+                    for id in range.clone() {
+                        assert!(hashmap_clone.contains_async(&id).await);
+                        assert!(hashmap_clone.remove_async(&id).await.is_some());
+                        assert!(!hashmap_clone.contains(&id));
+                    }
+                }));
+            }
+
+            for r in futures::future::join_all(task_handles).await {
+                assert!(r.is_ok());
+            }
+
+            assert_eq!(hashmap.len(), 0);
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn remove_if() {
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::default());
+        let num_tasks = 8;
+        let workload_size = 256;
+        for _ in 0..16 {
+            for k in 0..workload_size {
+                assert!(hashmap.insert(k, 0).is_ok());
+            }
+            let mut task_handles = Vec::with_capacity(num_tasks);
+            let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+            for task_id in 0..num_tasks {
+                let barrier_clone = barrier.clone();
+                let hashmap_clone = hashmap.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_clone.wait().await;
+                    let mut num_removed = 0;
+                    for k in 0..workload_size {
+                        // Only the last task to observe the value removes the entry.
+                        let result = if task_id % 2 == 0 {
+                            hashmap_clone.remove_if(&k, |v| {
+                                *v += 1;
+                                *v == num_tasks
+                            })
+                        } else {
+                            hashmap_clone
+                                .remove_if_async(&k, |v| {
+                                    *v += 1;
+                                    *v == num_tasks
+                                })
+                                .await
+                        };
+                        if let Some((key, val)) = result {
+                            assert_eq!(key, k);
+                            assert_eq!(val, num_tasks);
+                            num_removed += 1;
+                        }
+                    }
+                    num_removed
+                }));
+            }
+
+            let mut num_removed = 0;
+            for r in futures::future::join_all(task_handles).await {
+                num_removed += r.unwrap();
+            }
+            assert_eq!(num_removed, workload_size);
+            assert_eq!(hashmap.len(), 0);
+        }
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
     async fn entry_next_retain() {
@@ -590,102 +953,1095 @@ mod hashmap_test {
 
     #[cfg_attr(miri, ignore)]
     #[test]
-    fn iter() {
-        let data_size = 4096;
+    fn load_factor() {
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        assert!(hashmap.load_factor().abs() < f32::EPSILON);
+        for k in 0..4096_u16 {
+            assert!(hashmap.insert(usize::from(k), 0).is_ok());
+            // Nothing else modifies the `HashMap`, therefore the load factor is exactly the number
+            // of inserted entries divided by the capacity, even while it is being resized.
+            let capacity = u16::try_from(hashmap.capacity()).unwrap();
+            let expected = f32::from(k + 1) / f32::from(capacity);
+            assert!(
+                (hashmap.load_factor() - expected).abs() < f32::EPSILON,
+                "{k} {capacity}"
+            );
+        }
+        let capacity = u16::try_from(hashmap.capacity()).unwrap();
+        assert!(capacity > 4096 / 2);
+        let expected = 4096.0 / f32::from(capacity);
+        assert!((hashmap.load_factor() - expected).abs() < f32::EPSILON);
+        for k in 0..4096 {
+            assert!(hashmap.remove(&k).is_some());
+        }
+        assert!(hashmap.load_factor().abs() < f32::EPSILON);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn scan_exactly_once() {
+        let workload_size = 1024;
         for _ in 0..16 {
-            let hashmap: Arc<HashMap<u64, u64>> = Arc::new(HashMap::default());
-            let hashmap_copied = hashmap.clone();
+            let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+            for k in 0..workload_size {
+                assert!(hashmap.insert(k, k).is_ok());
+            }
             let barrier = Arc::new(Barrier::new(2));
-            let barrier_copied = barrier.clone();
-            let inserted = Arc::new(AtomicU64::new(0));
-            let inserted_copied = inserted.clone();
-            let removed = Arc::new(AtomicU64::new(data_size));
-            let removed_copied = removed.clone();
-            let thread_handle = thread::spawn(move || {
-                // test insert
-                for _ in 0..2 {
-                    barrier_copied.wait();
-                    let mut scanned = 0;
-                    let mut checker = BTreeSet::new();
-                    let max = inserted_copied.load(Acquire);
-                    hashmap_copied.retain(|k, _| {
-                        scanned += 1;
-                        checker.insert(*k);
-                        true
-                    });
-                    for key in 0..max {
-                        assert!(checker.contains(&key));
+            let done = Arc::new(AtomicBool::new(false));
+            let hashmap_clone = hashmap.clone();
+            let barrier_clone = barrier.clone();
+            let done_clone = done.clone();
+            let writer = thread::spawn(move || {
+                barrier_clone.wait();
+                while !done_clone.load(Relaxed) {
+                    // Keep resizing the `HashMap`.
+                    for k in workload_size..workload_size * 16 {
+                        assert!(hashmap_clone.insert(k, k).is_ok());
+                    }
+                    for k in workload_size..workload_size * 16 {
+                        assert!(hashmap_clone.remove(&k).is_some());
                     }
-                }
-                // test remove
-                for _ in 0..2 {
-                    barrier_copied.wait();
-                    let mut scanned = 0;
-                    let max = removed_copied.load(Acquire);
-                    hashmap_copied.retain(|k, _| {
-                        scanned += 1;
-                        assert!(*k < max);
-                        true
-                    });
                 }
             });
-            // insert
-            barrier.wait();
-            for i in 0..data_size {
-                if i == data_size / 2 {
-                    barrier.wait();
-                }
-                assert!(hashmap.insert(i, i).is_ok());
-                inserted.store(i, Release);
-            }
-            // remove
+
             barrier.wait();
-            for i in (0..data_size).rev() {
-                if i == data_size / 2 {
-                    barrier.wait();
-                }
-                assert!(hashmap.remove(&i).is_some());
-                removed.store(i, Release);
+            for _ in 0..16 {
+                let mut visited = vec![0_usize; workload_size];
+                hashmap.scan(|k, v| {
+                    assert_eq!(k, v);
+                    if *k < workload_size {
+                        visited[*k] += 1;
+                    }
+                });
+                assert!(visited.iter().all(|c| *c == 1));
             }
-            thread_handle.join().unwrap();
+            done.store(true, Relaxed);
+            assert!(writer.join().is_ok());
         }
     }
 
     #[cfg_attr(miri, ignore)]
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
-    async fn read() {
-        let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::default());
-        let num_tasks = 4;
-        let workload_size = 1024 * 1024;
-
-        for k in 0..num_tasks {
-            assert!(hashmap.insert(k, k).is_ok());
+    async fn scan_async_exactly_once() {
+        let workload_size = 1024;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        for k in 0..workload_size {
+            assert!(hashmap.insert_async(k, k).await.is_ok());
         }
+        let done = Arc::new(AtomicBool::new(false));
+        let hashmap_clone = hashmap.clone();
+        let done_clone = done.clone();
+        let writer = tokio::spawn(async move {
+            while !done_clone.load(Relaxed) {
+                for k in workload_size..workload_size * 16 {
+                    assert!(hashmap_clone.insert_async(k, k).await.is_ok());
+                }
+                for k in workload_size..workload_size * 16 {
+                    assert!(hashmap_clone.remove_async(&k).await.is_some());
+                }
+            }
+        });
 
-        let mut task_handles = Vec::with_capacity(num_tasks);
-        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
-        for task_id in 0..num_tasks {
-            let barrier_clone = barrier.clone();
-            let hashmap_clone = hashmap.clone();
-            task_handles.push(tokio::task::spawn(async move {
-                barrier_clone.wait().await;
-                if task_id == 0 {
-                    for k in num_tasks..workload_size {
-                        assert!(hashmap_clone.insert(k, k).is_ok());
-                    }
-                    for k in num_tasks..workload_size {
-                        assert!(hashmap_clone.remove(&k).is_some());
-                    }
-                } else {
-                    for k in 0..num_tasks {
-                        assert!(hashmap_clone.read(&k, |_, _| ()).is_some());
+        for _ in 0..64 {
+            let mut visited = vec![0_usize; workload_size];
+            hashmap
+                .scan_async(|k, _| {
+                    if *k < workload_size {
+                        visited[*k] += 1;
                     }
-                }
-            }));
+                })
+                .await;
+            assert!(visited.iter().all(|c| *c == 1));
         }
+        done.store(true, Relaxed);
+        assert!(writer.await.is_ok());
+    }
 
-        for r in futures::future::join_all(task_handles).await {
-            assert!(r.is_ok());
+    #[test]
+    fn snapshot() {
+        let hashmap: HashMap<usize, String> = HashMap::default();
+        assert!(hashmap.keys_snapshot().is_empty());
+        assert!(hashmap.values_snapshot().is_empty());
+        assert!(hashmap.entries_snapshot().is_empty());
+
+        let workload_size = 256;
+        for k in 0..workload_size {
+            assert!(hashmap.insert(k, k.to_string()).is_ok());
+        }
+        let mut keys = hashmap.keys_snapshot();
+        keys.sort_unstable();
+        assert_eq!(keys, (0..workload_size).collect::<Vec<_>>());
+
+        let values: BTreeSet<String> = hashmap.values_snapshot().into_iter().collect();
+        assert_eq!(values.len(), workload_size);
+        assert!((0..workload_size).all(|k| values.contains(&k.to_string())));
+
+        let mut entries = hashmap.entries_snapshot();
+        entries.sort_unstable();
+        assert_eq!(entries.len(), workload_size);
+        assert!(entries
+            .iter()
+            .enumerate()
+            .all(|(i, (k, v))| i == *k && *v == k.to_string()));
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn resize_policy() {
+        let default: HashMap<usize, usize> = HashMap::with_capacity(1024);
+        let eager: HashMap<usize, usize> =
+            HashMap::with_capacity(1024).with_load_factor_thresholds(0.25, 0.0625);
+        let capacity = default.capacity();
+        assert_eq!(eager.capacity(), capacity);
+        for k in 0..capacity / 2 {
+            assert!(default.insert(k, k).is_ok());
+            assert!(eager.insert(k, k).is_ok());
+        }
+        assert_eq!(default.capacity(), capacity);
+        assert!(eager.capacity() > capacity);
+
+        let limited: HashMap<usize, usize> = HashMap::default().with_max_growth_factor(2);
+        let mut prev_capacity = limited.capacity();
+        for k in 0..4096 {
+            assert!(limited.insert(k, k).is_ok());
+            let capacity = limited.capacity();
+            assert!(capacity <= prev_capacity.max(64) * 2);
+            prev_capacity = capacity;
+        }
+
+        assert!(std::panic::catch_unwind(|| {
+            HashMap::<usize, usize>::default().with_load_factor_thresholds(0.5, 0.5)
+        })
+        .is_err());
+        assert!(std::panic::catch_unwind(|| {
+            HashMap::<usize, usize>::default().with_load_factor_thresholds(0.5, 0.25)
+        })
+        .is_err());
+        assert!(std::panic::catch_unwind(|| {
+            HashMap::<usize, usize>::default().with_max_growth_factor(3)
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn entry_or_default() {
+        static NUM_DEFAULTS: AtomicUsize = AtomicUsize::new(0);
+        struct D(usize);
+        impl Default for D {
+            fn default() -> Self {
+                NUM_DEFAULTS.fetch_add(1, Relaxed);
+                D(0)
+            }
+        }
+
+        let hashmap: HashMap<usize, D> = HashMap::default();
+        assert!(hashmap.insert(1, D(1)).is_ok());
+        assert_eq!(hashmap.entry(1).or_default().get().0, 1);
+        assert_eq!(NUM_DEFAULTS.load(Relaxed), 0);
+
+        hashmap.entry(2).or_default().get_mut().0 += 2;
+        assert_eq!(NUM_DEFAULTS.load(Relaxed), 1);
+        assert_eq!(hashmap.read(&2, |_, v| v.0), Some(2));
+        assert_eq!(hashmap.entry(2).or_default().get().0, 2);
+        assert_eq!(NUM_DEFAULTS.load(Relaxed), 1);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn try_insert() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+        let hashmap: HashMap<usize, R> = HashMap::default();
+        for k in 0..256 {
+            assert!(hashmap.try_insert(k, R::new(&INST_CNT), |_| k).is_ok());
+        }
+        assert_eq!(INST_CNT.load(Relaxed), 256);
+        for k in 0..256 {
+            let existing = hashmap.try_insert(k, R::new(&INST_CNT), |v| v.0.load(Relaxed));
+            assert_eq!(existing, Err(257));
+            assert_eq!(INST_CNT.load(Relaxed), 256);
+        }
+        assert_eq!(hashmap.len(), 256);
+        drop(hashmap);
+        assert_eq!(INST_CNT.load(Relaxed), 0);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn try_insert_async() {
+        let num_tasks = 4;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        let inserted = Arc::new(AtomicUsize::new(0));
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        for task_id in 0..num_tasks {
+            let hashmap_clone = hashmap.clone();
+            let inserted_clone = inserted.clone();
+            task_handles.push(tokio::spawn(async move {
+                for k in 0..1024 {
+                    match hashmap_clone.try_insert_async(k, task_id, |v| *v).await {
+                        Ok(()) => {
+                            inserted_clone.fetch_add(1, Relaxed);
+                        }
+                        Err(v) => assert!(v < num_tasks),
+                    }
+                }
+            }));
+        }
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+        assert_eq!(inserted.load(Relaxed), 1024);
+        assert_eq!(hashmap.len(), 1024);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn modify_or_insert() {
+        let num_threads = 4;
+        let num_keys = 64;
+        let num_iters = 256;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        let defaults = Arc::new(AtomicUsize::new(0));
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for _ in 0..num_threads {
+            let hashmap_clone = hashmap.clone();
+            let defaults_clone = defaults.clone();
+            thread_handles.push(thread::spawn(move || {
+                for _ in 0..num_iters {
+                    for k in 0..num_keys {
+                        hashmap_clone.modify_or_insert(
+                            k,
+                            |v| *v += 1,
+                            || {
+                                defaults_clone.fetch_add(1, Relaxed);
+                                1
+                            },
+                        );
+                    }
+                }
+            }));
+        }
+        for handle in thread_handles {
+            assert!(handle.join().is_ok());
+        }
+        assert_eq!(defaults.load(Relaxed), num_keys);
+        assert_eq!(hashmap.len(), num_keys);
+        for k in 0..num_keys {
+            assert_eq!(hashmap.read(&k, |_, v| *v), Some(num_threads * num_iters));
+        }
+    }
+
+    #[test]
+    fn swap() {
+        let hashmap: HashMap<usize, String> = HashMap::default();
+        assert!(hashmap.swap(&1, "a".to_string()).is_none());
+        assert!(!hashmap.contains(&1));
+        assert!(hashmap.is_empty());
+
+        assert!(hashmap.insert(1, "b".to_string()).is_ok());
+        assert_eq!(hashmap.swap(&1, "c".to_string()), Some("b".to_string()));
+        assert_eq!(hashmap.swap(&1, "d".to_string()), Some("c".to_string()));
+        assert_eq!(hashmap.read(&1, |_, v| v.clone()), Some("d".to_string()));
+        assert!(hashmap.swap(&2, "e".to_string()).is_none());
+        assert_eq!(hashmap.len(), 1);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn swap_async() {
+        let num_tasks = 4;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        assert!(hashmap.insert(0, usize::MAX).is_ok());
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        for task_id in 0..num_tasks {
+            let hashmap_clone = hashmap.clone();
+            task_handles.push(tokio::spawn(async move {
+                let mut swapped = Vec::with_capacity(256);
+                for i in 0..256 {
+                    let val = task_id * 256 + i;
+                    swapped.push(hashmap_clone.swap_async(&0, val).await.unwrap());
+                    assert!(hashmap_clone.swap_async(&1, val).await.is_none());
+                }
+                swapped
+            }));
+        }
+        // Every value is swapped out exactly once except for the last one remaining in the map.
+        let mut values = Vec::with_capacity(num_tasks * 256 + 1);
+        for r in futures::future::join_all(task_handles).await {
+            values.extend(r.unwrap());
+        }
+        values.push(hashmap.read(&0, |_, v| *v).unwrap());
+        values.sort_unstable();
+        let mut expected: Vec<usize> = (0..num_tasks * 256).collect();
+        expected.push(usize::MAX);
+        assert_eq!(values, expected);
+        assert!(!hashmap.contains(&1));
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn read_any() {
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        assert!(hashmap.read_any(|k, _| *k).is_none());
+
+        let num_keys = 4096;
+        for k in 0..num_keys {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        let k = hashmap.read_any(|k, v| {
+            assert_eq!(k, v);
+            *k
+        });
+        assert!(k.is_some_and(|k| k < num_keys));
+
+        // Workers keep taking arbitrary entries until the `HashMap` becomes empty.
+        let num_threads = 4;
+        let processed = Arc::new(AtomicUsize::new(0));
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for _ in 0..num_threads {
+            let hashmap_clone = hashmap.clone();
+            let processed_clone = processed.clone();
+            thread_handles.push(thread::spawn(move || {
+                while let Some(k) = hashmap_clone.read_any(|k, _| *k) {
+                    if hashmap_clone.remove(&k).is_some() {
+                        processed_clone.fetch_add(1, Relaxed);
+                    }
+                }
+            }));
+        }
+        for handle in thread_handles {
+            assert!(handle.join().is_ok());
+        }
+        assert_eq!(processed.load(Relaxed), num_keys);
+        assert!(hashmap.read_any(|k, _| *k).is_none());
+    }
+
+    #[test]
+    fn compute() {
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+
+        // No-op on a vacant key.
+        hashmap.compute(1, |v| {
+            assert!(v.is_none());
+            None
+        });
+        assert!(!hashmap.contains(&1));
+
+        // Insert.
+        hashmap.compute(1, |v| {
+            assert!(v.is_none());
+            Some(7)
+        });
+        assert_eq!(hashmap.read(&1, |_, v| *v), Some(7));
+
+        // Overwrite.
+        hashmap.compute(1, |v| {
+            assert_eq!(v, Some(&7));
+            Some(11)
+        });
+        assert_eq!(hashmap.read(&1, |_, v| *v), Some(11));
+
+        // Remove.
+        hashmap.compute(1, |v| {
+            assert_eq!(v, Some(&11));
+            None
+        });
+        assert!(!hashmap.contains(&1));
+        assert!(hashmap.is_empty());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn compute_async() {
+        let num_tasks = 4;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        for _ in 0..num_tasks {
+            let hashmap_clone = hashmap.clone();
+            task_handles.push(tokio::spawn(async move {
+                for k in 0..1024 {
+                    hashmap_clone
+                        .compute_async(k % 16, |v| Some(v.map_or(1, |v| v + 1)))
+                        .await;
+                }
+            }));
+        }
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+        for k in 0..16 {
+            assert_eq!(
+                hashmap.read_async(&k, |_, v| *v).await,
+                Some(num_tasks * 64)
+            );
+            hashmap.compute_async(k, |_| None).await;
+        }
+        assert!(hashmap.is_empty());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn modify_or_insert_async() {
+        let num_tasks = 4;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        for _ in 0..num_tasks {
+            let hashmap_clone = hashmap.clone();
+            task_handles.push(tokio::spawn(async move {
+                for k in 0..1024 {
+                    hashmap_clone
+                        .modify_or_insert_async(k % 16, |v| *v += 1, || 1)
+                        .await;
+                }
+            }));
+        }
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+        assert_eq!(hashmap.len(), 16);
+        for k in 0..16 {
+            assert_eq!(hashmap.read(&k, |_, v| *v), Some(num_tasks * 64));
+        }
+    }
+
+    #[derive(Default)]
+    struct IdentityHasher(u64);
+
+    impl Hasher for IdentityHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+        fn write(&mut self, _: &[u8]) {
+            unreachable!();
+        }
+        fn write_u64(&mut self, i: u64) {
+            self.0 = i;
+        }
+    }
+
+    #[derive(Clone)]
+    struct FixedState(u64);
+
+    impl BuildHasher for FixedState {
+        type Hasher = DefaultHasher;
+        fn build_hasher(&self) -> DefaultHasher {
+            let mut hasher = DefaultHasher::new();
+            hasher.write_u64(self.0);
+            hasher
+        }
+    }
+
+    #[test]
+    fn custom_hasher() {
+        let num_keys = 1024;
+        let hashmap1 = HashMap::with_capacity_and_hasher(num_keys, FixedState(7));
+        let hashmap2 = HashMap::with_capacity_and_hasher(num_keys, FixedState(7));
+        let hashmap3 = HashMap::with_capacity_and_hasher(num_keys, FixedState(11));
+        assert!(hashmap1.capacity() >= num_keys);
+        assert_eq!(hashmap1.capacity(), hashmap3.capacity());
+        for k in 0..num_keys {
+            assert!(hashmap1.insert(k, k).is_ok());
+            assert!(hashmap2.insert(k, k).is_ok());
+            assert!(hashmap3.insert(k, k).is_ok());
+        }
+        assert!((0..num_keys).all(|k| hashmap1.bucket_index(&k) == hashmap2.bucket_index(&k)));
+        assert!((0..num_keys).any(|k| hashmap1.bucket_index(&k) != hashmap3.bucket_index(&k)));
+        assert!(
+            (0..num_keys).all(|k| hashmap1.hasher().hash_one(k) == HashTable::hash(&hashmap1, &k))
+        );
+
+        let hashmap4: HashMap<usize, usize, FixedState> = HashMap::with_hasher(FixedState(7));
+        assert_eq!(hashmap4.capacity(), 0);
+        assert!(hashmap4.insert(1, 1).is_ok());
+        assert_eq!(hashmap4.read(&1, |_, v| *v), Some(1));
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn fold() {
+        let num_keys = 4096;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        assert_eq!(hashmap.fold(0, |sum, _, v| sum + v), 0);
+        for k in 0..num_keys {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+
+        // Entries inserted during the operation do not affect the existing ones.
+        let hashmap_clone = hashmap.clone();
+        let task_handle = tokio::task::spawn(async move {
+            for k in num_keys..num_keys * 4 {
+                assert!(hashmap_clone.insert_async(k, 0).await.is_ok());
+            }
+        });
+        let expected = num_keys * (num_keys - 1) / 2;
+        assert_eq!(hashmap.fold(0, |sum, _, v| sum + v), expected);
+        assert_eq!(hashmap.fold_async(0, |sum, _, v| sum + v).await, expected);
+        assert!(task_handle.await.is_ok());
+
+        let max = hashmap.fold(None, |max: Option<usize>, k, _| max.max(Some(*k)));
+        assert_eq!(max, Some(num_keys * 4 - 1));
+    }
+
+    #[test]
+    fn reserve_overflow() {
+        let hashmap: HashMap<usize, usize> = HashMap::with_capacity(1000);
+        assert_eq!(hashmap.capacity(), 1024);
+        assert!(hashmap.reserve(usize::MAX).is_none());
+        assert!(hashmap.reserve(usize::MAX - 1024).is_none());
+        assert!(hashmap
+            .reserve((1_usize << (usize::BITS - 1)) - 1023)
+            .is_none());
+        assert_eq!(hashmap.capacity(), 1024);
+        assert_eq!(
+            *hashmap.capacity_range().end(),
+            1_usize << (usize::BITS - 1)
+        );
+        assert!(hashmap.insert(1, 1).is_ok());
+        assert_eq!(hashmap.read(&1, |_, v| *v), Some(1));
+
+        let empty: HashMap<usize, usize> = HashMap::default();
+        assert!(empty.reserve(usize::MAX).is_none());
+        assert!(empty.reserve(usize::MAX / 2 + 2).is_none());
+        assert_eq!(empty.capacity(), 0);
+        assert!(empty.insert(1, 1).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn with_capacity_overflow() {
+        // The capacity is clamped to the maximum capacity, yet the bucket array is too large.
+        let _hashmap: HashMap<usize, usize> = HashMap::with_capacity(usize::MAX);
+    }
+
+    #[tokio::test]
+    async fn get_cloned() {
+        let hashmap: HashMap<usize, String> = HashMap::default();
+        assert!(hashmap.get_cloned(&1).is_none());
+        assert!(hashmap.insert(1, String::from("one")).is_ok());
+
+        let cloned = hashmap.get_cloned(&1).unwrap();
+        assert!(hashmap.update(&1, |_, v| v.push('!')).is_some());
+        assert_eq!(cloned, "one");
+        assert_eq!(hashmap.get_cloned_async(&1).await.unwrap(), "one!");
+
+        assert!(hashmap.remove(&1).is_some());
+        assert_eq!(cloned, "one");
+        assert!(hashmap.get_cloned_async(&1).await.is_none());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn extract_if() {
+        let num_keys = 4096;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        for k in 0..num_keys {
+            assert!(hashmap.insert(k, k * 2).is_ok());
+        }
+        let hashmap_clone = hashmap.clone();
+        let thread_handle = thread::spawn(move || {
+            for k in num_keys..num_keys * 4 {
+                assert!(hashmap_clone.insert(k, k * 2).is_ok());
+            }
+        });
+        let mut extracted = hashmap.extract_if(|k, v| *k < num_keys && k % 3 == 0 && *v == k * 2);
+        thread_handle.join().unwrap();
+
+        extracted.sort_unstable();
+        let expected: Vec<(usize, usize)> = (0..num_keys)
+            .filter(|k| k % 3 == 0)
+            .map(|k| (k, k * 2))
+            .collect();
+        assert_eq!(extracted, expected);
+        assert_eq!(hashmap.len(), num_keys * 4 - expected.len());
+        for k in 0..num_keys * 4 {
+            assert_eq!(hashmap.contains(&k), k >= num_keys || k % 3 != 0);
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn transform_values() {
+        let num_keys = 4096;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::default();
+        for k in 0..num_keys {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        hashmap.transform_values(|_, v| v + 1);
+        assert!((0..num_keys).all(|k| hashmap.read(&k, |_, v| *v) == Some(k + 1)));
+
+        let hashmap_clone = hashmap.clone();
+        let thread_handle = thread::spawn(move || {
+            for k in num_keys..num_keys * 8 {
+                assert!(hashmap_clone.insert(k, k + 1).is_ok());
+            }
+            for k in num_keys..num_keys * 8 {
+                assert!(hashmap_clone.remove(&k).is_some());
+            }
+        });
+        hashmap.transform_values(|k, v| if *k < num_keys { v + 1 } else { v });
+        thread_handle.join().unwrap();
+
+        assert_eq!(hashmap.len(), num_keys);
+        assert!((0..num_keys).all(|k| hashmap.read(&k, |_, v| *v) == Some(k + 2)));
+    }
+
+    #[test]
+    fn merge() {
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        let other: HashMap<usize, usize> = HashMap::default();
+        for k in 0..1024 {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        for k in 512..4096 {
+            assert!(other.insert(k, 1).is_ok());
+        }
+
+        let mut num_conflicts = 0;
+        hashmap.merge(other, |k, existing, incoming| {
+            assert_eq!(*existing, *k);
+            num_conflicts += 1;
+            existing + incoming
+        });
+        assert_eq!(num_conflicts, 512);
+        assert_eq!(hashmap.len(), 4096);
+        for k in 0..4096 {
+            let expected = if k < 512 {
+                k
+            } else if k < 1024 {
+                k + 1
+            } else {
+                1
+            };
+            assert_eq!(hashmap.read(&k, |_, v| *v), Some(expected));
+        }
+
+        hashmap.merge(HashMap::default(), |_, _, _| unreachable!());
+        assert_eq!(hashmap.len(), 4096);
+    }
+
+    #[test]
+    fn rehash_with() {
+        let num_keys = 4096;
+        let hashmap = HashMap::with_hasher(FixedState(7));
+        for k in 0..num_keys {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        let rehashed = hashmap.rehash_with(FixedState(11));
+        assert_eq!(rehashed.len(), num_keys);
+        assert!(rehashed.capacity() >= num_keys);
+        assert!((0..num_keys).all(|k| rehashed.read(&k, |_, v| *v) == Some(k)));
+        assert!((0..num_keys).any(|k| hashmap.bucket_index(&k) != rehashed.bucket_index(&k)));
+        assert!(
+            (0..num_keys).all(|k| rehashed.hasher().hash_one(k) == HashTable::hash(&rehashed, &k))
+        );
+
+        let empty: HashMap<usize, usize> = HashMap::default();
+        let rehashed = empty.rehash_with(FixedState(7));
+        assert!(rehashed.is_empty());
+        assert_eq!(rehashed.capacity(), 0);
+    }
+
+    #[test]
+    fn memory_usage() {
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        assert_eq!(hashmap.memory_usage(), 0);
+        assert!(hashmap.insert(0, 0).is_ok());
+        let initial_usage = hashmap.memory_usage();
+        assert!(initial_usage > 0);
+
+        let num_keys = 4096;
+        for k in 1..num_keys {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        let peak_usage = hashmap.memory_usage();
+        assert!(peak_usage > initial_usage);
+
+        for k in 1..num_keys {
+            assert!(hashmap.remove(&k).is_some());
+        }
+        for k in num_keys..num_keys * 2 {
+            // Removing an entry lets the `HashMap` shrink.
+            if hashmap.capacity() < num_keys {
+                break;
+            }
+            assert!(hashmap.insert(k, k).is_ok());
+            assert!(hashmap.remove(&k).is_some());
+        }
+        assert!(hashmap.capacity() < num_keys);
+        assert_eq!(hashmap.len(), 1);
+        assert!(hashmap.memory_usage() < peak_usage);
+
+        let hashmap: HashMap<u64, u64, BuildHasherDefault<IdentityHasher>> =
+            HashMap::with_hasher(BuildHasherDefault::default());
+        for k in 0..64 {
+            assert!(hashmap.insert(k << 32, k).is_ok());
+        }
+        let baseline: HashMap<u64, u64, BuildHasherDefault<IdentityHasher>> =
+            HashMap::with_capacity_and_hasher(hashmap.capacity(), BuildHasherDefault::default());
+        assert!(baseline.insert(0, 0).is_ok());
+        assert_eq!(baseline.capacity(), hashmap.capacity());
+        assert!(hashmap.memory_usage() > baseline.memory_usage());
+    }
+
+    #[test]
+    fn resize_disabled() {
+        let hashmap: HashMap<u64, u64> = HashMap::with_capacity(1024).with_resize_disabled();
+        assert_eq!(hashmap.capacity(), 1024);
+        for k in 0..16384 {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        assert_eq!(hashmap.capacity(), 1024);
+        assert!(hashmap.bucket_occupancy().iter().all(|n| *n > 32));
+        assert!((0..16384).all(|k| hashmap.read(&k, |_, v| *v) == Some(k)));
+        assert!(hashmap.read(&16384, |_, v| *v).is_none());
+
+        let reserved = hashmap.reserve(1 << 20);
+        assert_eq!(hashmap.capacity(), 1024);
+        drop(reserved);
+
+        for k in 0..16384 {
+            assert_eq!(hashmap.remove(&k), Some((k, k)));
+        }
+        assert!(hashmap.is_empty());
+        assert_eq!(hashmap.capacity(), 1024);
+
+        let hashmap: HashMap<u64, u64> = HashMap::default().with_resize_disabled();
+        assert_eq!(hashmap.capacity(), 0);
+        for k in 0..1024 {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        assert_eq!(hashmap.capacity(), 64);
+        assert_eq!(hashmap.len(), 1024);
+    }
+
+    #[test]
+    fn allocation_failure() {
+        use crate::hash_table::bucket_array::MAX_ALLOCATION_SIZE;
+
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        assert!(hashmap.insert(0, 0).is_ok());
+        assert_eq!(hashmap.capacity(), 64);
+
+        // Bucket arrays larger than `128` entries cannot be allocated.
+        MAX_ALLOCATION_SIZE.with(|s| s.set(size_of::<(usize, usize)>() * 128));
+        for k in 1..4096 {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        assert_eq!(hashmap.capacity(), 128);
+        assert_eq!(hashmap.len(), 4096);
+        assert!((0..4096).all(|k| hashmap.read(&k, |_, v| *v) == Some(k)));
+
+        MAX_ALLOCATION_SIZE.with(|s| s.set(usize::MAX));
+        for k in 4096..8192 {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        assert!(hashmap.capacity() > 128);
+        assert_eq!(hashmap.len(), 8192);
+        assert!((0..8192).all(|k| hashmap.read(&k, |_, v| *v) == Some(k)));
+        for k in 0..8192 {
+            assert_eq!(hashmap.remove(&k), Some((k, k)));
+        }
+        assert!(hashmap.is_empty());
+    }
+
+    #[test]
+    fn replace_key() {
+        #[derive(Debug)]
+        struct Versioned(usize, usize);
+        impl PartialEq for Versioned {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Versioned {}
+        impl Hash for Versioned {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.hash(state);
+            }
+        }
+
+        let hashmap: HashMap<Versioned, usize> = HashMap::default();
+        for k in 0..256 {
+            assert!(hashmap.insert(Versioned(k, 0), k).is_ok());
+        }
+        for k in 0..256 {
+            let Entry::Occupied(mut o) = hashmap.entry(Versioned(k, 1)) else {
+                unreachable!();
+            };
+            let old_key = o.replace_key(Versioned(k, k + 1));
+            assert_eq!(old_key.1, 0);
+            assert_eq!(o.key().1, k + 1);
+            assert_eq!(*o.get(), k);
+        }
+        assert_eq!(hashmap.len(), 256);
+        for k in 0..256 {
+            assert_eq!(
+                hashmap.read(&Versioned(k, 0), |key, v| (key.1, *v)),
+                Some((k + 1, k))
+            );
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic]
+    fn replace_key_hash_mismatch() {
+        #[derive(Debug)]
+        struct Inconsistent(usize, usize);
+        impl PartialEq for Inconsistent {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Inconsistent {}
+        impl Hash for Inconsistent {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.hash(state);
+                self.1.hash(state);
+            }
+        }
+
+        let hashmap: HashMap<Inconsistent, usize> = HashMap::default();
+        assert!(hashmap.insert(Inconsistent(1, 0), 1).is_ok());
+        let Entry::Occupied(mut o) = hashmap.entry(Inconsistent(1, 0)) else {
+            unreachable!();
+        };
+        let _old_key = o.replace_key(Inconsistent(1, 1));
+    }
+
+    #[test]
+    fn bucket_collision() {
+        let hashmap: HashMap<u64, u64> = HashMap::with_capacity(1024).with_resize_disabled();
+        let bucket_index = hashmap.bucket_index(&0);
+        let colliding: Vec<u64> = (0..)
+            .filter(|k| hashmap.bucket_index(k) == bucket_index)
+            .take(48)
+            .collect();
+        for k in &colliding {
+            assert!(hashmap.insert(*k, *k).is_ok());
+        }
+        assert_eq!(hashmap.bucket_occupancy()[bucket_index], 48);
+        assert!(colliding
+            .iter()
+            .all(|k| hashmap.bucket_index(k) == bucket_index));
+        assert!(colliding
+            .iter()
+            .all(|k| hashmap.read(k, |_, v| *v) == Some(*k)));
+        for k in colliding.iter().step_by(2) {
+            assert_eq!(hashmap.remove(k), Some((*k, *k)));
+        }
+        for (i, k) in colliding.iter().enumerate() {
+            assert_eq!(hashmap.contains(k), i % 2 == 1);
+        }
+    }
+
+    #[test]
+    fn bucket_occupancy() {
+        let hashmap: HashMap<u64, u64> = HashMap::with_capacity(1 << 14);
+        assert!(hashmap.bucket_occupancy().iter().all(|n| *n == 0));
+        for k in 0..8192 {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        let occupancy = hashmap.bucket_occupancy();
+        assert_eq!(occupancy.len(), hashmap.capacity() / 32);
+        assert_eq!(occupancy.iter().sum::<usize>(), 8192);
+        assert!(occupancy.iter().all(|n| (1..=48).contains(n)));
+
+        // Keys that only differ in the upper bits are mapped to the same bucket.
+        let clustered: HashMap<u64, u64, BuildHasherDefault<IdentityHasher>> =
+            HashMap::with_capacity_and_hasher(8192, BuildHasherDefault::default());
+        for k in 0..256 {
+            assert!(clustered.insert(k << 48, k).is_ok());
+        }
+        let occupancy = clustered.bucket_occupancy();
+        assert_eq!(occupancy.iter().max(), Some(&256));
+        assert_eq!(occupancy.iter().filter(|n| **n != 0).count(), 1);
+    }
+
+    #[test]
+    fn max_link_depth() {
+        #[derive(Default)]
+        struct ConstantHasher;
+        impl Hasher for ConstantHasher {
+            fn finish(&self) -> u64 {
+                0
+            }
+            fn write(&mut self, _: &[u8]) {}
+        }
+
+        // The keys are mapped to the same bucket until the array has `512` buckets.
+        let keys: Vec<u64> = (0..256).map(|k| k << 48).collect();
+        let num_entries_per_bucket = |hashmap: &HashMap<u64, u64, _>| {
+            let mut num_entries = vec![0; hashmap.capacity() / 32];
+            for k in &keys {
+                num_entries[hashmap.bucket_index(k)] += 1;
+            }
+            num_entries.into_iter().max().unwrap()
+        };
+
+        let unbounded: HashMap<u64, u64, BuildHasherDefault<IdentityHasher>> =
+            HashMap::with_capacity_and_hasher(8192, BuildHasherDefault::default());
+        let bounded: HashMap<u64, u64, BuildHasherDefault<IdentityHasher>> =
+            HashMap::with_capacity_and_hasher(8192, BuildHasherDefault::default())
+                .with_max_link_depth(1);
+        for k in &keys {
+            assert!(unbounded.insert(*k, *k).is_ok());
+            assert!(bounded.insert(*k, *k).is_ok());
+        }
+        assert_eq!(unbounded.capacity(), 8192);
+        assert_eq!(num_entries_per_bucket(&unbounded), 256);
+        assert!(bounded.capacity() > 8192);
+        assert!(num_entries_per_bucket(&bounded) <= 40);
+        assert!(keys.iter().all(|k| bounded.read(k, |_, v| *v) == Some(*k)));
+
+        // Growing the array does not help if all the hash values are the same.
+        let same_hash: HashMap<u64, u64, BuildHasherDefault<ConstantHasher>> =
+            HashMap::with_hasher(BuildHasherDefault::default()).with_max_link_depth(1);
+        for k in 0..256 {
+            assert!(same_hash.insert(k, k).is_ok());
+        }
+        assert_eq!(same_hash.len(), 256);
+        assert!(same_hash.capacity() <= 512);
+    }
+
+    #[test]
+    fn mixing() {
+        let num_keys = 1024;
+        let hashmap: HashMap<u64, u64, BuildHasherDefault<IdentityHasher>> =
+            HashMap::with_capacity_and_hasher(num_keys, BuildHasherDefault::default());
+        for k in 0..num_keys as u64 {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        let num_buckets = hashmap.capacity() / 32;
+        let occupied_buckets = |hashmap: &HashMap<u64, u64, _>| {
+            let mut occupied = vec![false; num_buckets];
+            for k in 0..num_keys as u64 {
+                occupied[hashmap.bucket_index(&k)] = true;
+            }
+            occupied.into_iter().filter(|o| *o).count()
+        };
+        // The ratio of the most occupied bucket to the mean bucket occupancy.
+        let spread = |hashmap: &HashMap<u64, u64, _>| {
+            let occupancy = hashmap.bucket_occupancy();
+            let max = occupancy.iter().copied().max().unwrap();
+            max * occupancy.len() / occupancy.iter().sum::<usize>()
+        };
+        assert_eq!(occupied_buckets(&hashmap), 1);
+        assert_eq!(spread(&hashmap), num_buckets);
+
+        let hashmap = hashmap.with_mixing(true);
+        assert_eq!(hashmap.len(), num_keys);
+        assert_eq!(hashmap.capacity() / 32, num_buckets);
+        assert!(occupied_buckets(&hashmap) >= num_buckets * 3 / 4);
+        assert!(spread(&hashmap) <= 2);
+        for k in 0..num_keys as u64 {
+            assert_eq!(hashmap.read(&k, |_, v| *v), Some(k));
+        }
+
+        let hashmap = hashmap.with_mixing(false);
+        assert_eq!(hashmap.len(), num_keys);
+        for k in 0..num_keys as u64 {
+            assert_eq!(hashmap.remove(&k), Some((k, k)));
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn iter() {
+        let data_size = 4096;
+        for _ in 0..16 {
+            let hashmap: Arc<HashMap<u64, u64>> = Arc::new(HashMap::default());
+            let hashmap_copied = hashmap.clone();
+            let barrier = Arc::new(Barrier::new(2));
+            let barrier_copied = barrier.clone();
+            let inserted = Arc::new(AtomicU64::new(0));
+            let inserted_copied = inserted.clone();
+            let removed = Arc::new(AtomicU64::new(data_size));
+            let removed_copied = removed.clone();
+            let thread_handle = thread::spawn(move || {
+                // test insert
+                for _ in 0..2 {
+                    barrier_copied.wait();
+                    let mut scanned = 0;
+                    let mut checker = BTreeSet::new();
+                    let max = inserted_copied.load(Acquire);
+                    hashmap_copied.retain(|k, _| {
+                        scanned += 1;
+                        checker.insert(*k);
+                        true
+                    });
+                    for key in 0..max {
+                        assert!(checker.contains(&key));
+                    }
+                }
+                // test remove
+                for _ in 0..2 {
+                    barrier_copied.wait();
+                    let mut scanned = 0;
+                    let max = removed_copied.load(Acquire);
+                    hashmap_copied.retain(|k, _| {
+                        scanned += 1;
+                        assert!(*k < max);
+                        true
+                    });
+                }
+            });
+            // insert
+            barrier.wait();
+            for i in 0..data_size {
+                if i == data_size / 2 {
+                    barrier.wait();
+                }
+                assert!(hashmap.insert(i, i).is_ok());
+                inserted.store(i, Release);
+            }
+            // remove
+            barrier.wait();
+            for i in (0..data_size).rev() {
+                if i == data_size / 2 {
+                    barrier.wait();
+                }
+                assert!(hashmap.remove(&i).is_some());
+                removed.store(i, Release);
+            }
+            thread_handle.join().unwrap();
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn read() {
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::default());
+        let num_tasks = 4;
+        let workload_size = 1024 * 1024;
+
+        for k in 0..num_tasks {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        for task_id in 0..num_tasks {
+            let barrier_clone = barrier.clone();
+            let hashmap_clone = hashmap.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_clone.wait().await;
+                if task_id == 0 {
+                    for k in num_tasks..workload_size {
+                        assert!(hashmap_clone.insert(k, k).is_ok());
+                    }
+                    for k in num_tasks..workload_size {
+                        assert!(hashmap_clone.remove(&k).is_some());
+                    }
+                } else {
+                    for k in 0..num_tasks {
+                        assert!(hashmap_clone.read(&k, |_, _| ()).is_some());
+                    }
+                }
+            }));
+        }
+
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
         }
     }
 
@@ -749,15 +2105,18 @@ mod hashmap_test {
 #[cfg(test)]
 mod hashindex_test {
     use crate::ebr::Guard;
-    use crate::hash_index::Iter;
+    use crate::hash_index::{Entry, Iter};
     use crate::HashIndex;
+    use futures::task::noop_waker_ref;
     use proptest::strategy::{Strategy, ValueTree};
     use proptest::test_runner::TestRunner;
     use std::collections::BTreeSet;
+    use std::future::Future;
     use std::panic::UnwindSafe;
     use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
-    use std::sync::atomic::{AtomicU64, AtomicUsize};
+    use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
     use std::sync::{Arc, Barrier};
+    use std::task::Context;
     use std::thread;
     use tokio::sync::Barrier as AsyncBarrier;
 
@@ -787,48 +2146,227 @@ mod hashindex_test {
 
     #[cfg_attr(miri, ignore)]
     #[test]
-    fn compare() {
-        let hashindex1: HashIndex<String, usize> = HashIndex::new();
-        let hashindex2: HashIndex<String, usize> = HashIndex::new();
-        assert_eq!(hashindex1, hashindex2);
-
-        assert!(hashindex1.insert("Hi".to_string(), 1).is_ok());
-        assert_ne!(hashindex1, hashindex2);
+    fn compare() {
+        let hashindex1: HashIndex<String, usize> = HashIndex::new();
+        let hashindex2: HashIndex<String, usize> = HashIndex::new();
+        assert_eq!(hashindex1, hashindex2);
+
+        assert!(hashindex1.insert("Hi".to_string(), 1).is_ok());
+        assert_ne!(hashindex1, hashindex2);
+
+        assert!(hashindex2.insert("Hello".to_string(), 2).is_ok());
+        assert_ne!(hashindex1, hashindex2);
+
+        assert!(hashindex1.insert("Hello".to_string(), 2).is_ok());
+        assert_ne!(hashindex1, hashindex2);
+
+        assert!(hashindex2.insert("Hi".to_string(), 1).is_ok());
+        assert_eq!(hashindex1, hashindex2);
+
+        assert!(hashindex1.remove("Hi"));
+        assert_ne!(hashindex1, hashindex2);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn clear() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+        let hashindex: HashIndex<usize, R> = HashIndex::default();
+
+        let workload_size = 1_usize << 18;
+
+        for _ in 0..2 {
+            for k in 0..workload_size {
+                assert!(hashindex.insert_async(k, R::new(&INST_CNT)).await.is_ok());
+            }
+            assert!(INST_CNT.load(Relaxed) >= workload_size);
+            assert_eq!(hashindex.len(), workload_size);
+            hashindex.clear_async().await;
+        }
+        drop(hashindex);
+
+        while INST_CNT.load(Relaxed) != 0 {
+            drop(Guard::new());
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[test]
+    fn clear_async_cancel() {
+        let hashindex: HashIndex<usize, usize> = HashIndex::default();
+        for k in 0..64 {
+            assert!(hashindex.insert(k, k).is_ok());
+        }
+
+        // Drop a `clear_async` future waiting for a locked bucket.
+        let entry = hashindex.get(&7).unwrap();
+        let mut future_clear = Box::pin(hashindex.clear_async());
+        let mut context = Context::from_waker(noop_waker_ref());
+        assert!(future_clear.as_mut().poll(&mut context).is_pending());
+        drop(entry);
+        drop(future_clear);
+        assert_eq!(hashindex.len(), 0);
+        assert!((0..64).all(|k| !hashindex.contains(&k)));
+    }
+
+    #[test]
+    fn get_cloned() {
+        let hashindex: HashIndex<usize, String> = HashIndex::default();
+        assert!(hashindex.get_cloned(&1).is_none());
+        assert!(hashindex.insert(1, String::from("one")).is_ok());
+
+        let cloned = hashindex.get_cloned(&1).unwrap();
+        if let Some(entry) = hashindex.get(&1) {
+            entry.update(String::from("two"));
+        }
+        assert_eq!(cloned, "one");
+        assert_eq!(hashindex.get_cloned(&1).unwrap(), "two");
+
+        assert!(hashindex.remove(&1));
+        assert_eq!(cloned, "one");
+        assert!(hashindex.get_cloned(&1).is_none());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn contains_key() {
+        let num_threads = 4;
+        let workload_size = 4096;
+        let hashindex: Arc<HashIndex<usize, usize>> = Arc::new(HashIndex::default());
+        let barrier = Arc::new(Barrier::new(num_threads + 1));
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for task_id in 0..num_threads {
+            let hashindex = hashindex.clone();
+            let barrier = barrier.clone();
+            thread_handles.push(thread::spawn(move || {
+                barrier.wait();
+                let range = (task_id * workload_size)..((task_id + 1) * workload_size);
+                for k in range.clone() {
+                    assert!(hashindex.insert(k, k).is_ok());
+                }
+                for k in range.clone().filter(|k| k % 2 == 0) {
+                    assert!(hashindex.remove(&k));
+                }
+                let guard = Guard::new();
+                for k in range {
+                    assert_eq!(hashindex.contains_key(&k, &guard), k % 2 == 1);
+                }
+            }));
+        }
 
-        assert!(hashindex2.insert("Hello".to_string(), 2).is_ok());
-        assert_ne!(hashindex1, hashindex2);
+        // Odd keys are never removed, therefore they are found once observed even if the
+        // `HashIndex` is being resized.
+        barrier.wait();
+        for _ in 0..16 {
+            let guard = Guard::new();
+            for k in (1..num_threads * workload_size).step_by(2) {
+                if hashindex.peek_with(&k, |_, _| ()).is_some() {
+                    assert!(hashindex.contains_key(&k, &guard));
+                }
+            }
+        }
+        for handle in thread_handles {
+            assert!(handle.join().is_ok());
+        }
+        let guard = Guard::new();
+        for k in 0..num_threads * workload_size {
+            assert_eq!(
+                hashindex.contains_key(&k, &guard),
+                hashindex.peek_with(&k, |_, _| ()).is_some()
+            );
+        }
+    }
 
-        assert!(hashindex1.insert("Hello".to_string(), 2).is_ok());
-        assert_ne!(hashindex1, hashindex2);
+    #[test]
+    fn into_iter() {
+        let hashindex: HashIndex<usize, usize> = HashIndex::default();
+        for k in 0..1024 {
+            assert!(hashindex.insert(k, k * 2).is_ok());
+        }
+        for k in (0..1024).step_by(2) {
+            assert!(hashindex.remove(&k));
+        }
 
-        assert!(hashindex2.insert("Hi".to_string(), 1).is_ok());
-        assert_eq!(hashindex1, hashindex2);
+        let guard = Guard::new();
+        let value_ref = hashindex.peek(&1, &guard).unwrap();
+        let mut num_entries = 0;
+        let mut collected = std::collections::HashMap::new();
+        for (k, v) in hashindex {
+            num_entries += 1;
+            assert!(collected.insert(k, v).is_none());
+        }
+        assert_eq!(*value_ref, 2);
+        drop(guard);
 
-        assert!(hashindex1.remove("Hi"));
-        assert_ne!(hashindex1, hashindex2);
+        let expected: std::collections::HashMap<usize, usize> =
+            (1..1024).step_by(2).map(|k| (k, k * 2)).collect();
+        assert_eq!(num_entries, expected.len());
+        assert_eq!(collected, expected);
     }
 
     #[cfg_attr(miri, ignore)]
-    #[tokio::test]
-    async fn clear() {
+    #[test]
+    fn clear_snapshot() {
         static INST_CNT: AtomicUsize = AtomicUsize::new(0);
-        let hashindex: HashIndex<usize, R> = HashIndex::default();
-
-        let workload_size = 1_usize << 18;
+        let hashindex: Arc<HashIndex<usize, (usize, R)>> = Arc::new(HashIndex::default());
+        let workload_size = 4096;
+        for k in 0..workload_size {
+            assert!(hashindex.insert(k, (k, R::new(&INST_CNT))).is_ok());
+        }
 
-        for _ in 0..2 {
-            for k in 0..workload_size {
-                assert!(hashindex.insert_async(k, R::new(&INST_CNT)).await.is_ok());
+        let barrier = Arc::new(Barrier::new(2));
+        let hashindex_clone = hashindex.clone();
+        let barrier_clone = barrier.clone();
+        let reader = thread::spawn(move || {
+            let guard = Guard::new();
+            let captured = (0..workload_size)
+                .map(|k| hashindex_clone.peek(&k, &guard).unwrap())
+                .collect::<Vec<_>>();
+            barrier_clone.wait();
+            barrier_clone.wait();
+            assert!((0..workload_size).all(|k| hashindex_clone.peek(&k, &guard).is_none()));
+            for (k, v) in captured.into_iter().enumerate() {
+                assert_eq!(v.0, k);
             }
-            assert!(INST_CNT.load(Relaxed) >= workload_size);
-            assert_eq!(hashindex.len(), workload_size);
-            hashindex.clear_async().await;
+        });
+
+        barrier.wait();
+        hashindex.clear();
+        assert_eq!(hashindex.len(), 0);
+        assert!(hashindex.is_empty());
+        barrier.wait();
+        reader.join().unwrap();
+
+        assert!(hashindex.insert(1, (1, R::new(&INST_CNT))).is_ok());
+        assert_eq!(hashindex.len(), 1);
+
+        let num_threads = 4;
+        let barrier = Arc::new(Barrier::new(num_threads + 1));
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for thread_id in 0..num_threads {
+            let hashindex_clone = hashindex.clone();
+            let barrier_clone = barrier.clone();
+            thread_handles.push(thread::spawn(move || {
+                barrier_clone.wait();
+                for k in (thread_id * workload_size)..((thread_id + 1) * workload_size) {
+                    let _result = hashindex_clone.insert(k, (k, R::new(&INST_CNT)));
+                }
+            }));
+        }
+        barrier.wait();
+        for _ in 0..16 {
+            hashindex.clear();
+            thread::yield_now();
         }
+        for handle in thread_handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(hashindex.len(), hashindex.iter(&Guard::new()).count());
         drop(hashindex);
 
         while INST_CNT.load(Relaxed) != 0 {
             drop(Guard::new());
-            tokio::task::yield_now().await;
+            thread::yield_now();
         }
     }
 
@@ -928,6 +2466,157 @@ mod hashindex_test {
         }
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn len() {
+        let num_tasks = 4;
+        let workload_size = 1024;
+        let hashindex: Arc<HashIndex<usize, usize>> = Arc::default();
+        for _ in 0..4 {
+            let mut task_handles = Vec::with_capacity(num_tasks);
+            for task_id in 0..num_tasks {
+                let hashindex_clone = hashindex.clone();
+                task_handles.push(tokio::spawn(async move {
+                    // Keys overlap with the neighboring task so that insertions and removals fail.
+                    let range = task_id * workload_size..(task_id + 2) * workload_size;
+                    for k in range.clone() {
+                        let _result = hashindex_clone.insert_async(k, k).await;
+                    }
+                    for k in range.clone().step_by(3) {
+                        let _result = hashindex_clone.remove_async(&k).await;
+                    }
+                    hashindex_clone.retain_async(|k, _| k % 5 != 0).await;
+                    for k in range.step_by(7) {
+                        let _result = hashindex_clone.insert(k, k);
+                        if let Entry::Occupied(o) = hashindex_clone.entry(k + 1) {
+                            o.remove_entry();
+                        }
+                    }
+                    hashindex_clone.retain(|k, _| k % 11 != 0);
+                }));
+            }
+            for r in futures::future::join_all(task_handles).await {
+                assert!(r.is_ok());
+            }
+            assert_eq!(hashindex.len(), hashindex.iter(&Guard::new()).count());
+        }
+        hashindex.clear();
+        assert_eq!(hashindex.len(), 0);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn retain_concurrent_read() {
+        let num_readers = 4;
+        let workload_size = 1024;
+        for _ in 0..16 {
+            let hashindex: Arc<HashIndex<usize, usize>> = Arc::default();
+            for k in 0..workload_size {
+                assert!(hashindex.insert(k, k).is_ok());
+            }
+            let barrier = Arc::new(Barrier::new(num_readers + 2));
+            let done = Arc::new(AtomicBool::new(false));
+            let mut thread_handles = Vec::with_capacity(num_readers + 1);
+            for _ in 0..num_readers {
+                let hashindex_clone = hashindex.clone();
+                let barrier_clone = barrier.clone();
+                let done_clone = done.clone();
+                thread_handles.push(thread::spawn(move || {
+                    barrier_clone.wait();
+                    while !done_clone.load(Relaxed) {
+                        for k in 0..workload_size {
+                            let v = hashindex_clone.peek_with(&k, |_, v| *v);
+                            if k % 2 == 0 {
+                                assert_eq!(v, Some(k));
+                            } else {
+                                assert!(v.is_none() || v == Some(k));
+                            }
+                        }
+                    }
+                }));
+            }
+            let hashindex_clone = hashindex.clone();
+            let barrier_clone = barrier.clone();
+            let done_clone = done.clone();
+            thread_handles.push(thread::spawn(move || {
+                barrier_clone.wait();
+                while !done_clone.load(Relaxed) {
+                    // Keep resizing the `HashIndex`.
+                    for k in workload_size..workload_size * 16 {
+                        assert!(hashindex_clone.insert(k, k).is_ok());
+                    }
+                    for k in workload_size..workload_size * 16 {
+                        assert!(hashindex_clone.remove(&k));
+                    }
+                }
+            }));
+
+            barrier.wait();
+            let mut visited = vec![0_usize; workload_size];
+            hashindex.retain(|k, v| {
+                assert_eq!(k, v);
+                if *k < workload_size {
+                    visited[*k] += 1;
+                    *k % 2 == 0
+                } else {
+                    true
+                }
+            });
+            assert!(visited.iter().all(|c| *c == 1));
+            done.store(true, Relaxed);
+            for handle in thread_handles {
+                assert!(handle.join().is_ok());
+            }
+            for k in 0..workload_size {
+                assert_eq!(hashindex.contains(&k), k % 2 == 0);
+            }
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn peek_with_guard_remove() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+        let hashindex: Arc<HashIndex<usize, R>> = Arc::new(HashIndex::default());
+        let workload_size = 256;
+
+        for k in 0..workload_size {
+            assert!(hashindex.insert(k, R::new(&INST_CNT)).is_ok());
+        }
+
+        let guard = Guard::new();
+        let refs: Vec<(&usize, &R)> = (0..workload_size)
+            .map(|k| {
+                hashindex
+                    .peek_with_guard(&k, &guard, |k, v| (k, v))
+                    .unwrap()
+            })
+            .collect();
+
+        let hashindex_clone = hashindex.clone();
+        thread::spawn(move || {
+            for k in 0..workload_size {
+                assert!(hashindex_clone.remove(&k));
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert!(hashindex.is_empty());
+        for (i, (k, v)) in refs.iter().enumerate() {
+            assert_eq!(**k, i);
+            assert_eq!(v.0.load(Relaxed), INST_CNT.load(Relaxed));
+        }
+        assert!(INST_CNT.load(Relaxed) >= workload_size);
+        drop(refs);
+        drop(guard);
+
+        while INST_CNT.load(Relaxed) != 0 {
+            drop(Guard::new());
+            thread::yield_now();
+        }
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     async fn rebuild() {
@@ -1236,11 +2925,74 @@ mod hashindex_test {
 #[cfg(test)]
 mod hashset_test {
     use crate::HashSet;
+    use futures::task::noop_waker_ref;
+    use std::future::Future;
     use std::panic::UnwindSafe;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::Context;
+    use std::thread;
 
     static_assertions::assert_impl_all!(HashSet<String>: Send, Sync, UnwindSafe);
     static_assertions::assert_not_impl_all!(HashSet<*const String>: Send, Sync, UnwindSafe);
 
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn len_counter() {
+        let num_threads = 4;
+        let hashset: Arc<HashSet<usize>> = Arc::new(HashSet::default());
+        for _ in 0..4 {
+            let thread_handles: Vec<_> = (0..num_threads)
+                .map(|t| {
+                    let hashset = hashset.clone();
+                    thread::spawn(move || {
+                        // Keys overlap between threads to contend for the same entries.
+                        for k in 0..4096 {
+                            let _result = hashset.insert((k * num_threads + t) % 8192);
+                        }
+                        for k in (0..4096).step_by(3) {
+                            let _result = hashset.remove(&((k * num_threads + t) % 8192));
+                        }
+                        for k in (0..4096).step_by(5) {
+                            let _result = hashset.remove_if(&k, || t % 2 == 0);
+                        }
+                        hashset.retain(|k| k % 7 != t);
+                    })
+                })
+                .collect();
+            for thread_handle in thread_handles {
+                thread_handle.join().unwrap();
+            }
+
+            let mut num_keys = 0;
+            hashset.scan(|_| num_keys += 1);
+            assert_eq!(hashset.len(), num_keys);
+            assert_eq!(hashset.is_empty(), num_keys == 0);
+        }
+        let num_keys = hashset.len();
+        assert_eq!(hashset.drain().len(), num_keys);
+        assert!(hashset.is_empty());
+        assert!(hashset.insert(1).is_ok());
+        assert_eq!(hashset.clone().len(), 1);
+        hashset.clear();
+        assert_eq!(hashset.len(), 0);
+    }
+
+    #[test]
+    fn len_counter_cancel() {
+        let hashset: HashSet<usize> = HashSet::default();
+        assert!(hashset.insert(1).is_ok());
+
+        // Drop an `insert_async` future while the bucket is locked by `retain`.
+        hashset.retain(|_| {
+            let mut future_insert = pin!(hashset.insert_async(1));
+            let mut context = Context::from_waker(noop_waker_ref());
+            assert!(future_insert.as_mut().poll(&mut context).is_pending());
+            true
+        });
+        assert_eq!(hashset.len(), 1);
+    }
+
     #[cfg_attr(miri, ignore)]
     #[test]
     fn compare() {
@@ -1263,17 +3015,81 @@ mod hashset_test {
         assert!(hashset1.remove("Hi").is_some());
         assert_ne!(hashset1, hashset2);
     }
+
+    #[test]
+    fn from_array() {
+        let hashset: HashSet<usize> = HashSet::from([3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5]);
+        assert_eq!(hashset.len(), 7);
+        for k in 0..10 {
+            assert_eq!(hashset.contains(&k), [1, 2, 3, 4, 5, 6, 9].contains(&k));
+        }
+
+        let keys: Vec<String> = (0..256).map(|k| (k % 64).to_string()).collect();
+        let hashset: HashSet<String> = HashSet::from(keys.as_slice());
+        assert_eq!(hashset.len(), 64);
+        assert!(keys.iter().all(|k| hashset.contains(k)));
+
+        let empty: HashSet<usize> = HashSet::from([]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.capacity(), 0);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn retain() {
+        let hashset: HashSet<usize> = HashSet::default();
+        for k in 0..4096 {
+            assert!(hashset.insert(k).is_ok());
+        }
+        hashset.retain(|k| k % 3 == 0);
+        assert_eq!(hashset.len(), 1366);
+        for k in 0..4096 {
+            assert_eq!(hashset.contains(&k), k % 3 == 0);
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn drain() {
+        let num_keys = 4096;
+        for _ in 0..16 {
+            let hashset: Arc<HashSet<usize>> = Arc::default();
+            for k in 0..num_keys {
+                assert!(hashset.insert(k).is_ok());
+            }
+            let hashset_clone = hashset.clone();
+            let thread_handle = thread::spawn(move || {
+                for k in num_keys..num_keys * 4 {
+                    assert!(hashset_clone.insert(k).is_ok());
+                }
+            });
+            let drained = hashset.drain();
+            assert!(thread_handle.join().is_ok());
+
+            let mut visited = vec![false; num_keys * 4];
+            for k in drained {
+                assert!(!visited[k]);
+                visited[k] = true;
+            }
+            assert!(visited[..num_keys].iter().all(|v| *v));
+            for (k, v) in visited.iter().enumerate() {
+                assert_ne!(hashset.contains(&k), *v);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod hashcache_test {
     use crate::hash_cache;
+    use crate::hash_table::HashTable;
     use crate::HashCache;
     use proptest::prelude::*;
     use std::panic::UnwindSafe;
     use std::sync::atomic::AtomicUsize;
     use std::sync::atomic::Ordering::Relaxed;
-    use std::sync::Arc;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
     use tokio::sync::Barrier as AsyncBarrier;
 
     static_assertions::assert_impl_all!(HashCache<String, String>: Send, Sync, UnwindSafe);
@@ -1312,9 +3128,163 @@ mod hashcache_test {
         for k in 0..workload_size {
             assert!(hashcache.put_async(k, R::new(&INST_CNT)).await.is_ok());
         }
-        assert!(INST_CNT.load(Relaxed) <= hashcache.capacity());
-        drop(hashcache);
-        assert_eq!(INST_CNT.load(Relaxed), 0);
+        assert!(INST_CNT.load(Relaxed) <= hashcache.capacity());
+        drop(hashcache);
+        assert_eq!(INST_CNT.load(Relaxed), 0);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn stats() {
+        let hashcache: HashCache<usize, usize> = HashCache::with_capacity(0, 64);
+        assert_eq!(hashcache.stats(), hash_cache::CacheStats::default());
+
+        assert!(hashcache.put(1, 1).is_ok());
+        assert!(hashcache.get(&1).is_some());
+        assert!(hashcache.get_async(&1).await.is_some());
+        assert!(hashcache.get(&2).is_none());
+        assert!(hashcache.get_async(&3).await.is_none());
+        assert!(hashcache.read(&1, |_, _| ()).is_some());
+        let stats = hashcache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (2, 2, 0));
+
+        let mut num_evicted = 0;
+        for k in 2..1024 {
+            if let Ok(Some(_)) = hashcache.put(k, k) {
+                num_evicted += 1;
+            }
+        }
+        assert!(num_evicted > 0);
+        let stats = hashcache.stats();
+        assert_eq!(
+            (stats.hits, stats.misses, stats.evictions),
+            (2, 2, num_evicted)
+        );
+    }
+
+    #[test]
+    fn read_keeps_recency() {
+        let hashcache: HashCache<usize, usize> = HashCache::with_capacity(64, 64);
+        let bucket_index = HashTable::calculate_bucket_index(&hashcache, &0);
+        let keys: Vec<usize> = (0..)
+            .filter(|k| HashTable::calculate_bucket_index(&hashcache, k) == bucket_index)
+            .take(34)
+            .collect();
+        for k in &keys[0..32] {
+            assert!(matches!(hashcache.put(*k, *k), Ok(None)));
+        }
+
+        // Reading the least recently used entry does not protect it from eviction.
+        for k in &keys[1..32] {
+            assert!(hashcache.get(k).is_some());
+        }
+        assert_eq!(hashcache.read(&keys[0], |_, v| *v), Some(keys[0]));
+        let evicted = hashcache.put(keys[32], keys[32]).unwrap();
+        assert_eq!(evicted, Some((keys[0], keys[0])));
+
+        // Getting an entry marks it as the most recently used one.
+        assert!(hashcache.get(&keys[1]).is_some());
+        let evicted = hashcache.put(keys[33], keys[33]).unwrap();
+        assert_eq!(evicted, Some((keys[2], keys[2])));
+    }
+
+    #[test]
+    fn eviction_policy() {
+        for policy in [
+            hash_cache::EvictionPolicy::Lru,
+            hash_cache::EvictionPolicy::Lfu,
+        ] {
+            let hashcache: HashCache<usize, usize> =
+                HashCache::with_capacity(64, 64).with_eviction_policy(policy);
+            let bucket_index = HashTable::calculate_bucket_index(&hashcache, &0);
+            let keys: Vec<usize> = (0..)
+                .filter(|k| HashTable::calculate_bucket_index(&hashcache, k) == bucket_index)
+                .take(129)
+                .collect();
+
+            // `keys[0]` is hot, and the other keys are accessed only once.
+            assert!(matches!(hashcache.put(keys[0], 0), Ok(None)));
+            for _ in 0..4 {
+                assert!(hashcache.get(&keys[0]).is_some());
+            }
+            for (i, k) in keys.iter().enumerate().take(128).skip(1) {
+                let evicted = hashcache.put(*k, *k).unwrap();
+                assert_eq!(evicted.is_some(), i >= 32);
+            }
+            assert_eq!(
+                hashcache.contains(&keys[0]),
+                policy == hash_cache::EvictionPolicy::Lfu
+            );
+
+            // Among the least frequently used entries, the least recently used one is evicted.
+            if policy == hash_cache::EvictionPolicy::Lfu {
+                let evicted = hashcache.put(keys[128], keys[128]).unwrap();
+                assert_eq!(evicted, Some((keys[97], keys[97])));
+            }
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn or_put_with_once() {
+        let num_tasks = 8;
+        let workload_size = 64;
+        let hashcache: Arc<HashCache<usize, usize>> = Arc::new(HashCache::default());
+        let num_computed = Arc::new(AtomicUsize::new(0));
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        for task_id in 0..num_tasks {
+            let barrier_clone = barrier.clone();
+            let hashcache_clone = hashcache.clone();
+            let num_computed_clone = num_computed.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_clone.wait().await;
+                for k in 0..workload_size {
+                    let (_, occupied) = hashcache_clone.entry_async(k).await.or_put_with(|| {
+                        num_computed_clone.fetch_add(1, Relaxed);
+                        task_id
+                    });
+                    assert!(*occupied.get() < num_tasks);
+                }
+            }));
+        }
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+        assert_eq!(num_computed.load(Relaxed), workload_size);
+        assert_eq!(hashcache.len(), workload_size);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn get_or_insert_with_once() {
+        let num_threads = 8;
+        let workload_size = 64;
+        let hashcache: Arc<HashCache<usize, usize>> = Arc::new(HashCache::default());
+        let num_computed = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(num_threads));
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for thread_id in 0..num_threads {
+            let barrier = barrier.clone();
+            let hashcache = hashcache.clone();
+            let num_computed = num_computed.clone();
+            thread_handles.push(thread::spawn(move || {
+                barrier.wait();
+                for k in 0..workload_size {
+                    let (evicted, occupied) = hashcache.get_or_insert_with(k, || {
+                        num_computed.fetch_add(1, Relaxed);
+                        thread_id
+                    });
+                    assert!(evicted.is_none());
+                    assert!(*occupied.get() < num_threads);
+                }
+            }));
+        }
+        for handle in thread_handles {
+            assert!(handle.join().is_ok());
+        }
+        assert_eq!(num_computed.load(Relaxed), workload_size);
+        assert_eq!(hashcache.len(), workload_size);
     }
 
     #[cfg_attr(miri, ignore)]
@@ -1379,94 +3349,661 @@ mod hashcache_test {
                 assert!(r.is_ok());
             }
 
-            assert_eq!(hashcache.len(), 0);
+            assert_eq!(hashcache.len(), 0);
+        }
+        drop(hashcache);
+        assert_eq!(INST_CNT.load(Relaxed), 0);
+    }
+
+    proptest! {
+        #[cfg_attr(miri, ignore)]
+        #[test]
+        fn capacity(xs in 0_usize..256) {
+            let hashcache: HashCache<usize, usize> = HashCache::with_capacity(0, 64);
+            for k in 0..xs {
+                assert!(hashcache.put(k, k).is_ok());
+            }
+            assert!(hashcache.capacity() <= 64);
+
+            let hashcache: HashCache<usize, usize> = HashCache::with_capacity(xs, xs * 2);
+            for k in 0..xs {
+                assert!(hashcache.put(k, k).is_ok());
+            }
+            if xs == 0 {
+                assert_eq!(hashcache.capacity_range(), 0..=64);
+            } else {
+                assert_eq!(hashcache.capacity_range(), xs.next_power_of_two().max(64)..=(xs * 2).next_power_of_two().max(64));
+            }
+         }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn weighted() {
+        let hashcache: HashCache<usize, usize> = HashCache::with_weigher(256, 256, |_, v| *v);
+        for k in 0..256 {
+            assert!(hashcache.put(k, 16).is_ok());
+            assert!(hashcache.weight() <= 256);
+        }
+        assert_eq!(hashcache.weight(), 256);
+        assert_eq!(hashcache.len(), 16);
+
+        // An entry heavier than the budget is stored, and evicted first.
+        assert!(hashcache.put(1024, 1024).is_ok());
+        assert!(hashcache.contains(&1024));
+        assert_eq!(hashcache.weight(), 256 + 1024);
+        assert!(hashcache.put(1025, 1).is_ok());
+        assert!(!hashcache.contains(&1024));
+        assert!(hashcache.contains(&1025));
+        assert!(hashcache.weight() <= 256);
+
+        if let Some(mut o) = hashcache.get(&1025) {
+            assert_eq!(o.put(8), 1);
+        }
+        let weight = hashcache.weight();
+        assert_eq!(hashcache.remove(&1025), Some((1025, 8)));
+        assert_eq!(hashcache.weight(), weight - 8);
+
+        hashcache.retain(|k, _| k % 2 == 0);
+        assert_eq!(hashcache.weight(), hashcache.len() * 16);
+        hashcache.clear();
+        assert_eq!(hashcache.weight(), 0);
+    }
+
+    #[test]
+    fn weighted_occupied_entry() {
+        let hashcache: HashCache<usize, usize> = HashCache::with_weigher(1024, 64, |_, v| *v);
+        assert_eq!(hashcache.capacity_range(), 0..=1024);
+
+        // Modifying the value in place does not affect the total weight.
+        assert!(hashcache.put(0, 4).is_ok());
+        assert!(hashcache.put(1, 16).is_ok());
+        if let Some(mut o) = hashcache.get(&0) {
+            *o.get_mut() = 12;
+        }
+        assert_eq!(hashcache.weight(), 20);
+        assert_eq!(hashcache.remove(&0), Some((0, 12)));
+        assert_eq!(hashcache.weight(), 16);
+        assert_eq!(hashcache.remove(&1), Some((1, 16)));
+        assert_eq!(hashcache.weight(), 0);
+
+        // Replacing a value evicts other entries to stay under the budget.
+        for k in 0..8 {
+            assert!(hashcache.put(k, 8).is_ok());
+        }
+        assert_eq!(hashcache.weight(), 64);
+        if let Some(mut o) = hashcache.get(&7) {
+            assert_eq!(o.put(40), 8);
+        }
+        assert!(hashcache.weight() <= 64);
+        assert_eq!(hashcache.read(&7, |_, v| *v), Some(40));
+        assert_eq!(hashcache.len(), 4);
+
+        // A value heavier than the budget is kept, and evicted first.
+        if let Some(mut o) = hashcache.get(&7) {
+            assert_eq!(o.put(128), 40);
+        }
+        assert!(hashcache.contains(&7));
+        assert!(hashcache.put(8, 8).is_ok());
+        assert!(!hashcache.contains(&7));
+        assert!(hashcache.weight() <= 64);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn weighted_put_remove() {
+        let hashcache: Arc<HashCache<usize, usize>> =
+            Arc::new(HashCache::with_weigher(4096, 4096, |_, v| *v));
+        for _ in 0..64 {
+            let num_tasks = 8;
+            let workload_size = 256;
+            let mut task_handles = Vec::with_capacity(num_tasks);
+            let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+            for task_id in 0..num_tasks {
+                let barrier_clone = barrier.clone();
+                let hashcache_clone = hashcache.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_clone.wait().await;
+                    let range = (task_id * workload_size)..((task_id + 1) * workload_size);
+                    for id in range.clone() {
+                        assert!(hashcache_clone.put_async(id, id % 16 + 1).await.is_ok());
+                    }
+                    for id in range.clone() {
+                        hashcache_clone.remove_async(&id).await;
+                    }
+                }));
+            }
+
+            for r in futures::future::join_all(task_handles).await {
+                assert!(r.is_ok());
+            }
+
+            assert_eq!(hashcache.len(), 0);
+            assert_eq!(hashcache.weight(), 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod treeindex_test {
+    use crate::ebr::Guard;
+    use crate::tree_index::{Iter, Range, RangeRev};
+    use crate::TreeIndex;
+    use proptest::strategy::{Strategy, ValueTree};
+    use proptest::test_runner::TestRunner;
+    use std::collections::BTreeSet;
+    use std::ops::Bound::{self, Excluded, Included, Unbounded};
+    use std::ops::RangeInclusive;
+    use std::panic::UnwindSafe;
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use tokio::sync::Barrier as AsyncBarrier;
+
+    static_assertions::assert_impl_all!(TreeIndex<String, String>: Send, Sync, UnwindSafe);
+    static_assertions::assert_impl_all!(Iter<'static, 'static, String, String>: UnwindSafe);
+    static_assertions::assert_impl_all!(Range<'static, 'static, String, String, RangeInclusive<String>>: UnwindSafe);
+    static_assertions::assert_not_impl_all!(TreeIndex<String, *const String>: Send, Sync, UnwindSafe);
+    static_assertions::assert_not_impl_all!(Iter<'static, 'static, String, *const String>: Send, Sync, UnwindSafe);
+    static_assertions::assert_not_impl_all!(Range<'static, 'static, String, *const String, RangeInclusive<String>>: Send, Sync, UnwindSafe);
+    static_assertions::assert_impl_all!(RangeRev<'static, 'static, String, String, RangeInclusive<String>>: UnwindSafe);
+
+    struct R(&'static AtomicUsize);
+    impl R {
+        fn new(cnt: &'static AtomicUsize) -> R {
+            cnt.fetch_add(1, Relaxed);
+            R(cnt)
+        }
+    }
+    impl Clone for R {
+        fn clone(&self) -> Self {
+            self.0.fetch_add(1, Relaxed);
+            R(self.0)
+        }
+    }
+    impl Drop for R {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Relaxed);
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn insert_drop() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+        let tree: TreeIndex<usize, R> = TreeIndex::default();
+
+        let workload_size = 1024;
+        for k in 0..workload_size {
+            assert!(tree.insert_async(k, R::new(&INST_CNT)).await.is_ok());
+        }
+        assert!(INST_CNT.load(Relaxed) >= workload_size);
+        assert_eq!(tree.len(), workload_size);
+        drop(tree);
+
+        while INST_CNT.load(Relaxed) != 0 {
+            drop(Guard::new());
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn get_or_insert() {
+        let num_threads = 4;
+        let num_keys = 256;
+        let tree: Arc<TreeIndex<usize, usize>> = Arc::default();
+        let constructed = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(num_threads));
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for task_id in 0..num_threads {
+            let tree_clone = tree.clone();
+            let constructed_clone = constructed.clone();
+            let barrier_clone = barrier.clone();
+            thread_handles.push(thread::spawn(move || {
+                barrier_clone.wait();
+                let guard = Guard::new();
+                for k in 0..num_keys {
+                    let v = tree_clone.get_or_insert(
+                        k,
+                        || {
+                            constructed_clone.fetch_add(1, Relaxed);
+                            k * num_threads + task_id
+                        },
+                        &guard,
+                    );
+                    assert_eq!(*v / num_threads, k);
+                    assert_eq!(tree_clone.peek(&k, &guard), Some(v));
+                }
+            }));
+        }
+        for handle in thread_handles {
+            assert!(handle.join().is_ok());
+        }
+        assert_eq!(tree.len(), num_keys);
+        assert!(constructed.load(Relaxed) >= num_keys);
+        assert!(constructed.load(Relaxed) <= num_keys * num_threads);
+
+        let guard = Guard::new();
+        assert_eq!(
+            *tree.get_or_insert(0, || unreachable!(), &guard),
+            tree.peek(&0, &guard).copied().unwrap()
+        );
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn iter_sorted() {
+        let workload_size = 4096;
+        let tree: Arc<TreeIndex<usize, usize>> = Arc::default();
+        let mut keys: Vec<usize> = (0..workload_size)
+            .map(|k| (k * 7919) % workload_size)
+            .collect();
+        for k in &keys {
+            assert!(tree.insert(*k * 2, *k).is_ok());
+        }
+        keys.sort_unstable();
+        let expected: Vec<usize> = keys.iter().map(|k| k * 2).collect();
+        let guard = Guard::new();
+        let collected: Vec<usize> = tree.iter(&guard).map(|(k, _)| *k).collect();
+        assert_eq!(collected, expected);
+
+        // Odd keys inserted by another thread split leaves while the iterator is being consumed.
+        let tree_clone = tree.clone();
+        let thread_handle = thread::spawn(move || {
+            for k in 0..workload_size {
+                assert!(tree_clone.insert(k * 2 + 1, k).is_ok());
+            }
+        });
+        let collected: Vec<usize> = tree.iter(&guard).map(|(k, _)| *k).collect();
+        assert!(thread_handle.join().is_ok());
+        assert!(collected.windows(2).all(|w| w[0] < w[1]));
+        let mut evens = collected.iter().filter(|k| *k % 2 == 0).copied();
+        assert!(expected.iter().all(|k| evens.next() == Some(*k)));
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn split_off() {
+        let workload_size = 1024;
+        for pivot in [0, 1, 31, 512, 1023, 1024, 2048] {
+            let tree: TreeIndex<usize, usize> = TreeIndex::default();
+            for k in 0..workload_size {
+                assert!(tree.insert(k, k * 2).is_ok());
+            }
+            let split = tree.split_off(&pivot);
+            let guard = Guard::new();
+            let left: Vec<usize> = tree.iter(&guard).map(|(k, _)| *k).collect();
+            let right: Vec<usize> = split.iter(&guard).map(|(k, _)| *k).collect();
+            assert_eq!(left, (0..pivot.min(workload_size)).collect::<Vec<_>>());
+            assert_eq!(
+                right,
+                (pivot.min(workload_size)..workload_size).collect::<Vec<_>>()
+            );
+            assert_eq!(tree.len() + split.len(), workload_size);
+            assert!(split.iter(&guard).all(|(k, v)| *v == k * 2));
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn insert_duplicate() {
+        for workload_size in [1, 16, 64, 256, 1024] {
+            let tree: TreeIndex<usize, usize> = TreeIndex::default();
+            for k in 0..workload_size {
+                assert!(tree.insert(k, k).is_ok());
+            }
+            let depth = tree.depth();
+            for k in (0..workload_size).rev() {
+                assert_eq!(tree.insert(k, k + 1), Err((k, k + 1)));
+                assert_eq!(tree.depth(), depth);
+            }
+            assert_eq!(tree.len(), workload_size);
+            for k in 0..workload_size {
+                assert_eq!(tree.peek_with(&k, |_, v| *v), Some(k));
+            }
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn depth_logarithmic() {
+        let tree: TreeIndex<usize, usize> = TreeIndex::default();
+        assert_eq!(tree.depth(), 0);
+
+        let mut prev_depth = 0;
+        let mut num_entries = 0;
+        for log4_size in 0..9 {
+            let size = 1_usize << (log4_size * 2);
+            while num_entries < size {
+                let key = num_entries.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                assert!(tree.insert(key, num_entries).is_ok());
+                num_entries += 1;
+            }
+            let depth = tree.depth();
+            assert!(depth >= prev_depth);
+            assert!(depth <= log4_size + 1, "{size} {depth}");
+            prev_depth = depth;
+        }
+        assert!(prev_depth > 2);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn insert_or_update() {
+        let num_threads = 4;
+        let num_keys = 64;
+        let workload_size = 256;
+        let tree: Arc<TreeIndex<usize, usize>> = Arc::new(TreeIndex::default());
+        let barrier = Arc::new(Barrier::new(num_threads));
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for _ in 0..num_threads {
+            let tree = tree.clone();
+            let barrier = barrier.clone();
+            thread_handles.push(thread::spawn(move || {
+                barrier.wait();
+                for _ in 0..workload_size {
+                    for k in 0..num_keys {
+                        tree.insert_or_update(k, 1, |v| *v += 1);
+                        assert!(tree.peek_with(&k, |_, v| *v).is_some_and(|v| v >= 1));
+                    }
+                }
+            }));
+        }
+        for handle in thread_handles {
+            assert!(handle.join().is_ok());
+        }
+        assert_eq!(tree.len(), num_keys);
+        let guard = Guard::new();
+        assert!(tree
+            .iter(&guard)
+            .enumerate()
+            .all(|(i, (k, v))| *k == i && *v == num_threads * workload_size));
+        assert!(tree.remove(&0));
+        assert!(!tree.contains(&0));
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn insert_or_update_panic() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+        let tree: TreeIndex<usize, R> = TreeIndex::default();
+
+        let num_keys = 64;
+        for k in 0..num_keys {
+            assert!(tree.insert(k, R::new(&INST_CNT)).is_ok());
+        }
+        for k in 0..num_keys {
+            assert!(std::panic::catch_unwind(|| {
+                tree.insert_or_update(k, R::new(&INST_CNT), |_| panic!());
+            })
+            .is_err());
+            tree.insert_or_update(k, R::new(&INST_CNT), |_| ());
+        }
+        assert_eq!(tree.len(), num_keys);
+        drop(tree);
+
+        while INST_CNT.load(Relaxed) != 0 {
+            assert!(INST_CNT.load(Relaxed) < usize::MAX / 2);
+            drop(Guard::new());
+            thread::yield_now();
+        }
+        for _ in 0..16 {
+            drop(Guard::new());
+            thread::yield_now();
+        }
+        assert_eq!(INST_CNT.load(Relaxed), 0);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn scan_range() {
+        let tree: Arc<TreeIndex<usize, usize>> = Arc::default();
+        let mut oracle = std::collections::BTreeMap::new();
+        for i in 0..4096 {
+            let k = (i * 7919) % 8192 * 2;
+            assert!(tree.insert(k, i).is_ok());
+            oracle.insert(k, i);
+        }
+
+        for (start, end) in [(0, 0), (0, 16384), (100, 2000), (3001, 3002), (8000, 20000)] {
+            let mut sum = 0;
+            let mut prev = None;
+            tree.scan_range(
+                start..end,
+                |k, v| {
+                    assert!(prev.is_none_or(|p| p < *k));
+                    prev.replace(*k);
+                    sum += v;
+                },
+                &Guard::new(),
+            );
+            assert_eq!(sum, oracle.range(start..end).map(|(_, v)| v).sum::<usize>());
+        }
+
+        // Odd keys inserted concurrently split leaves without affecting the even keys.
+        let tree_clone = tree.clone();
+        let thread_handle = thread::spawn(move || {
+            for k in (1..16384).step_by(2) {
+                assert!(tree_clone.insert(k, 0).is_ok());
+            }
+        });
+        for _ in 0..16 {
+            let mut sum = 0;
+            tree.scan_range(..=8191, |_, v| sum += v, &Guard::new());
+            assert_eq!(sum, oracle.range(..=8191).map(|(_, v)| v).sum::<usize>());
+        }
+        thread_handle.join().unwrap();
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn get_cloned_range_cloned() {
+        let tree: TreeIndex<usize, String> = TreeIndex::default();
+        assert!(tree.get_cloned(&0).is_none());
+        assert!(tree.range_cloned(..).is_empty());
+
+        for k in 0..256 {
+            assert!(tree.insert(k, k.to_string()).is_ok());
+        }
+        for k in 0..256 {
+            assert_eq!(tree.get_cloned(&k), Some(k.to_string()));
+        }
+        assert!(tree.get_cloned(&256).is_none());
+
+        let expected: Vec<(usize, String)> = (16..64).map(|k| (k, k.to_string())).collect();
+        assert_eq!(tree.range_cloned(16..64), expected);
+        assert_eq!(tree.range_cloned(..).len(), 256);
+        assert!(tree.range_cloned(256..).is_empty());
+
+        let value = tree.get_cloned(&7).unwrap();
+        assert!(tree.remove(&7));
+        assert_eq!(value, "7");
+        assert!(tree.get_cloned(&7).is_none());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn contains() {
+        let num_keys = 1024;
+        let tree: Arc<TreeIndex<usize, usize>> = Arc::default();
+        for k in (0..num_keys).filter(|k| k % 2 == 0) {
+            assert!(tree.insert(k, k).is_ok());
+        }
+
+        // Odd keys are repeatedly inserted and removed to split and merge nodes.
+        let tree_clone = tree.clone();
+        let thread_handle = thread::spawn(move || {
+            for _ in 0..4 {
+                for k in (0..num_keys).filter(|k| k % 2 == 1) {
+                    assert!(tree_clone.insert(k, k).is_ok());
+                }
+                for k in (0..num_keys).filter(|k| k % 2 == 1) {
+                    assert!(tree_clone.remove(&k));
+                }
+            }
+        });
+        for _ in 0..4 {
+            for k in 0..num_keys {
+                let contains = tree.contains(&k);
+                if k % 2 == 0 {
+                    assert!(contains);
+                }
+                assert!(!tree.contains(&(k + num_keys)));
+            }
+        }
+        thread_handle.join().unwrap();
+
+        let guard = Guard::new();
+        for k in 0..num_keys * 2 {
+            assert_eq!(tree.contains(&k), tree.peek(&k, &guard).is_some());
+            assert_eq!(tree.contains(&k), k < num_keys && k % 2 == 0);
         }
-        drop(hashcache);
-        assert_eq!(INST_CNT.load(Relaxed), 0);
     }
 
-    proptest! {
-        #[cfg_attr(miri, ignore)]
-        #[test]
-        fn capacity(xs in 0_usize..256) {
-            let hashcache: HashCache<usize, usize> = HashCache::with_capacity(0, 64);
-            for k in 0..xs {
-                assert!(hashcache.put(k, k).is_ok());
-            }
-            assert!(hashcache.capacity() <= 64);
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn from_sorted() {
+        let workload_size = 100_000;
+        let tree: TreeIndex<usize, usize> =
+            TreeIndex::from_sorted((0..workload_size).map(|k| (k * 2, k)));
+        assert_eq!(tree.len(), workload_size);
+        assert!(tree.depth() > 1);
 
-            let hashcache: HashCache<usize, usize> = HashCache::with_capacity(xs, xs * 2);
-            for k in 0..xs {
-                assert!(hashcache.put(k, k).is_ok());
-            }
-            if xs == 0 {
-                assert_eq!(hashcache.capacity_range(), 0..=64);
-            } else {
-                assert_eq!(hashcache.capacity_range(), xs.next_power_of_two().max(64)..=(xs * 2).next_power_of_two().max(64));
-            }
-         }
-    }
-}
+        let guard = Guard::new();
+        assert!(tree
+            .iter(&guard)
+            .enumerate()
+            .all(|(i, (k, v))| *k == i * 2 && *v == i));
+        assert_eq!(tree.iter(&guard).count(), workload_size);
+        assert_eq!(tree.range(1000..2000, &guard).count(), 500);
+        for k in 0..workload_size {
+            assert_eq!(tree.peek_with(&(k * 2), |_, v| *v), Some(k));
+            assert!(tree.peek(&(k * 2 + 1), &guard).is_none());
+        }
 
-#[cfg(test)]
-mod treeindex_test {
-    use crate::ebr::Guard;
-    use crate::tree_index::{Iter, Range};
-    use crate::TreeIndex;
-    use proptest::strategy::{Strategy, ValueTree};
-    use proptest::test_runner::TestRunner;
-    use std::collections::BTreeSet;
-    use std::ops::RangeInclusive;
-    use std::panic::UnwindSafe;
-    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
-    use std::sync::atomic::{AtomicBool, AtomicUsize};
-    use std::sync::{Arc, Barrier};
-    use std::thread;
-    use tokio::sync::Barrier as AsyncBarrier;
+        // The tree keeps working as usual after being built.
+        for k in 0..workload_size {
+            assert!(tree.insert(k * 2 + 1, k).is_ok());
+            assert!(tree.insert(k * 2, k).is_err());
+        }
+        assert_eq!(tree.len(), workload_size * 2);
+        assert!(tree.iter(&guard).map(|(k, _)| *k).eq(0..workload_size * 2));
+        for k in 0..workload_size * 2 {
+            assert!(tree.remove(&k));
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.iter(&guard).count(), 0);
 
-    static_assertions::assert_impl_all!(TreeIndex<String, String>: Send, Sync, UnwindSafe);
-    static_assertions::assert_impl_all!(Iter<'static, 'static, String, String>: UnwindSafe);
-    static_assertions::assert_impl_all!(Range<'static, 'static, String, String, RangeInclusive<String>>: UnwindSafe);
-    static_assertions::assert_not_impl_all!(TreeIndex<String, *const String>: Send, Sync, UnwindSafe);
-    static_assertions::assert_not_impl_all!(Iter<'static, 'static, String, *const String>: Send, Sync, UnwindSafe);
-    static_assertions::assert_not_impl_all!(Range<'static, 'static, String, *const String, RangeInclusive<String>>: Send, Sync, UnwindSafe);
+        let tree: TreeIndex<usize, usize> = TreeIndex::from_sorted(std::iter::empty());
+        assert!(tree.is_empty());
+        assert_eq!(tree.depth(), 0);
+    }
 
-    struct R(&'static AtomicUsize);
-    impl R {
-        fn new(cnt: &'static AtomicUsize) -> R {
-            cnt.fetch_add(1, Relaxed);
-            R(cnt)
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn clear_concurrent() {
+        let num_threads = 4;
+        let workload_size = 4096;
+        let tree: Arc<TreeIndex<usize, usize>> = Arc::new(TreeIndex::default());
+        let barrier = Arc::new(Barrier::new(num_threads + 2));
+        let done = Arc::new(AtomicBool::new(false));
+        let mut thread_handles = Vec::with_capacity(num_threads + 1);
+        for thread_id in 0..num_threads {
+            let barrier_clone = barrier.clone();
+            let tree_clone = tree.clone();
+            thread_handles.push(thread::spawn(move || {
+                barrier_clone.wait();
+                for k in (thread_id * workload_size)..((thread_id + 1) * workload_size) {
+                    let _result = tree_clone.insert(k, k);
+                }
+            }));
         }
-    }
-    impl Clone for R {
-        fn clone(&self) -> Self {
-            self.0.fetch_add(1, Relaxed);
-            R(self.0)
+        let barrier_clone = barrier.clone();
+        let tree_clone = tree.clone();
+        let done_clone = done.clone();
+        thread_handles.push(thread::spawn(move || {
+            barrier_clone.wait();
+            while !done_clone.load(Acquire) {
+                let guard = Guard::new();
+                let mut prev = None;
+                for (k, v) in tree_clone.iter(&guard) {
+                    assert_eq!(k, v);
+                    if let Some(p) = prev {
+                        assert!(p < *k);
+                    }
+                    prev.replace(*k);
+                }
+            }
+        }));
+        barrier.wait();
+        for _ in 0..16 {
+            tree.clear();
+            thread::yield_now();
         }
-    }
-    impl Drop for R {
-        fn drop(&mut self) {
-            self.0.fetch_sub(1, Relaxed);
+        for (i, handle) in thread_handles.into_iter().enumerate() {
+            if i == num_threads {
+                done.store(true, Release);
+            }
+            handle.join().unwrap();
         }
+
+        tree.clear();
+        assert_eq!(tree.iter(&Guard::new()).count(), 0);
+        for k in 0..workload_size {
+            assert!(tree.insert(k, k).is_ok());
+        }
+        assert_eq!(tree.iter(&Guard::new()).count(), workload_size);
     }
 
     #[cfg_attr(miri, ignore)]
-    #[tokio::test]
-    async fn insert_drop() {
-        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
-        let tree: TreeIndex<usize, R> = TreeIndex::default();
-
-        let workload_size = 1024;
-        for k in 0..workload_size {
-            assert!(tree.insert_async(k, R::new(&INST_CNT)).await.is_ok());
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn len() {
+        let tree: Arc<TreeIndex<usize, usize>> = Arc::new(TreeIndex::default());
+        assert_eq!(tree.len(), 0);
+        assert!(tree.is_empty());
+        for k in 0..64 {
+            assert!(tree.insert(k, k).is_ok());
+            assert!(tree.insert(k, k).is_err());
+            assert_eq!(tree.len(), k + 1);
         }
-        assert!(INST_CNT.load(Relaxed) >= workload_size);
-        assert_eq!(tree.len(), workload_size);
-        drop(tree);
+        for k in 0..32 {
+            assert!(tree.remove(&k));
+            assert!(!tree.remove(&k));
+            assert!(!tree.remove_if(&(k + 32), |_| false));
+            assert_eq!(tree.len(), 63 - k);
+        }
+        tree.clear();
+        assert_eq!(tree.len(), 0);
+        assert!(tree.is_empty());
 
-        while INST_CNT.load(Relaxed) != 0 {
-            drop(Guard::new());
-            tokio::task::yield_now().await;
+        let num_tasks = 8;
+        let workload_size = 256;
+        for _ in 0..16 {
+            let mut task_handles = Vec::with_capacity(num_tasks);
+            let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+            for task_id in 0..num_tasks {
+                let barrier_clone = barrier.clone();
+                let tree_clone = tree.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_clone.wait().await;
+                    let range = (task_id * workload_size)..((task_id + 1) * workload_size);
+                    for id in range.clone() {
+                        assert!(tree_clone.insert_async(id, id).await.is_ok());
+                    }
+                    for id in range.clone().filter(|id| id % 2 == 0) {
+                        assert!(tree_clone.remove_async(&id).await);
+                    }
+                }));
+            }
+            for r in futures::future::join_all(task_handles).await {
+                assert!(r.is_ok());
+            }
+            assert_eq!(tree.len(), num_tasks * workload_size / 2);
+            assert_eq!(tree.len(), tree.iter(&Guard::new()).count());
+            tree.clear();
+            assert_eq!(tree.len(), 0);
         }
     }
 
@@ -2016,6 +4553,87 @@ mod treeindex_test {
             3
         );
     }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn retain() {
+        let tree: TreeIndex<usize, usize> = TreeIndex::default();
+        for k in 0..4096 {
+            assert!(tree.insert(k, k).is_ok());
+        }
+        let mut visited = Vec::new();
+        tree.retain(|k, v| {
+            assert_eq!(k, v);
+            visited.push(*k);
+            k % 2 == 0
+        });
+        assert_eq!(visited, (0..4096).collect::<Vec<_>>());
+        assert_eq!(tree.len(), 2048);
+        let guard = Guard::new();
+        assert!(tree.iter(&guard).map(|(k, _)| *k).eq((0..4096).step_by(2)));
+        assert!(tree.range(..1024, &guard).all(|(k, _)| k % 2 == 0));
+        assert_eq!(tree.peek(&1, &guard), None);
+        assert_eq!(tree.peek(&2, &guard), Some(&2));
+
+        tree.retain(|k, _| *k >= 4000);
+        assert_eq!(tree.len(), 48);
+        assert!(tree
+            .iter(&guard)
+            .map(|(k, _)| *k)
+            .eq((4000..4096).step_by(2)));
+        for k in (0..4096).filter(|k| k % 2 == 1) {
+            assert!(tree.insert(k, k).is_ok());
+        }
+        assert_eq!(tree.len(), 2096);
+        tree.retain(|_, _| false);
+        assert!(tree.is_empty());
+        assert_eq!(tree.iter(&guard).count(), 0);
+        assert!(tree.insert(0, 0).is_ok());
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn range_rev() {
+        let tree: TreeIndex<usize, usize> = TreeIndex::default();
+        let guard = Guard::new();
+        assert_eq!(tree.range_rev(.., &guard).count(), 0);
+        for k in (0..1024).map(|k| k * 2) {
+            assert!(tree.insert(k, k).is_ok());
+        }
+        let check = |start: Bound<usize>, end: Bound<usize>| {
+            let guard = Guard::new();
+            let mut expected: Vec<usize> =
+                tree.range((start, end), &guard).map(|(k, _)| *k).collect();
+            expected.reverse();
+            let result: Vec<usize> = tree
+                .range_rev((start, end), &guard)
+                .map(|(k, v)| {
+                    assert_eq!(k, v);
+                    *k
+                })
+                .collect();
+            assert_eq!(result, expected, "{start:?} {end:?}");
+        };
+        let bounds = [
+            Unbounded,
+            Included(0),
+            Excluded(0),
+            Included(511),
+            Excluded(512),
+            Included(512),
+            Included(2046),
+            Excluded(2046),
+            Included(4096),
+        ];
+        for start in bounds {
+            for end in bounds {
+                check(start, end);
+            }
+        }
+        assert_eq!(tree.range_rev(.., &guard).count(), 1024);
+        assert_eq!(tree.range_rev(1000..1000, &guard).count(), 0);
+    }
 }
 
 #[cfg(test)]
@@ -2052,6 +4670,56 @@ mod bag_test {
         }
     }
 
+    #[test]
+    fn with_capacity() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+        for workload_size in [0, 16, 17, 40, 64] {
+            let mut bag: Bag<R, 16> = Bag::with_capacity(workload_size);
+            for _ in 0..workload_size {
+                bag.push(R::new(&INST_CNT));
+            }
+            assert!(!bag.has_fallback_storage());
+            assert_eq!(bag.iter_mut().count(), workload_size);
+            bag.push(R::new(&INST_CNT));
+            assert_eq!(
+                bag.has_fallback_storage(),
+                workload_size != 0 && workload_size % 16 == 0
+            );
+            assert_eq!(bag.pop_many(workload_size + 1).len(), workload_size + 1);
+            assert!(bag.is_empty());
+            for _ in 0..workload_size {
+                bag.push(R::new(&INST_CNT));
+            }
+            drop(bag);
+            assert_eq!(INST_CNT.load(Relaxed), 0);
+        }
+    }
+
+    #[test]
+    fn clear_drain() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+        for workload_size in [0, 16, 40, 120] {
+            let bag: Bag<R, 16> = Bag::new();
+            for _ in 0..workload_size {
+                bag.push(R::new(&INST_CNT));
+            }
+            bag.clear();
+            assert!(bag.is_empty());
+            assert!(!bag.has_fallback_storage());
+            assert_eq!(INST_CNT.load(Relaxed), 0);
+
+            for _ in 0..workload_size {
+                bag.push(R::new(&INST_CNT));
+            }
+            let drained = bag.drain();
+            assert_eq!(drained.len(), workload_size);
+            assert!(bag.is_empty());
+            assert!(bag.drain().is_empty());
+            drop(drained);
+            assert_eq!(INST_CNT.load(Relaxed), 0);
+        }
+    }
+
     #[cfg_attr(miri, ignore)]
     #[test]
     fn reclaim() {
@@ -2141,6 +4809,51 @@ mod bag_test {
         assert_eq!(INST_CNT.load(Relaxed), 0);
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 16)]
+    async fn pop_many() {
+        const NUM_TASKS: usize = 6;
+        let workload_size = 1024;
+        let total: usize = (0..NUM_TASKS * workload_size).sum();
+        for _ in 0..64 {
+            let bag: Arc<Bag<usize, 17>> = Arc::new(Bag::new());
+            let sum = Arc::new(AtomicUsize::new(0));
+            let mut task_handles = Vec::with_capacity(NUM_TASKS * 2);
+            let barrier = Arc::new(AsyncBarrier::new(NUM_TASKS * 2));
+            for task_id in 0..NUM_TASKS {
+                let barrier_clone = barrier.clone();
+                let bag_clone = bag.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_clone.wait().await;
+                    for i in 0..workload_size {
+                        bag_clone.push(task_id * workload_size + i);
+                    }
+                }));
+            }
+            for task_id in 0..NUM_TASKS {
+                let barrier_clone = barrier.clone();
+                let bag_clone = bag.clone();
+                let sum_clone = sum.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_clone.wait().await;
+                    for _ in 0..workload_size {
+                        let popped = bag_clone.pop_many(task_id + 1);
+                        assert!(popped.len() <= task_id + 1);
+                        sum_clone.fetch_add(popped.into_iter().sum(), Relaxed);
+                    }
+                }));
+            }
+
+            for r in futures::future::join_all(task_handles).await {
+                assert!(r.is_ok());
+            }
+            sum.fetch_add(bag.pop_all(0, |a, v| a + v), Relaxed);
+            assert_eq!(sum.load(Relaxed), total);
+            assert!(bag.pop_many(1).is_empty());
+            assert!(bag.is_empty());
+        }
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test(flavor = "multi_thread", worker_threads = 16)]
     async fn mpsc() {
@@ -2193,6 +4906,50 @@ mod bag_test {
     }
 }
 
+/// Pushes values with `push`, and pops them with multiple threads calling `try_pop` at once.
+///
+/// Each value has to be popped exactly once even if `try_pop` fails due to contention.
+#[cfg(test)]
+fn try_pop_concurrently<C, P, T>(container: &C, push: P, try_pop: T)
+where
+    C: Sync,
+    P: Fn(&C, usize),
+    T: Fn(&C) -> Result<Option<usize>, crate::Contended> + Sync,
+{
+    let num_threads = 4;
+    let workload_size = 64;
+    let barrier = std::sync::Barrier::new(num_threads);
+    for _ in 0..64 {
+        for i in 0..workload_size {
+            push(container, i);
+        }
+        let mut values: Vec<usize> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..num_threads)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut popped = Vec::new();
+                        barrier.wait();
+                        loop {
+                            match try_pop(container) {
+                                Ok(Some(val)) => popped.push(val),
+                                Ok(None) => break,
+                                Err(_) => (),
+                            }
+                        }
+                        popped
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        values.sort_unstable();
+        assert_eq!(values, (0..workload_size).collect::<Vec<_>>());
+    }
+}
+
 #[cfg(test)]
 mod queue_test {
     use crate::Queue;
@@ -2212,6 +4969,36 @@ mod queue_test {
         }
     }
 
+    #[test]
+    fn peek_clone() {
+        let queue: Queue<String> = Queue::default();
+        assert!(queue.peek_clone().is_none());
+        for i in 0..4 {
+            queue.push(i.to_string());
+        }
+        for i in 0..4 {
+            for _ in 0..3 {
+                assert_eq!(queue.peek_clone(), Some(i.to_string()));
+            }
+            assert_eq!(**queue.pop().unwrap(), i.to_string());
+        }
+        assert!(queue.peek_clone().is_none());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn try_pop_contended() {
+        let queue: Queue<usize> = Queue::default();
+        super::try_pop_concurrently(
+            &queue,
+            |queue, val| {
+                queue.push(val);
+            },
+            |queue| queue.try_pop().map(|entry| entry.map(|entry| **entry)),
+        );
+        assert!(queue.is_empty());
+    }
+
     #[cfg_attr(miri, ignore)]
     #[test]
     fn clone() {
@@ -2222,9 +5009,11 @@ mod queue_test {
 
         let queue_clone = queue.clone();
 
+        queue.push(11);
         assert_eq!(queue.pop().map(|e| **e), Some(37));
         assert_eq!(queue.pop().map(|e| **e), Some(3));
         assert_eq!(queue.pop().map(|e| **e), Some(1));
+        assert_eq!(queue.pop().map(|e| **e), Some(11));
         assert!(queue.pop().is_none());
 
         assert_eq!(queue_clone.pop().map(|e| **e), Some(37));
@@ -2233,6 +5022,71 @@ mod queue_test {
         assert!(queue_clone.pop().is_none());
     }
 
+    #[test]
+    fn from_iter_extend() {
+        let mut queue: Queue<usize> = (0..16).collect();
+        queue.extend(16..32);
+        assert_eq!(
+            queue.into_iter().map(|e| **e).collect::<Vec<_>>(),
+            (0..32).collect::<Vec<_>>()
+        );
+
+        let queue: Queue<usize> = std::iter::empty().collect();
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn into_iter() {
+        let queue = Queue::default();
+        for i in 0..16 {
+            queue.push(i);
+        }
+        let values: Vec<usize> = queue.into_iter().map(|e| **e).collect();
+        assert_eq!(values, (0..16).collect::<Vec<_>>());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 16)]
+    async fn drain() {
+        const NUM_TASKS: usize = 8;
+        let queue: Arc<Queue<R>> = Arc::new(Queue::default());
+        let workload_size = 256;
+        for _ in 0..16 {
+            let mut task_handles = Vec::with_capacity(NUM_TASKS + 1);
+            let barrier = Arc::new(AsyncBarrier::new(NUM_TASKS + 1));
+            for task_id in 0..NUM_TASKS {
+                let barrier_clone = barrier.clone();
+                let queue_clone = queue.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_clone.wait().await;
+                    for seq in 1..=workload_size {
+                        queue_clone.push(R::new(task_id, seq));
+                    }
+                }));
+            }
+            let barrier_clone = barrier.clone();
+            let queue_clone = queue.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_clone.wait().await;
+                let mut drained_acc: [usize; NUM_TASKS] = Default::default();
+                let mut cnt = 0;
+                while cnt < workload_size * NUM_TASKS {
+                    for drained in queue_clone.drain() {
+                        assert_eq!(drained_acc[drained.0] + 1, drained.1);
+                        drained_acc[drained.0] = drained.1;
+                        cnt += 1;
+                    }
+                    tokio::task::yield_now().await;
+                }
+            }));
+
+            for r in futures::future::join_all(task_handles).await {
+                assert!(r.is_ok());
+            }
+            assert!(queue.is_empty());
+        }
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test(flavor = "multi_thread", worker_threads = 16)]
     async fn mpmc() {
@@ -2284,8 +5138,10 @@ mod queue_test {
 
 #[cfg(test)]
 mod stack_test {
-    use crate::Stack;
-    use std::{panic::UnwindSafe, sync::Arc};
+    use crate::{LinkedList, Stack};
+    use std::panic::UnwindSafe;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::Arc;
     use tokio::sync::Barrier as AsyncBarrier;
 
     static_assertions::assert_impl_all!(Stack<String>: Send, Sync, UnwindSafe);
@@ -2309,6 +5165,8 @@ mod stack_test {
 
         let stack_clone = stack.clone();
 
+        stack.push(11);
+        assert_eq!(stack.pop().map(|e| **e), Some(11));
         assert_eq!(stack.pop().map(|e| **e), Some(1));
         assert_eq!(stack.pop().map(|e| **e), Some(3));
         assert_eq!(stack.pop().map(|e| **e), Some(37));
@@ -2320,6 +5178,165 @@ mod stack_test {
         assert!(stack_clone.pop().is_none());
     }
 
+    #[test]
+    fn from_iter_extend() {
+        let mut stack: Stack<usize> = (0..16).collect();
+        stack.extend(16..32);
+        assert_eq!(
+            stack.into_iter().map(|e| **e).collect::<Vec<_>>(),
+            (0..32).rev().collect::<Vec<_>>()
+        );
+
+        let stack: Stack<usize> = std::iter::empty().collect();
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn swap() {
+        let front: Stack<usize> = (0..4).collect();
+        let back: Stack<usize> = (4..12).collect();
+        front.swap(&back);
+        front.swap(&front);
+        assert_eq!(
+            front.pop_all().into_iter().map(|e| **e).collect::<Vec<_>>(),
+            (4..12).rev().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            back.pop_all().into_iter().map(|e| **e).collect::<Vec<_>>(),
+            (0..4).rev().collect::<Vec<_>>()
+        );
+
+        let empty: Stack<usize> = Stack::default();
+        let popped = back.push(17);
+        assert!(back.push(19).delete_self(Relaxed));
+        empty.swap(&back);
+        assert!(back.is_empty());
+        assert_eq!(empty.pop().map(|e| **e), Some(17));
+        assert!(empty.is_empty());
+        drop(popped);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn swap_concurrent() {
+        let num_threads = 4;
+        let workload_size = 4096;
+        let front: Arc<Stack<usize>> = Arc::default();
+        let back: Arc<Stack<usize>> = Arc::default();
+        let mut thread_handles = Vec::with_capacity(num_threads + 1);
+        for task_id in 0..num_threads {
+            let front = front.clone();
+            let back = back.clone();
+            thread_handles.push(std::thread::spawn(move || {
+                for i in 0..workload_size {
+                    let stack = if i % 2 == 0 { &front } else { &back };
+                    stack.push(task_id * workload_size + i);
+                }
+            }));
+        }
+        let front_clone = front.clone();
+        let back_clone = back.clone();
+        thread_handles.push(std::thread::spawn(move || {
+            for _ in 0..workload_size / 16 {
+                front_clone.swap(&back_clone);
+            }
+        }));
+        for handle in thread_handles {
+            assert!(handle.join().is_ok());
+        }
+
+        let mut values: Vec<usize> = front
+            .pop_all()
+            .into_iter()
+            .chain(back.pop_all())
+            .map(|e| **e)
+            .collect();
+        values.sort_unstable();
+        assert_eq!(values, (0..num_threads * workload_size).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn peek_clone() {
+        let stack: Stack<String> = Stack::default();
+        assert!(stack.peek_clone().is_none());
+        for i in 0..4 {
+            stack.push(i.to_string());
+        }
+        for i in (0..4).rev() {
+            for _ in 0..3 {
+                assert_eq!(stack.peek_clone(), Some(i.to_string()));
+            }
+            assert_eq!(**stack.pop().unwrap(), i.to_string());
+        }
+        assert!(stack.peek_clone().is_none());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn try_pop_contended() {
+        let stack: Stack<usize> = Stack::default();
+        super::try_pop_concurrently(
+            &stack,
+            |stack, val| {
+                stack.push(val);
+            },
+            |stack| stack.try_pop().map(|entry| entry.map(|entry| **entry)),
+        );
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn into_iter() {
+        let stack = Stack::default();
+        for i in 0..16 {
+            stack.push(i);
+        }
+        let values: Vec<usize> = stack.into_iter().map(|e| **e).collect();
+        assert_eq!(values, (0..16).rev().collect::<Vec<_>>());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn push_all() {
+        let stack: Stack<R> = Stack::default();
+        assert!(stack.push_all(std::iter::empty()).is_none());
+        assert!(stack.is_empty());
+
+        let num_tasks = 4;
+        let batch_size = 16;
+        let stack = Arc::new(stack);
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        for task_id in 0..num_tasks {
+            let barrier_clone = barrier.clone();
+            let stack_clone = stack.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_clone.wait().await;
+                for batch in 0..64 {
+                    let range = (batch * batch_size)..((batch + 1) * batch_size);
+                    let newest = stack_clone.push_all(range.map(|seq| R::new(task_id, seq)));
+                    assert_eq!(newest.map(|e| e.1), Some((batch + 1) * batch_size - 1));
+                }
+            }));
+        }
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+
+        let mut num_popped = 0;
+        while let Some(newest) = stack.pop() {
+            // Each batch is popped in reverse order without being interleaved.
+            let (task_id, seq) = (newest.0, newest.1);
+            assert_eq!(seq % batch_size, batch_size - 1);
+            for i in (0..seq % batch_size).rev() {
+                let entry = stack.pop().unwrap();
+                assert_eq!((entry.0, entry.1), (task_id, seq + 1 - batch_size + i));
+            }
+            num_popped += batch_size;
+        }
+        assert_eq!(num_popped, num_tasks * 64 * batch_size);
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test(flavor = "multi_thread", worker_threads = 16)]
     async fn mpmc() {
@@ -2403,9 +5420,76 @@ mod stack_test {
     }
 }
 
+#[cfg(test)]
+mod linked_list_test {
+    use crate::ebr::{AtomicShared, Guard, Shared};
+    use crate::LinkedList;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::{AcqRel, Relaxed};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[derive(Default)]
+    struct L(AtomicShared<L>, usize);
+    impl LinkedList for L {
+        fn link_ref(&self) -> &AtomicShared<L> {
+            &self.0
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn remove_if() {
+        let num_threads = 4;
+        let num_entries = 256;
+        let head: Arc<L> = Arc::default();
+        let guard = Guard::new();
+        for i in 0..num_entries {
+            let entry = Shared::new(L(AtomicShared::null(), i));
+            assert!(head.push_back(entry, false, Relaxed, &guard).is_ok());
+        }
+        drop(guard);
+
+        let num_removed = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(num_threads));
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for thread_id in 0..num_threads {
+            let head = head.clone();
+            let num_removed = num_removed.clone();
+            let barrier = barrier.clone();
+            thread_handles.push(thread::spawn(move || {
+                barrier.wait();
+                // Threads `n` and `n + 1` target the same even entries.
+                let target = thread_id / 2;
+                let guard = Guard::new();
+                while head.remove_if(|l| l.1 % 2 == 0 && l.1 / 2 % 2 == target, AcqRel, &guard) {
+                    num_removed.fetch_add(1, Relaxed);
+                }
+            }));
+        }
+        for t in thread_handles {
+            t.join().unwrap();
+        }
+
+        assert_eq!(num_removed.load(Relaxed), num_entries / 2);
+        let guard = Guard::new();
+        let mut remaining = 0;
+        let mut current_ptr = head.next_ptr(Relaxed, &guard);
+        while let Some(current) = current_ptr.as_ref() {
+            assert_eq!(current.1 % 2, 1);
+            remaining += 1;
+            current_ptr = current.next_ptr(Relaxed, &guard);
+        }
+        assert_eq!(remaining, num_entries / 2);
+    }
+}
+
 #[cfg(test)]
 mod ebr_test {
-    use crate::ebr::{suspend, AtomicOwned, AtomicShared, Guard, Owned, Ptr, Shared, Tag};
+    use crate::ebr::{
+        debug_assert_unpinned, set_advance_threshold, suspend, AtomicOwned, AtomicShared, Guard,
+        Owned, Ptr, Shared, Tag,
+    };
     use std::ops::Deref;
     use std::panic::UnwindSafe;
     use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
@@ -2442,6 +5526,115 @@ mod ebr_test {
         }
     }
 
+    #[test]
+    fn pinned_thread_count() {
+        debug_assert_unpinned();
+        let guard = Guard::new();
+        assert!(crate::ebr::pinned_thread_count() >= 1);
+        if cfg!(debug_assertions) {
+            assert!(std::panic::catch_unwind(debug_assert_unpinned).is_err());
+        }
+        drop(guard);
+        debug_assert_unpinned();
+
+        let barrier = std::sync::Arc::new(std::sync::Barrier::new(2));
+        let barrier_clone = barrier.clone();
+        let thread_handle = std::thread::spawn(move || {
+            let guard = Guard::new();
+            barrier_clone.wait();
+            barrier_clone.wait();
+            drop(guard);
+        });
+        barrier.wait();
+        assert!(crate::ebr::pinned_thread_count() >= 1);
+        barrier.wait();
+        thread_handle.join().unwrap();
+        debug_assert_unpinned();
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn repin() {
+        let executed = Shared::new(AtomicBool::new(false));
+        let executed_clone = executed.clone();
+        let barrier = std::sync::Arc::new(std::sync::Barrier::new(2));
+        let barrier_clone = barrier.clone();
+        let thread_handle = std::thread::spawn(move || {
+            let mut guard = Guard::new();
+            barrier_clone.wait();
+            let mut num_repins = 0;
+            while !executed_clone.load(Relaxed) {
+                guard.repin();
+                num_repins += 1;
+                std::thread::yield_now();
+            }
+            num_repins
+        });
+        barrier.wait();
+
+        let executed_clone = executed.clone();
+        let guard = Guard::new();
+        guard.defer_execute(move || executed_clone.store(true, Relaxed));
+        drop(guard);
+        while !executed.load(Relaxed) {
+            drop(Guard::new());
+            std::thread::yield_now();
+        }
+        assert!(thread_handle.join().unwrap() > 0);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn advance_threshold() {
+        fn guards_until_reclaimed() -> usize {
+            let executed = Shared::new(AtomicBool::new(false));
+            let executed_clone = executed.clone();
+            let guard = Guard::new();
+            guard.defer_execute(move || executed_clone.store(true, Relaxed));
+            drop(guard);
+
+            let mut num_guards = 0;
+            while !executed.load(Relaxed) {
+                drop(Guard::new());
+                num_guards += 1;
+            }
+            num_guards
+        }
+
+        // Other tests may advance the global epoch, therefore the fastest reclamation under the
+        // low threshold is compared with the slowest one under the high threshold.
+        set_advance_threshold(0);
+        let low = (0..4).map(|_| guards_until_reclaimed()).min().unwrap();
+        set_advance_threshold(1 << 16);
+        let high = (0..4).map(|_| guards_until_reclaimed()).max().unwrap();
+        set_advance_threshold(255);
+        assert!(low < high, "{low} {high}");
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn deferred_once() {
+        static EXECUTED: AtomicUsize = AtomicUsize::new(0);
+
+        let guard = Guard::new();
+        guard.defer_execute(|| {
+            EXECUTED.fetch_add(1, Relaxed);
+        });
+        for _ in 0..1024 {
+            drop(Guard::new());
+            assert_eq!(EXECUTED.load(Relaxed), 0);
+        }
+        drop(guard);
+
+        while EXECUTED.load(Relaxed) == 0 {
+            drop(Guard::new());
+        }
+        for _ in 0..1024 {
+            drop(Guard::new());
+        }
+        assert_eq!(EXECUTED.load(Relaxed), 1);
+    }
+
     #[cfg_attr(miri, ignore)]
     #[test]
     fn shared() {
@@ -2480,6 +5673,30 @@ mod ebr_test {
         }
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn ptr_get_shared() {
+        static DESTROYED: AtomicBool = AtomicBool::new(false);
+
+        let atomic_shared = AtomicShared::new(A(AtomicUsize::new(10), 10, &DESTROYED));
+        let guard = Guard::new();
+        let ptr = atomic_shared.load(Acquire, &guard);
+        let shared = ptr.get_shared().unwrap();
+        assert_eq!(shared.1, 10);
+
+        drop(atomic_shared.swap((None, Tag::None), Relaxed));
+        assert!(ptr.get_shared().is_some());
+        drop(shared);
+        assert!(ptr.get_shared().is_none());
+        assert_eq!(ptr.as_ref().map(|a| a.1), Some(10));
+        assert!(!DESTROYED.load(Relaxed));
+
+        drop(guard);
+        while !DESTROYED.load(Relaxed) {
+            drop(Guard::new());
+        }
+    }
+
     #[cfg_attr(miri, ignore)]
     #[test]
     fn owned() {
@@ -2982,9 +6199,11 @@ mod random_failure_test {
 #[cfg(feature = "serde")]
 #[cfg(test)]
 mod serde_test {
-    use crate::{HashCache, HashIndex, HashMap, HashSet, TreeIndex};
+    use crate::{Bag, HashCache, HashIndex, HashMap, HashSet, Queue, Stack, TreeIndex};
 
-    use serde_test::{assert_tokens, Token};
+    use serde::de::value::{Error, SeqDeserializer};
+    use serde::Deserialize;
+    use serde_test::{assert_ser_tokens, assert_tokens, Token};
 
     #[test]
     fn hashmap() {
@@ -3064,4 +6283,225 @@ mod serde_test {
             ],
         );
     }
+
+    #[test]
+    fn queue() {
+        let queue: Queue<u64> = Queue::default();
+        assert_ser_tokens(&queue, &[Token::Seq { len: Some(0) }, Token::SeqEnd]);
+        assert!(deserialize::<Queue<u64>>(&[]).is_empty());
+
+        queue.push(2);
+        queue.push(7);
+        queue.push(5);
+        assert_ser_tokens(
+            &queue,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::U64(2),
+                Token::U64(7),
+                Token::U64(5),
+                Token::SeqEnd,
+            ],
+        );
+        let deserialized: Queue<u64> = deserialize(&[2, 7, 5]);
+        for q in [queue, deserialized] {
+            assert_eq!(q.drain().map(|e| **e).collect::<Vec<_>>(), [2, 7, 5]);
+        }
+    }
+
+    #[test]
+    fn stack() {
+        let stack: Stack<u64> = Stack::default();
+        assert_ser_tokens(&stack, &[Token::Seq { len: Some(0) }, Token::SeqEnd]);
+        assert!(deserialize::<Stack<u64>>(&[]).is_empty());
+
+        stack.push(2);
+        stack.push(7);
+        stack.push(5);
+        assert_ser_tokens(
+            &stack,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::U64(5),
+                Token::U64(7),
+                Token::U64(2),
+                Token::SeqEnd,
+            ],
+        );
+        let deserialized: Stack<u64> = deserialize(&[5, 7, 2]);
+        for s in [stack, deserialized] {
+            let mut popped = Vec::new();
+            while let Some(e) = s.pop() {
+                popped.push(**e);
+            }
+            assert_eq!(popped, [5, 7, 2]);
+        }
+    }
+
+    #[test]
+    fn bag() {
+        assert!(deserialize::<Bag<u64>>(&[]).is_empty());
+
+        let deserialized: Bag<u64> = deserialize(&[2, 4, 3, 1]);
+        let mut popped = deserialized.pop_all(Vec::new(), |mut a, v| {
+            a.push(v);
+            a
+        });
+        popped.sort_unstable();
+        assert_eq!(popped, [1, 2, 3, 4]);
+    }
+
+    fn deserialize<C: for<'d> Deserialize<'d>>(vals: &[u64]) -> C {
+        let deserializer = SeqDeserializer::<_, Error>::new(vals.iter().copied());
+        C::deserialize(deserializer).unwrap()
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[cfg(test)]
+mod tracing_test {
+    use crate::HashMap;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    #[derive(Default)]
+    struct ResizeRecorder(Arc<Mutex<Vec<(u64, u64)>>>);
+
+    #[derive(Default)]
+    struct CapacityVisitor(Option<u64>, Option<u64>);
+
+    impl Visit for CapacityVisitor {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            match field.name() {
+                "capacity" => self.0 = Some(value),
+                "new_capacity" => self.1 = Some(value),
+                _ => (),
+            }
+        }
+        fn record_debug(&mut self, _: &Field, _: &dyn Debug) {}
+    }
+
+    impl Subscriber for ResizeRecorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let mut visitor = CapacityVisitor::default();
+            event.record(&mut visitor);
+            if let (Some(old_capacity), Some(new_capacity)) = (visitor.0, visitor.1) {
+                self.0.lock().unwrap().push((old_capacity, new_capacity));
+            }
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn resize() {
+        let recorder = ResizeRecorder::default();
+        let resized = recorder.0.clone();
+        tracing::subscriber::with_default(recorder, || {
+            let hashmap: HashMap<usize, usize> = HashMap::default();
+            for k in 0..4096 {
+                assert!(hashmap.insert(k, k).is_ok());
+            }
+        });
+        let resized = resized.lock().unwrap();
+        assert!(!resized.is_empty());
+        assert!(resized.iter().any(|(old, new)| old < new));
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[cfg(test)]
+mod rayon_test {
+    use crate::HashMap;
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn par_extend() {
+        let workload_size = 1 << 20;
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        hashmap.par_extend((0..workload_size).into_par_iter().map(|k| (k, k)));
+        assert_eq!(hashmap.len(), workload_size);
+        assert!(hashmap.capacity() >= workload_size);
+        assert!((0..workload_size).all(|k| hashmap.read(&k, |_, v| *v) == Some(k)));
+
+        // Duplicate keys are resolved in favor of either one of them.
+        hashmap.par_extend(
+            (0..workload_size * 2)
+                .into_par_iter()
+                .map(|k| (k / 2, usize::MAX - k)),
+        );
+        assert_eq!(hashmap.len(), workload_size);
+        assert!((0..workload_size).all(|k| hashmap.read(&k, |_, v| *v) == Some(k)));
+
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        hashmap.par_extend(
+            (0..workload_size * 2)
+                .into_par_iter()
+                .map(|k| (k / 2, k % 2)),
+        );
+        assert_eq!(hashmap.len(), workload_size);
+        assert!(!hashmap.any(|_, v| *v > 1));
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn par_retain() {
+        let hashmap: HashMap<usize, usize> = HashMap::default();
+        for k in 0..4096 {
+            assert!(hashmap.insert(k, k).is_ok());
+        }
+        hashmap.par_retain(|k, v| {
+            *v += 1;
+            k % 2 == 0
+        });
+        assert_eq!(hashmap.len(), 2048);
+        for k in 0..4096 {
+            if k % 2 == 0 {
+                assert_eq!(hashmap.read(&k, |_, v| *v), Some(k + 1));
+            } else {
+                assert!(!hashmap.contains(&k));
+            }
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn par_retain_resize() {
+        let workload_size = 4096;
+        for _ in 0..16 {
+            let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::default());
+            for k in 0..workload_size {
+                assert!(hashmap.insert(k, k).is_ok());
+            }
+            let hashmap_clone = hashmap.clone();
+            let inserter = thread::spawn(move || {
+                for k in workload_size..workload_size * 16 {
+                    assert!(hashmap_clone.insert(k, k).is_ok());
+                }
+            });
+            hashmap.par_retain(|k, v| {
+                if *k < workload_size {
+                    *v += 1;
+                }
+                true
+            });
+            inserter.join().unwrap();
+            assert!((0..workload_size).all(|k| hashmap.read(&k, |_, v| *v) == Some(k + 1)));
+        }
+    }
 }
@@ -7,16 +7,19 @@ mod node;
 
 use crate::ebr::{AtomicShared, Guard, Ptr, Shared, Tag};
 use crate::wait_queue::AsyncWait;
-use leaf::{InsertResult, Leaf, RemoveResult, Scanner};
+use crate::LinkedList;
+use leaf::{InsertResult, Leaf, RemoveResult, Scanner, DIMENSION};
 use node::Node;
 use std::borrow::Borrow;
 use std::cmp::Ordering;
+use std::collections::VecDeque;
 use std::fmt::{self, Debug};
 use std::iter::FusedIterator;
 use std::ops::Bound::{Excluded, Included, Unbounded};
 use std::ops::RangeBounds;
 use std::panic::UnwindSafe;
 use std::pin::Pin;
+use std::ptr;
 use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
 
 /// Scalable concurrent B-plus tree.
@@ -92,6 +95,25 @@ where
     guard: &'g Guard,
 }
 
+/// An iterator over a sub-range of entries in a [`TreeIndex`] in descending order.
+///
+/// A [`RangeRev`] buffers at most one leaf worth of entries; each time the buffer runs dry, it
+/// locates the leaf containing the largest key smaller than the last returned key by traversing
+/// the tree from the root.
+pub struct RangeRev<'t, 'g, K, V, R>
+where
+    K: 'static + Clone + Ord,
+    V: 'static + Clone,
+    R: RangeBounds<K>,
+{
+    root: &'t AtomicShared<Node<K, V>>,
+    buffer: VecDeque<(&'g K, &'g V)>,
+    range: R,
+    cursor: Option<&'g K>,
+    finished: bool,
+    guard: &'g Guard,
+}
+
 impl<K, V> TreeIndex<K, V>
 where
     K: 'static + Clone + Ord,
@@ -114,8 +136,71 @@ where
         }
     }
 
+    /// Creates a [`TreeIndex`] from key-value pairs sorted by key in ascending order.
+    ///
+    /// The entries are packed into full leaves, and the tree is built from the bottom up without
+    /// splitting any nodes. Entries that are out of order are inserted one by one after the tree
+    /// is built, and those having duplicate keys are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the keys are not sorted in strictly ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::from_sorted((0..64).map(|k| (k, 0)));
+    ///
+    /// assert_eq!(treeindex.len(), 64);
+    /// assert_eq!(treeindex.peek_with(&63, |_, v| *v), Some(0));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn from_sorted<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut leaves: Vec<Shared<Leaf<K, V>>> = Vec::new();
+        let mut unsorted = Vec::new();
+        let mut max_key: Option<K> = None;
+        let mut leaf_len = DIMENSION.num_entries;
+        for (key, val) in iter {
+            let sorted = !matches!(&max_key, Some(max_key) if *max_key >= key);
+            debug_assert!(sorted, "keys are not sorted");
+            if !sorted {
+                unsorted.push((key, val));
+                continue;
+            }
+            if leaf_len == DIMENSION.num_entries {
+                let leaf = Shared::new(Leaf::new());
+                if let Some(prev_leaf) = leaves.last() {
+                    prev_leaf
+                        .link_ref()
+                        .swap((Some(leaf.clone()), Tag::None), Relaxed);
+                }
+                leaves.push(leaf);
+                leaf_len = 0;
+            }
+            if let Some(leaf) = leaves.last() {
+                max_key.replace(key.clone());
+                leaf.insert_unchecked(key, val, leaf_len);
+                leaf_len += 1;
+            }
+        }
+
+        let treeindex = Self {
+            root: Node::build(leaves).map_or_else(AtomicShared::null, AtomicShared::from),
+        };
+        for (key, val) in unsorted {
+            let _result = treeindex.insert(key, val);
+        }
+        treeindex
+    }
+
     /// Inserts a key-value pair.
     ///
+    /// The existing value is never overwritten; the target leaf is searched for the key before it
+    /// is split, therefore the [`TreeIndex`] is not restructured if the key exists.
+    ///
     /// # Errors
     ///
     /// Returns an error along with the supplied key-value pair if the key exists.
@@ -132,14 +217,52 @@ where
     /// assert_eq!(treeindex.peek_with(&1, |k, v| *v).unwrap(), 10);
     /// ```
     #[inline]
-    pub fn insert(&self, mut key: K, mut val: V) -> Result<(), (K, V)> {
+    pub fn insert(&self, key: K, val: V) -> Result<(), (K, V)> {
+        self.upsert(key, val, None)
+    }
+
+    /// Inserts a key-value pair, or updates the value if the key exists.
+    ///
+    /// The existing value is not modified in place: `updater` is invoked on a clone of the value,
+    /// and the entry is replaced with the updated clone at once, therefore readers observe either
+    /// the old or the new value. `updater` may be invoked more than once if the entry is modified
+    /// by another thread in the meantime, however only one of the results is stored, and each
+    /// invocation is passed a fresh clone of the latest value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// treeindex.insert_or_update(1, 10, |v| *v += 1);
+    /// assert_eq!(treeindex.peek_with(&1, |_, v| *v), Some(10));
+    ///
+    /// treeindex.insert_or_update(1, 10, |v| *v += 1);
+    /// assert_eq!(treeindex.peek_with(&1, |_, v| *v), Some(11));
+    /// assert_eq!(treeindex.len(), 1);
+    /// ```
+    #[inline]
+    pub fn insert_or_update<F: FnMut(&mut V)>(&self, key: K, val: V, mut updater: F) {
+        let result = self.upsert(key, val, Some(&mut updater));
+        debug_assert!(result.is_ok());
+    }
+
+    /// Inserts a key-value pair, or updates the value if the key exists and `updater` is given.
+    fn upsert(
+        &self,
+        mut key: K,
+        mut val: V,
+        mut updater: Option<&mut (dyn FnMut(&mut V) + '_)>,
+    ) -> Result<(), (K, V)> {
         let mut new_root = None;
         loop {
             let guard = Guard::new();
             if let Some(root_ref) = self.root.load(Acquire, &guard).as_ref() {
-                match root_ref.insert(key, val, &mut (), &guard) {
+                match root_ref.upsert(key, val, updater.as_deref_mut(), &mut (), &guard) {
                     Ok(r) => match r {
-                        InsertResult::Success => return Ok(()),
+                        InsertResult::Success | InsertResult::Updated => return Ok(()),
                         InsertResult::Frozen(k, v) | InsertResult::Retry(k, v) => {
                             key = k;
                             val = v;
@@ -147,7 +270,7 @@ where
                         }
                         InsertResult::Duplicate(k, v) => return Err((k, v)),
                         InsertResult::Full(k, v) => {
-                            let (k, v) = Node::split_root(k, v, &self.root, &guard);
+                            let (k, v) = Node::split_root(root_ref, k, v, &self.root, &guard);
                             key = k;
                             val = v;
                             continue;
@@ -218,8 +341,9 @@ where
                                 true
                             }
                             InsertResult::Duplicate(k, v) => return Err((k, v)),
+                            InsertResult::Updated => unreachable!(),
                             InsertResult::Full(k, v) => {
-                                let (k, v) = Node::split_root(k, v, &self.root, &guard);
+                                let (k, v) = Node::split_root(root_ref, k, v, &self.root, &guard);
                                 key = k;
                                 val = v;
                                 continue;
@@ -490,6 +614,97 @@ where
         self.peek(key, &guard).map(|v| reader(key, v))
     }
 
+    /// Returns `true` if the [`TreeIndex`] contains the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::default();
+    ///
+    /// assert!(!treeindex.contains(&1));
+    /// assert!(treeindex.insert(1, 0).is_ok());
+    /// assert!(treeindex.contains(&1));
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.peek(key, &Guard::new()).is_some()
+    }
+
+    /// Returns a clone of the value associated with the key without acquiring locks.
+    ///
+    /// Returns `None` if the key does not exist. The returned value is not affected by subsequent
+    /// modifications.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, String> = TreeIndex::default();
+    ///
+    /// assert!(treeindex.get_cloned(&1).is_none());
+    /// assert!(treeindex.insert(1, String::from("one")).is_ok());
+    /// assert_eq!(treeindex.get_cloned(&1).unwrap(), "one");
+    /// ```
+    #[inline]
+    pub fn get_cloned<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.peek(key, &Guard::new()).cloned()
+    }
+
+    /// Returns a guarded reference to the value for the specified key, inserting the value
+    /// returned by `constructor` if the key does not exist.
+    ///
+    /// `constructor` is invoked at most once, and only if the key does not exist. If another
+    /// thread inserts the same key first, the value inserted by the other thread is returned. The
+    /// returned reference can survive as long as the associated [`Guard`] is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Guard;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// let guard = Guard::new();
+    /// assert_eq!(*treeindex.get_or_insert(1, || 10, &guard), 10);
+    /// assert_eq!(*treeindex.get_or_insert(1, || 11, &guard), 10);
+    /// ```
+    #[inline]
+    pub fn get_or_insert<'g, F: FnOnce() -> V>(
+        &self,
+        key: K,
+        constructor: F,
+        guard: &'g Guard,
+    ) -> &'g V {
+        if let Some(v) = self.peek(&key, guard) {
+            return v;
+        }
+        let query = key.clone();
+        let (mut key, val) = (key, constructor());
+        loop {
+            // The key is read again to retrieve the value in the `TreeIndex` regardless of which
+            // thread inserted it; the loop only repeats if the key is removed in the meantime.
+            key = match self.insert(key, val.clone()) {
+                Ok(()) => query.clone(),
+                Err((k, _)) => k,
+            };
+            if let Some(v) = self.peek(&query, guard) {
+                return v;
+            }
+        }
+    }
+
     /// Clears the [`TreeIndex`].
     ///
     /// # Examples
@@ -507,6 +722,81 @@ where
         self.root.swap((None, Tag::None), Relaxed);
     }
 
+    /// Retains the entries specified by the predicate.
+    ///
+    /// Entries are visited in ascending key order, and those failing the predicate are removed;
+    /// leaves emptied by the removal are unlinked and merged in the same way as
+    /// [`remove`](Self::remove). Entries that have existed since the invocation of the method are
+    /// guaranteed to be visited if they are not removed; an entry is not removed if its value has
+    /// been updated by another thread after the predicate was evaluated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Guard;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// for k in 0..8 {
+    ///     assert!(treeindex.insert(k, 1).is_ok());
+    /// }
+    ///
+    /// treeindex.retain(|k, _| k % 2 == 0);
+    /// let keys: Vec<u64> = treeindex.iter(&Guard::new()).map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, vec![0, 2, 4, 6]);
+    /// ```
+    #[inline]
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut pred: F) {
+        let guard = Guard::new();
+        for (k, v) in self.iter(&guard) {
+            if !pred(k, v) {
+                self.remove_if(k, |current| ptr::eq(current, v));
+            }
+        }
+    }
+
+    /// Moves all the entries having a key greater than or equal to the given key into a new
+    /// [`TreeIndex`].
+    ///
+    /// Each entry is inserted into the new [`TreeIndex`] before being removed from `self`,
+    /// therefore a reader always finds the entry in at least one of them. The operation is not
+    /// atomic as a whole; entries inserted into `self` by another thread during the operation may
+    /// or may not be moved, and an entry updated by another thread after being copied stays in
+    /// `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, 10).is_ok());
+    /// assert!(treeindex.insert(2, 11).is_ok());
+    /// assert!(treeindex.insert(3, 12).is_ok());
+    ///
+    /// let split = treeindex.split_off(&2);
+    /// assert_eq!(treeindex.len(), 1);
+    /// assert_eq!(split.len(), 2);
+    /// assert_eq!(split.peek_with(&2, |_, v| *v), Some(11));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn split_off(&self, key: &K) -> Self {
+        let split = Self::new();
+        let guard = Guard::new();
+        for (k, v) in self.range(key.clone().., &guard) {
+            if split.insert(k.clone(), v.clone()).is_ok()
+                && !self.remove_if(k, |current| ptr::eq(current, v))
+            {
+                // The entry was updated or removed by another thread after being copied.
+                split.remove(k);
+            }
+        }
+        split
+    }
+
     /// Returns the size of the [`TreeIndex`].
     ///
     /// It internally scans all the leaf nodes, and therefore the time complexity is O(N).
@@ -527,6 +817,8 @@ where
 
     /// Returns `true` if the [`TreeIndex`] is empty.
     ///
+    /// It only examines the first entry in the [`TreeIndex`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -544,6 +836,10 @@ where
 
     /// Returns the depth of the [`TreeIndex`].
     ///
+    /// The depth is the number of nodes on the path from the root to the leftmost leaf; the value
+    /// is a momentary snapshot, and it may be outdated if the [`TreeIndex`] is being split or
+    /// merged concurrently.
+    ///
     /// # Examples
     ///
     /// ```
@@ -566,7 +862,8 @@ where
     /// The returned [`Iter`] starts scanning from the minimum key-value pair. Key-value pairs
     /// are scanned in ascending order, and key-value pairs that have existed since the invocation
     /// of the method are guaranteed to be visited if they are not removed. However, it is possible
-    /// to visit removed key-value pairs momentarily.
+    /// to visit removed key-value pairs momentarily. Leaf nodes being split by other threads do
+    /// not make the [`Iter`] yield keys out of order.
     ///
     /// # Examples
     ///
@@ -576,7 +873,14 @@ where
     ///
     /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
     ///
+    /// assert!(treeindex.insert(2, 0).is_ok());
+    /// assert!(treeindex.insert(1, 0).is_ok());
+    ///
     /// let guard = Guard::new();
+    /// let keys: Vec<u64> = treeindex.iter(&guard).map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, vec![1, 2]);
+    ///
+    /// treeindex.clear();
     /// let mut iter = treeindex.iter(&guard);
     /// assert!(iter.next().is_none());
     /// ```
@@ -610,6 +914,101 @@ where
     ) -> Range<'t, 'g, K, V, R> {
         Range::new(&self.root, range, guard)
     }
+
+    /// Returns a [`RangeRev`] that scans keys in the given range in descending order.
+    ///
+    /// Key-value pairs in the range are scanned in descending order, and key-value pairs that
+    /// have existed since the invocation of the method are guaranteed to be visited if they are
+    /// not removed. However, it is possible to visit removed key-value pairs.
+    ///
+    /// Unlike [`Range`], the returned iterator cannot follow the leaf linked list backwards;
+    /// instead, it keeps a buffer of at most one leaf worth of entries, and each refill of the
+    /// buffer costs a traversal from the root of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Guard;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// for k in 0..16 {
+    ///     assert!(treeindex.insert(k, 1).is_ok());
+    /// }
+    ///
+    /// let guard = Guard::new();
+    /// let keys: Vec<u64> = treeindex.range_rev(4..8, &guard).map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, vec![7, 6, 5, 4]);
+    /// ```
+    #[inline]
+    pub fn range_rev<'t, 'g, R: RangeBounds<K>>(
+        &'t self,
+        range: R,
+        guard: &'g Guard,
+    ) -> RangeRev<'t, 'g, K, V, R> {
+        RangeRev::new(&self.root, range, guard)
+    }
+
+    /// Invokes the supplied closure for each key-value pair in the given range in ascending order.
+    ///
+    /// The same guarantees as [`range`](Self::range) apply; key-value pairs that have existed
+    /// since the invocation of the method are visited if they are not removed, and the closure is
+    /// never invoked for the same key twice even if leaves are split or merged concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Guard;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// for k in 0..16 {
+    ///     assert!(treeindex.insert(k, 1).is_ok());
+    /// }
+    ///
+    /// let mut sum = 0;
+    /// treeindex.scan_range(4..8, |_, v| sum += v, &Guard::new());
+    /// assert_eq!(sum, 4);
+    /// ```
+    #[inline]
+    pub fn scan_range<R: RangeBounds<K>, F: FnMut(&K, &V)>(
+        &self,
+        range: R,
+        mut scanner: F,
+        guard: &Guard,
+    ) {
+        for (k, v) in self.range(range, guard) {
+            scanner(k, v);
+        }
+    }
+
+    /// Returns clones of the key-value pairs in the given range in ascending order.
+    ///
+    /// The returned key-value pairs do not form a consistent snapshot of the [`TreeIndex`]; the
+    /// same guarantees as [`range`](Self::range) apply, therefore entries inserted or removed
+    /// during the scan may or may not be included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, 10).is_ok());
+    /// assert!(treeindex.insert(4, 40).is_ok());
+    /// assert!(treeindex.insert(9, 90).is_ok());
+    /// assert_eq!(treeindex.range_cloned(4..=8), vec![(4, 40)]);
+    /// ```
+    #[inline]
+    pub fn range_cloned<R: RangeBounds<K>>(&self, range: R) -> Vec<(K, V)> {
+        let guard = Guard::new();
+        self.range(range, &guard)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
 }
 
 impl<K, V> Clone for TreeIndex<K, V>
@@ -930,3 +1329,125 @@ where
     R: RangeBounds<K> + UnwindSafe,
 {
 }
+
+impl<'t, 'g, K, V, R> RangeRev<'t, 'g, K, V, R>
+where
+    K: 'static + Clone + Ord,
+    V: 'static + Clone,
+    R: RangeBounds<K>,
+{
+    #[inline]
+    fn new(
+        root: &'t AtomicShared<Node<K, V>>,
+        range: R,
+        guard: &'g Guard,
+    ) -> RangeRev<'t, 'g, K, V, R> {
+        RangeRev::<'t, 'g, K, V, R> {
+            root,
+            buffer: VecDeque::new(),
+            range,
+            cursor: None,
+            finished: false,
+            guard,
+        }
+    }
+
+    /// Fills the buffer with the largest entries smaller than the cursor.
+    fn refill(&mut self) {
+        let Some(root_ref) = self.root.load(Acquire, self.guard).as_ref() else {
+            self.finished = true;
+            return;
+        };
+        let upper_bound = self.cursor.map_or(self.range.end_bound(), Excluded);
+
+        let bounds = (self.range.start_bound(), upper_bound);
+
+        // Scanning from the leaf containing the largest key below the upper bound is only an
+        // optimization; fall back to the lower bound of the range if it yields nothing.
+        let leaf_scanner = match upper_bound {
+            Excluded(key) | Included(key) => root_ref.max_less_leaf(key, self.guard),
+            Unbounded => root_ref.max_appr(self.guard),
+        };
+        let ranges = leaf_scanner
+            .map(|leaf_scanner| Range {
+                root: self.root,
+                leaf_scanner: Some(leaf_scanner),
+                range: bounds,
+                check_lower_bound: true,
+                check_upper_bound: true,
+                guard: self.guard,
+            })
+            .into_iter()
+            .chain(Some(Range::new(self.root, bounds, self.guard)));
+        for range in ranges {
+            for entry in range {
+                if self.buffer.len() == DIMENSION.num_entries {
+                    self.buffer.pop_front();
+                }
+                self.buffer.push_back(entry);
+            }
+            if !self.buffer.is_empty() {
+                break;
+            }
+        }
+        if let Some((key, _)) = self.buffer.front() {
+            self.cursor.replace(key);
+        } else {
+            self.finished = true;
+        }
+    }
+}
+
+impl<'t, 'g, K, V, R> Debug for RangeRev<'t, 'g, K, V, R>
+where
+    K: 'static + Clone + Ord,
+    V: 'static + Clone,
+    R: RangeBounds<K>,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangeRev")
+            .field("root", &self.root)
+            .field("buffered", &self.buffer.len())
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+impl<'t, 'g, K, V, R> Iterator for RangeRev<'t, 'g, K, V, R>
+where
+    K: 'static + Clone + Ord,
+    V: 'static + Clone,
+    R: RangeBounds<K>,
+{
+    type Item = (&'g K, &'g V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.buffer.pop_back() {
+                return Some(entry);
+            }
+            if self.finished {
+                return None;
+            }
+            self.refill();
+        }
+    }
+}
+
+impl<'t, 'g, K, V, R> FusedIterator for RangeRev<'t, 'g, K, V, R>
+where
+    K: 'static + Clone + Ord,
+    V: 'static + Clone,
+    R: RangeBounds<K>,
+{
+}
+
+impl<'t, 'g, K, V, R> UnwindSafe for RangeRev<'t, 'g, K, V, R>
+where
+    K: 'static + Clone + Ord + UnwindSafe,
+    V: 'static + Clone + UnwindSafe,
+    R: RangeBounds<K> + UnwindSafe,
+{
+}
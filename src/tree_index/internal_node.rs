@@ -54,6 +54,24 @@ where
         }
     }
 
+    /// Creates a new [`InternalNode`] from child nodes sorted by their maximum keys.
+    ///
+    /// The last child becomes the unbounded child.
+    pub(super) fn from_children(children: Vec<(K, Shared<Node<K, V>>)>) -> InternalNode<K, V> {
+        debug_assert!(!children.is_empty() && children.len() <= DIMENSION.num_entries + 1);
+        let mut internal_node = Self::new();
+        let mut children = children.into_iter();
+        if let Some((_, unbounded)) = children.next_back() {
+            internal_node.unbounded_child = AtomicShared::from(unbounded);
+        }
+        for (index, (max_key, child)) in children.enumerate() {
+            internal_node
+                .children
+                .insert_unchecked(max_key, AtomicShared::from(child), index);
+        }
+        internal_node
+    }
+
     /// Returns the depth of the node.
     #[inline]
     pub(super) fn depth(&self, depth: usize, guard: &Guard) -> usize {
@@ -135,6 +153,17 @@ where
         }
     }
 
+    /// Returns a [`Scanner`] of the rightmost leaf.
+    ///
+    /// Returns `None` if the [`InternalNode`] is being split or merged.
+    #[inline]
+    pub(super) fn max_appr<'g>(&self, guard: &'g Guard) -> Option<Scanner<'g, K, V>> {
+        self.unbounded_child
+            .load(Acquire, guard)
+            .as_ref()
+            .and_then(|unbounded| unbounded.max_appr(guard))
+    }
+
     /// Returns a [`Scanner`] pointing to an entry that is close enough to the entry with the
     /// maximum key among those keys smaller than or equal to the given key.
     ///
@@ -183,12 +212,64 @@ where
         None
     }
 
-    /// Inserts a key-value pair.
+    /// Returns a [`Scanner`] of the leaf containing the entry with the maximum key among those
+    /// keys smaller than the given key.
+    ///
+    /// Returns `None` if all the keys in the [`InternalNode`] are equal to or greater than the given
+    /// key.
     #[inline]
-    pub(super) fn insert<D: DeriveAsyncWait>(
+    pub(super) fn max_less_leaf<'g, Q>(
+        &self,
+        key: &Q,
+        guard: &'g Guard,
+    ) -> Option<Scanner<'g, K, V>>
+    where
+        K: 'g + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let (child, metadata) = self.children.min_greater_equal(key);
+            let child_ptr = child.map_or_else(
+                || self.unbounded_child.load(Acquire, guard),
+                |(_, child)| child.load(Acquire, guard),
+            );
+            if let Some(child) = child_ptr.as_ref() {
+                if !self.children.validate(metadata) {
+                    // Data race resolution - see `LeafNode::search`.
+                    continue;
+                }
+                if let Some(scanner) = child.max_less_leaf(key, guard) {
+                    return Some(scanner);
+                }
+            } else if child.is_some() {
+                // It is not a hot loop - see `LeafNode::search`.
+                continue;
+            }
+
+            // The child covering the key does not have a smaller key; visit the preceding children.
+            let mut bound: &Q = key;
+            while let Some((child_key, child)) =
+                Scanner::max_less(&self.children, bound).and_then(|scanner| scanner.get())
+            {
+                if let Some(child) = child.load(Acquire, guard).as_ref() {
+                    if let Some(scanner) = child.max_less_leaf(key, guard) {
+                        return Some(scanner);
+                    }
+                }
+                bound = child_key.borrow();
+            }
+            return None;
+        }
+    }
+
+    /// Inserts a key-value pair, or updates the value if the key exists and `updater` is given.
+    #[allow(clippy::too_many_lines)]
+    #[inline]
+    pub(super) fn upsert<D: DeriveAsyncWait>(
         &self,
         mut key: K,
         mut val: V,
+        mut updater: Option<&mut (dyn FnMut(&mut V) + '_)>,
         async_wait: &mut D,
         guard: &Guard,
     ) -> Result<InsertResult<K, V>, (K, V)> {
@@ -199,11 +280,18 @@ where
                 if let Some(child_ref) = child_ptr.as_ref() {
                     if self.children.validate(metadata) {
                         // Data race resolution - see `LeafNode::search`.
-                        let insert_result = child_ref.insert(key, val, async_wait, guard)?;
+                        let insert_result = child_ref.upsert(
+                            key,
+                            val,
+                            updater.as_deref_mut(),
+                            async_wait,
+                            guard,
+                        )?;
                         match insert_result {
                             InsertResult::Success
                             | InsertResult::Duplicate(..)
-                            | InsertResult::Frozen(..) => return Ok(insert_result),
+                            | InsertResult::Frozen(..)
+                            | InsertResult::Updated => return Ok(insert_result),
                             InsertResult::Full(k, v) => {
                                 let split_result = self.split_node(
                                     k,
@@ -252,11 +340,13 @@ where
                 if !self.children.validate(metadata) {
                     continue;
                 }
-                let insert_result = unbounded.insert(key, val, async_wait, guard)?;
+                let insert_result =
+                    unbounded.upsert(key, val, updater.as_deref_mut(), async_wait, guard)?;
                 match insert_result {
                     InsertResult::Success
                     | InsertResult::Duplicate(..)
-                    | InsertResult::Frozen(..) => return Ok(insert_result),
+                    | InsertResult::Frozen(..)
+                    | InsertResult::Updated => return Ok(insert_result),
                     InsertResult::Full(k, v) => {
                         let split_result = self.split_node(
                             k,
@@ -640,9 +730,10 @@ where
             self.split_op.low_key_node.clone(Relaxed, guard),
         ) {
             InsertResult::Success => (),
-            InsertResult::Duplicate(..) | InsertResult::Frozen(..) | InsertResult::Retry(..) => {
-                unreachable!()
-            }
+            InsertResult::Duplicate(..)
+            | InsertResult::Frozen(..)
+            | InsertResult::Retry(..)
+            | InsertResult::Updated => unreachable!(),
             InsertResult::Full(..) | InsertResult::Retired(..) => {
                 // Insertion failed: expects that the parent splits this node.
                 *exit_guard = false;
@@ -1001,14 +1092,15 @@ mod test {
         assert_eq!(internal_node.depth(1, &guard), 3);
 
         for k in 0..8192 {
-            match internal_node.insert(k, k, &mut (), &guard) {
+            match internal_node.upsert(k, k, None, &mut (), &guard) {
                 Ok(result) => match result {
                     InsertResult::Success => {
                         assert_eq!(internal_node.search(&k, &guard), Some(&k));
                     }
                     InsertResult::Duplicate(..)
                     | InsertResult::Frozen(..)
-                    | InsertResult::Retired(..) => unreachable!(),
+                    | InsertResult::Retired(..)
+                    | InsertResult::Updated => unreachable!(),
                     InsertResult::Full(_, _) => {
                         internal_node.rollback(&guard);
                         for j in 0..k {
@@ -1033,13 +1125,13 @@ mod test {
                         break;
                     }
                     InsertResult::Retry(k, v) => {
-                        let result = internal_node.insert(k, v, &mut (), &guard);
+                        let result = internal_node.upsert(k, v, None, &mut (), &guard);
                         assert!(result.is_ok());
                         assert_eq!(internal_node.search(&k, &guard), Some(&k));
                     }
                 },
                 Err((k, v)) => {
-                    let result = internal_node.insert(k, v, &mut (), &guard);
+                    let result = internal_node.upsert(k, v, None, &mut (), &guard);
                     assert!(result.is_ok());
                     assert_eq!(internal_node.search(&k, &guard), Some(&k));
                 }
@@ -1056,7 +1148,7 @@ mod test {
         for _ in 0..64 {
             let internal_node = Shared::new(new_level_3_node());
             assert!(internal_node
-                .insert(usize::MAX, usize::MAX, &mut (), &Guard::new())
+                .upsert(usize::MAX, usize::MAX, None, &mut (), &Guard::new())
                 .is_ok());
             let mut task_handles = Vec::with_capacity(num_tasks);
             for task_id in 0..num_tasks {
@@ -1069,10 +1161,17 @@ mod test {
                     let range = (task_id * workload_size)..((task_id + 1) * workload_size);
                     for id in range.clone() {
                         loop {
-                            if let Ok(r) = internal_node_clone.insert(id, id, &mut (), &guard) {
+                            if let Ok(r) = internal_node_clone.upsert(id, id, None, &mut (), &guard)
+                            {
                                 match r {
                                     InsertResult::Success => {
-                                        match internal_node_clone.insert(id, id, &mut (), &guard) {
+                                        match internal_node_clone.upsert(
+                                            id,
+                                            id,
+                                            None,
+                                            &mut (),
+                                            &guard,
+                                        ) {
                                             Ok(InsertResult::Duplicate(..)) | Err(_) => (),
                                             _ => unreachable!(),
                                         }
@@ -1166,9 +1265,10 @@ mod test {
                         {
                             barrier_clone.wait().await;
                             let guard = Guard::new();
-                            match internal_node_clone.insert(
+                            match internal_node_clone.upsert(
                                 fixed_point,
                                 fixed_point,
+                                None,
                                 &mut (),
                                 &guard,
                             ) {
@@ -1192,7 +1292,7 @@ mod test {
                                 if i != fixed_point {
                                     if let Ok(
                                         InsertResult::Full(_, _) | InsertResult::Retired(_, _),
-                                    ) = internal_node_clone.insert(i, i, &mut (), &guard)
+                                    ) = internal_node_clone.upsert(i, i, None, &mut (), &guard)
                                     {
                                         internal_node_clone.rollback(&guard);
                                     }
@@ -64,6 +64,25 @@ pub enum InsertResult<K, V> {
 
     /// The operation can be retried.
     Retry(K, V),
+
+    /// The value associated with the key was updated.
+    Updated,
+}
+
+/// The result of replacing an entry.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReplaceResult {
+    /// Replace succeeded.
+    Success,
+
+    /// The key was not found.
+    NotFound,
+
+    /// No vacant slot for the updated entry.
+    Full,
+
+    /// The [`Leaf`] is frozen.
+    Frozen,
 }
 
 /// The result of removal.
@@ -276,7 +295,8 @@ where
                                 }
                                 Err(actual) => {
                                     if DIMENSION.rank(actual, i) == DIMENSION.removed_rank() {
-                                        return RemoveResult::Fail;
+                                        // The entry may have been replaced with an updated one.
+                                        return self.remove_if(key, condition);
                                     }
                                     if Dimension::frozen(actual) {
                                         return RemoveResult::Frozen;
@@ -294,6 +314,109 @@ where
         RemoveResult::Fail
     }
 
+    /// Inserts a key value pair, or updates the value if the key exists.
+    ///
+    /// `updater` is invoked on a clone of the existing value, and it may be invoked more than once
+    /// if the entry is concurrently modified.
+    #[inline]
+    pub(super) fn upsert(
+        &self,
+        mut key: K,
+        mut val: V,
+        updater: Option<&mut (dyn FnMut(&mut V) + '_)>,
+    ) -> InsertResult<K, V> {
+        let Some(updater) = updater else {
+            return self.insert(key, val);
+        };
+        loop {
+            match self.insert(key, val) {
+                InsertResult::Duplicate(k, v) => match self.replace(&k, updater) {
+                    ReplaceResult::Success => return InsertResult::Updated,
+                    ReplaceResult::NotFound => {
+                        key = k;
+                        val = v;
+                    }
+                    ReplaceResult::Full => return InsertResult::Full(k, v),
+                    ReplaceResult::Frozen => return InsertResult::Frozen(k, v),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Replaces the entry associated with the key with an updated clone.
+    ///
+    /// The updated entry is written into a vacant slot, and the slot takes over the rank of the
+    /// existing entry at once, therefore readers observe either of the two values.
+    pub(super) fn replace<Q>(&self, key: &Q, updater: &mut dyn FnMut(&mut V)) -> ReplaceResult
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut metadata = self.metadata.load(Acquire);
+        'after_read_metadata: loop {
+            if Dimension::frozen(metadata) || Dimension::retired(metadata) {
+                return ReplaceResult::Frozen;
+            }
+            let Some(index) = self.search_slot(key, metadata) else {
+                return ReplaceResult::NotFound;
+            };
+            let Some(free_slot_index) = (0..DIMENSION.num_entries)
+                .find(|i| DIMENSION.rank(metadata, *i) == Dimension::uninit_rank())
+            else {
+                return ReplaceResult::Full;
+            };
+
+            // Build the updated entry before reserving the slot, otherwise the reserved slot would
+            // be left uninitialized if any of the user-provided functions panics.
+            let new_key = self.key_at(index).clone();
+            let mut new_val = self.value_at(index).clone();
+            updater(&mut new_val);
+
+            // Reserve the slot.
+            let mut prev_metadata =
+                DIMENSION.augment(metadata, free_slot_index, DIMENSION.removed_rank());
+            if let Err(actual) =
+                self.metadata
+                    .compare_exchange(metadata, prev_metadata, Acquire, Acquire)
+            {
+                metadata = actual;
+                continue;
+            }
+
+            self.write(free_slot_index, new_key, new_val);
+            loop {
+                let rank = DIMENSION.rank(prev_metadata, index);
+                let new_metadata = DIMENSION.augment(
+                    DIMENSION.augment(prev_metadata, free_slot_index, rank),
+                    index,
+                    DIMENSION.removed_rank(),
+                );
+                match self
+                    .metadata
+                    .compare_exchange(prev_metadata, new_metadata, AcqRel, Acquire)
+                {
+                    Ok(_) => return ReplaceResult::Success,
+                    Err(actual) => {
+                        if Dimension::frozen(actual)
+                            || Dimension::retired(actual)
+                            || DIMENSION.rank(actual, index) == DIMENSION.removed_rank()
+                        {
+                            // Release the reserved slot, and start over.
+                            drop(self.take(free_slot_index));
+                            metadata = self
+                                .metadata
+                                .fetch_and(!DIMENSION.rank_mask(free_slot_index), Acquire)
+                                & (!DIMENSION.rank_mask(free_slot_index));
+                            continue 'after_read_metadata;
+                        }
+                        prev_metadata = actual;
+                    }
+                }
+            }
+        }
+    }
+
     /// Returns a value associated with the key.
     #[inline]
     pub(super) fn search<Q>(&self, key: &Q) -> Option<&V>
@@ -1023,6 +1146,39 @@ mod test {
     }
 
     #[cfg_attr(miri, ignore)]
+    #[test]
+    fn replace() {
+        let leaf: Leaf<usize, usize> = Leaf::new();
+        assert!(matches!(leaf.insert(2, 2), InsertResult::Success));
+        assert!(matches!(leaf.insert(1, 1), InsertResult::Success));
+        assert!(matches!(leaf.insert(3, 3), InsertResult::Success));
+        assert_eq!(leaf.replace(&2, &mut |v| *v += 10), ReplaceResult::Success);
+        assert_eq!(leaf.replace(&4, &mut |v| *v += 10), ReplaceResult::NotFound);
+        assert_eq!(leaf.search(&2), Some(&12));
+        assert_eq!(
+            Scanner::new(&leaf)
+                .map(|(k, v)| (*k, *v))
+                .collect::<Vec<_>>(),
+            vec![(1, 1), (2, 12), (3, 3)]
+        );
+        assert!(matches!(
+            leaf.upsert(3, 0, Some(&mut |v: &mut usize| *v += 1)),
+            InsertResult::Updated
+        ));
+        assert!(matches!(
+            leaf.upsert(3, 0, None),
+            InsertResult::Duplicate(..)
+        ));
+        assert_eq!(leaf.search(&3), Some(&4));
+
+        while leaf.replace(&1, &mut |v| *v += 1) == ReplaceResult::Success {}
+        assert_eq!(leaf.replace(&1, &mut |_| ()), ReplaceResult::Full);
+        assert_eq!(leaf.search(&1), Some(&(DIMENSION.num_entries - 4)));
+        assert_eq!(leaf.remove_if(&2, &mut |v| *v == 12), RemoveResult::Success);
+        assert!(leaf.freeze());
+        assert_eq!(leaf.replace(&3, &mut |_| ()), ReplaceResult::Frozen);
+    }
+
     #[test]
     fn calculate_boundary() {
         let leaf: Leaf<usize, usize> = Leaf::new();
@@ -1159,7 +1315,8 @@ mod test {
                         InsertResult::Duplicate(_, _)
                         | InsertResult::Frozen(_, _)
                         | InsertResult::Retired(_, _)
-                        | InsertResult::Retry(_, _) => {
+                        | InsertResult::Retry(_, _)
+                        | InsertResult::Updated => {
                             unreachable!();
                         }
                         InsertResult::Full(k, v) => {
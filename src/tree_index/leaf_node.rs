@@ -60,6 +60,27 @@ where
         }
     }
 
+    /// Creates a new [`LeafNode`] from non-empty [`Leaf`] instances sorted by their keys.
+    ///
+    /// The last [`Leaf`] becomes the unbounded child.
+    pub(super) fn from_leaves(leaves: Vec<Shared<Leaf<K, V>>>) -> LeafNode<K, V> {
+        debug_assert!(!leaves.is_empty() && leaves.len() <= DIMENSION.num_entries + 1);
+        let mut leaf_node = Self::new();
+        let mut leaves = leaves.into_iter();
+        if let Some(unbounded) = leaves.next_back() {
+            leaf_node.unbounded_child = AtomicShared::from(unbounded);
+        }
+        for (index, leaf) in leaves.enumerate() {
+            if let Some(max_key) = leaf.max_key() {
+                let max_key = max_key.clone();
+                leaf_node
+                    .children
+                    .insert_unchecked(max_key, AtomicShared::from(leaf), index);
+            }
+        }
+        leaf_node
+    }
+
     /// Returns `true` if the [`LeafNode`] has retired.
     #[inline]
     pub(super) fn retired(&self, mo: Ordering) -> bool {
@@ -136,6 +157,17 @@ where
         }
     }
 
+    /// Returns a [`Scanner`] of the rightmost leaf.
+    ///
+    /// Returns `None` if the [`LeafNode`] is being split or merged.
+    #[inline]
+    pub(super) fn max_appr<'g>(&self, guard: &'g Guard) -> Option<Scanner<'g, K, V>> {
+        self.unbounded_child
+            .load(Acquire, guard)
+            .as_ref()
+            .map(Scanner::new)
+    }
+
     /// Returns a [`Scanner`] pointing to an entry that is close enough to the entry with the
     /// maximum key among those keys smaller than the given key.
     ///
@@ -184,16 +216,67 @@ where
         None
     }
 
-    /// Inserts a key-value pair.
+    /// Returns a [`Scanner`] of the leaf containing the entry with the maximum key among those
+    /// keys smaller than the given key.
+    ///
+    /// Returns `None` if all the keys in the [`LeafNode`] are equal to or greater than the given
+    /// key.
+    #[inline]
+    pub(super) fn max_less_leaf<'g, Q>(
+        &self,
+        key: &Q,
+        guard: &'g Guard,
+    ) -> Option<Scanner<'g, K, V>>
+    where
+        K: 'g + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let (child, metadata) = self.children.min_greater_equal(key);
+            let child_ptr = child.map_or_else(
+                || self.unbounded_child.load(Acquire, guard),
+                |(_, child)| child.load(Acquire, guard),
+            );
+            if let Some(child) = child_ptr.as_ref() {
+                if !self.children.validate(metadata) {
+                    // Data race resolution - see `LeafNode::search`.
+                    continue;
+                }
+                if Scanner::max_less(child, key).is_some() {
+                    return Some(Scanner::new(child));
+                }
+            } else if child.is_some() {
+                // It is not a hot loop - see `LeafNode::search`.
+                continue;
+            }
+
+            // The leaf covering the key does not have a smaller key; visit the preceding leaves.
+            let mut bound: &Q = key;
+            while let Some((child_key, child)) =
+                Scanner::max_less(&self.children, bound).and_then(|scanner| scanner.get())
+            {
+                if let Some(child) = child.load(Acquire, guard).as_ref() {
+                    if Scanner::max_less(child, key).is_some() {
+                        return Some(Scanner::new(child));
+                    }
+                }
+                bound = child_key.borrow();
+            }
+            return None;
+        }
+    }
+
+    /// Inserts a key-value pair, or updates the value if the key exists and `updater` is given.
     ///
     /// # Errors
     ///
     /// Returns an error if a retry is required.
     #[inline]
-    pub(super) fn insert<D: DeriveAsyncWait>(
+    pub(super) fn upsert<D: DeriveAsyncWait>(
         &self,
         mut key: K,
         mut val: V,
+        mut updater: Option<&mut (dyn FnMut(&mut V) + '_)>,
         async_wait: &mut D,
         guard: &Guard,
     ) -> Result<InsertResult<K, V>, (K, V)> {
@@ -204,11 +287,12 @@ where
                 if let Some(child_ref) = child_ptr.as_ref() {
                     if self.children.validate(metadata) {
                         // Data race resolution - see `LeafNode::search`.
-                        let insert_result = child_ref.insert(key, val);
+                        let insert_result = child_ref.upsert(key, val, updater.as_deref_mut());
                         match insert_result {
                             InsertResult::Success
                             | InsertResult::Duplicate(..)
-                            | InsertResult::Retry(..) => return Ok(insert_result),
+                            | InsertResult::Retry(..)
+                            | InsertResult::Updated => return Ok(insert_result),
                             InsertResult::Full(k, v) | InsertResult::Retired(k, v) => {
                                 let split_result = self.split_leaf(
                                     k,
@@ -260,11 +344,12 @@ where
                 if !self.children.validate(metadata) {
                     continue;
                 }
-                let insert_result = unbounded.insert(key, val);
+                let insert_result = unbounded.upsert(key, val, updater.as_deref_mut());
                 match insert_result {
                     InsertResult::Success
                     | InsertResult::Duplicate(..)
-                    | InsertResult::Retry(..) => return Ok(insert_result),
+                    | InsertResult::Retry(..)
+                    | InsertResult::Updated => return Ok(insert_result),
                     InsertResult::Full(k, v) | InsertResult::Retired(k, v) => {
                         let split_result = self.split_leaf(
                             k,
@@ -722,7 +807,8 @@ where
                 InsertResult::Success => (),
                 InsertResult::Duplicate(..)
                 | InsertResult::Frozen(..)
-                | InsertResult::Retry(..) => unreachable!(),
+                | InsertResult::Retry(..)
+                | InsertResult::Updated => unreachable!(),
                 InsertResult::Full(_, _) | InsertResult::Retired(_, _) => {
                     // Need to freeze the other leaf.
                     let frozen = high_key_leaf.freeze();
@@ -1006,18 +1092,20 @@ mod test {
         let guard = Guard::new();
         let leaf_node: LeafNode<String, String> = LeafNode::new();
         assert!(matches!(
-            leaf_node.insert(
+            leaf_node.upsert(
                 "MY GOODNESS!".to_owned(),
                 "OH MY GOD!!".to_owned(),
+                None,
                 &mut (),
                 &guard
             ),
             Ok(InsertResult::Success)
         ));
         assert!(matches!(
-            leaf_node.insert(
+            leaf_node.upsert(
                 "GOOD DAY".to_owned(),
                 "OH MY GOD!!".to_owned(),
+                None,
                 &mut (),
                 &guard
             ),
@@ -1050,7 +1138,7 @@ mod test {
             Ok(RemoveResult::Retired)
         ));
         assert!(matches!(
-            leaf_node.insert("HI".to_owned(), "HO".to_owned(), &mut (), &guard),
+            leaf_node.upsert("HI".to_owned(), "HO".to_owned(), None, &mut (), &guard),
             Ok(InsertResult::Retired(..))
         ));
     }
@@ -1061,9 +1149,9 @@ mod test {
         let guard = Guard::new();
         let leaf_node: LeafNode<usize, usize> = LeafNode::new();
         for k in 0..1024 {
-            let mut result = leaf_node.insert(k, k, &mut (), &guard);
+            let mut result = leaf_node.upsert(k, k, None, &mut (), &guard);
             if result.is_err() {
-                result = leaf_node.insert(k, k, &mut (), &guard);
+                result = leaf_node.upsert(k, k, None, &mut (), &guard);
             }
             match result.unwrap() {
                 InsertResult::Success => {
@@ -1072,7 +1160,8 @@ mod test {
                 }
                 InsertResult::Duplicate(..)
                 | InsertResult::Frozen(..)
-                | InsertResult::Retired(..) => unreachable!(),
+                | InsertResult::Retired(..)
+                | InsertResult::Updated => unreachable!(),
                 InsertResult::Full(_, _) => {
                     leaf_node.rollback(&guard);
                     for r in 0..(k - 1) {
@@ -1091,7 +1180,7 @@ mod test {
                     break;
                 }
                 InsertResult::Retry(..) => {
-                    assert!(leaf_node.insert(k, k, &mut (), &guard).is_ok());
+                    assert!(leaf_node.upsert(k, k, None, &mut (), &guard).is_ok());
                 }
             }
         }
@@ -1106,7 +1195,7 @@ mod test {
         for _ in 0..16 {
             let leaf_node = Shared::new(LeafNode::new());
             assert!(leaf_node
-                .insert(usize::MAX, usize::MAX, &mut (), &Guard::new())
+                .upsert(usize::MAX, usize::MAX, None, &mut (), &Guard::new())
                 .is_ok());
             let mut task_handles = Vec::with_capacity(num_tasks);
             for task_id in 0..num_tasks {
@@ -1119,10 +1208,11 @@ mod test {
                     let range = (task_id * workload_size)..((task_id + 1) * workload_size);
                     for id in range.clone() {
                         loop {
-                            if let Ok(r) = leaf_node_clone.insert(id, id, &mut (), &guard) {
+                            if let Ok(r) = leaf_node_clone.upsert(id, id, None, &mut (), &guard) {
                                 match r {
                                     InsertResult::Success => {
-                                        match leaf_node_clone.insert(id, id, &mut (), &guard) {
+                                        match leaf_node_clone.upsert(id, id, None, &mut (), &guard)
+                                        {
                                             Ok(InsertResult::Duplicate(..)) | Err(_) => (),
                                             _ => unreachable!(),
                                         }
@@ -1214,7 +1304,7 @@ mod test {
                         {
                             barrier_clone.wait().await;
                             let guard = Guard::new();
-                            match leaf_node_clone.insert(k, k, &mut (), &guard) {
+                            match leaf_node_clone.upsert(k, k, None, &mut (), &guard) {
                                 Ok(InsertResult::Success) => {
                                     assert!(!inserted_clone.swap(true, Relaxed));
                                 }
@@ -1231,7 +1321,7 @@ mod test {
                                 if i != k {
                                     if let Ok(
                                         InsertResult::Full(_, _) | InsertResult::Retired(_, _),
-                                    ) = leaf_node_clone.insert(i, i, &mut (), &guard)
+                                    ) = leaf_node_clone.upsert(i, i, None, &mut (), &guard)
                                     {
                                         leaf_node_clone.rollback(&guard);
                                     }
@@ -1,10 +1,11 @@
 use super::internal_node::{self, InternalNode};
-use super::leaf::{InsertResult, RemoveResult, Scanner};
+use super::leaf::{InsertResult, Leaf, RemoveResult, Scanner, DIMENSION};
 use super::leaf_node::{self, LeafNode};
 use crate::ebr::{AtomicShared, Guard, Shared, Tag};
 use crate::wait_queue::DeriveAsyncWait;
 use std::borrow::Borrow;
 use std::fmt::{self, Debug};
+use std::ptr;
 use std::sync::atomic::Ordering::{self, Acquire, Relaxed, Release};
 
 /// [`Node`] is either [`Self::Internal`] or [`Self::Leaf`].
@@ -37,6 +38,46 @@ where
         Self::Leaf(LeafNode::new())
     }
 
+    /// Builds a tree from non-empty [`Leaf`] instances sorted by their keys, and returns the root.
+    ///
+    /// Nodes are built from the bottom up, and children are evenly distributed to the nodes at
+    /// each level.
+    pub(super) fn build(leaves: Vec<Shared<Leaf<K, V>>>) -> Option<Shared<Node<K, V>>> {
+        let mut nodes: Vec<(K, Shared<Node<K, V>>)> = Self::distribute(leaves)
+            .into_iter()
+            .filter_map(|leaves| {
+                let max_key = leaves.last()?.max_key()?.clone();
+                Some((
+                    max_key,
+                    Shared::new(Self::Leaf(LeafNode::from_leaves(leaves))),
+                ))
+            })
+            .collect();
+        while nodes.len() > 1 {
+            nodes = Self::distribute(nodes)
+                .into_iter()
+                .filter_map(|children| {
+                    let max_key = children.last()?.0.clone();
+                    let internal_node = InternalNode::from_children(children);
+                    Some((max_key, Shared::new(Self::Internal(internal_node))))
+                })
+                .collect();
+        }
+        nodes.pop().map(|(_, root)| root)
+    }
+
+    /// Evenly distributes the items into groups that can be accommodated by a node.
+    fn distribute<T>(items: Vec<T>) -> Vec<Vec<T>> {
+        let num_groups = items.len().div_ceil(DIMENSION.num_entries + 1);
+        let mut items = items.into_iter();
+        (0..num_groups)
+            .map(|i| {
+                let group_size = items.len().div_ceil(num_groups - i);
+                items.by_ref().take(group_size).collect()
+            })
+            .collect()
+    }
+
     /// Returns the depth of the node.
     #[inline]
     pub(super) fn depth(&self, depth: usize, guard: &Guard) -> usize {
@@ -79,6 +120,17 @@ where
         }
     }
 
+    /// Returns a [`Scanner`] of the rightmost leaf.
+    ///
+    /// This method is not linearizable.
+    #[inline]
+    pub(super) fn max_appr<'g>(&self, guard: &'g Guard) -> Option<Scanner<'g, K, V>> {
+        match &self {
+            Self::Internal(internal_node) => internal_node.max_appr(guard),
+            Self::Leaf(leaf_node) => leaf_node.max_appr(guard),
+        }
+    }
+
     /// Returns a [`Scanner`] pointing to an entry that is close enough to the entry with the
     /// maximum key among those keys smaller than or equal to the given key.
     ///
@@ -95,6 +147,26 @@ where
         }
     }
 
+    /// Returns a [`Scanner`] of the leaf containing the entry with the maximum key among those
+    /// keys smaller than the given key.
+    ///
+    /// This method is not linearizable.
+    #[inline]
+    pub(super) fn max_less_leaf<'g, Q>(
+        &self,
+        key: &Q,
+        guard: &'g Guard,
+    ) -> Option<Scanner<'g, K, V>>
+    where
+        K: 'g + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &self {
+            Self::Internal(internal_node) => internal_node.max_less_leaf(key, guard),
+            Self::Leaf(leaf_node) => leaf_node.max_less_leaf(key, guard),
+        }
+    }
+
     /// Inserts a key-value pair.
     #[inline]
     pub(super) fn insert<D: DeriveAsyncWait>(
@@ -103,10 +175,25 @@ where
         val: V,
         async_wait: &mut D,
         guard: &Guard,
+    ) -> Result<InsertResult<K, V>, (K, V)> {
+        self.upsert(key, val, None, async_wait, guard)
+    }
+
+    /// Inserts a key-value pair, or updates the value if the key exists and `updater` is given.
+    #[inline]
+    pub(super) fn upsert<D: DeriveAsyncWait>(
+        &self,
+        key: K,
+        val: V,
+        updater: Option<&mut (dyn FnMut(&mut V) + '_)>,
+        async_wait: &mut D,
+        guard: &Guard,
     ) -> Result<InsertResult<K, V>, (K, V)> {
         match &self {
-            Self::Internal(internal_node) => internal_node.insert(key, val, async_wait, guard),
-            Self::Leaf(leaf_node) => leaf_node.insert(key, val, async_wait, guard),
+            Self::Internal(internal_node) => {
+                internal_node.upsert(key, val, updater, async_wait, guard)
+            }
+            Self::Leaf(leaf_node) => leaf_node.upsert(key, val, updater, async_wait, guard),
         }
     }
 
@@ -137,6 +224,7 @@ where
     /// Splits the current root node.
     #[inline]
     pub(super) fn split_root(
+        root_ref: &Node<K, V>,
         key: K,
         val: V,
         root: &AtomicShared<Node<K, V>>,
@@ -147,11 +235,17 @@ where
         let mut new_root = Shared::new(Node::new_internal_node());
         if let Some(Self::Internal(internal_node)) = unsafe { new_root.get_mut() } {
             internal_node.unbounded_child = root.clone(Relaxed, guard);
+            let root_ptr = internal_node.unbounded_child.load(Relaxed, guard);
+            if !ptr::eq(root_ptr.as_ptr(), root_ref) {
+                // The `TreeIndex` has been cleared, and the root is no longer reachable.
+                root_ref.rollback(guard);
+                return (key, val);
+            }
             let result = internal_node.split_node(
                 key,
                 val,
                 None,
-                root.load(Relaxed, guard),
+                root_ptr,
                 &internal_node.unbounded_child,
                 true,
                 &mut (),
@@ -163,13 +257,31 @@ where
 
             // Updates the pointer before unlocking the root.
             let new_root_ref = new_root.get_guarded_ptr(guard).as_ref();
-            if let Some(old_root) = root.swap((Some(new_root), Tag::None), Release).0 {
-                if let Some(Self::Internal(internal_node)) = new_root_ref.as_ref() {
-                    internal_node.finish_split(guard);
-                    old_root.commit(guard);
+            match root.compare_exchange(
+                root_ptr,
+                (Some(new_root), Tag::None),
+                Release,
+                Relaxed,
+                guard,
+            ) {
+                Ok((old_root, _)) => {
+                    if let Some(old_root) = old_root {
+                        if let Some(Self::Internal(internal_node)) = new_root_ref.as_ref() {
+                            internal_node.finish_split(guard);
+                            old_root.commit(guard);
+                        }
+                        let _: bool = old_root.release(guard);
+                    }
                 }
-                let _: bool = old_root.release(guard);
-            };
+                Err(_) => {
+                    // The `TreeIndex` has been cleared; the split is completed in the detached
+                    // tree so that no nodes are left locked.
+                    if let Some(Self::Internal(internal_node)) = new_root_ref.as_ref() {
+                        internal_node.finish_split(guard);
+                        root_ref.commit(guard);
+                    }
+                }
+            }
 
             (key, val)
         } else {
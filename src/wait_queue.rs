@@ -14,6 +14,9 @@ const ASYNC: usize = 1_usize;
 
 /// [`WaitQueue`] implements an unfair wait queue.
 ///
+/// Waiting entries are signaled in the order they were pushed, however a thread that has yet to
+/// be pushed into the [`WaitQueue`] may acquire the resource before the signaled ones.
+///
 /// The sole purpose of the data structure is to avoid busy-waiting. [`WaitQueue`] should always
 /// protected by [`ebr`](crate::ebr).
 #[derive(Debug, Default)]
@@ -47,6 +50,10 @@ impl WaitQueue {
             self.signal();
         }
 
+        #[cfg(feature = "tracing")]
+        if result.is_err() {
+            tracing::trace!("parking the thread until the wait queue is signaled");
+        }
         entry_mut.wait();
         result
     }
@@ -302,11 +309,114 @@ impl SyncWait {
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::sync::atomic::Ordering::Release;
+    use futures::task::{waker, ArcWake};
+    use std::ptr;
+    use std::sync::atomic::Ordering::{Acquire, Release};
     use std::sync::Arc;
     use std::sync::Barrier;
     use std::thread::yield_now;
 
+    struct WakeOrder {
+        id: usize,
+        order: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl ArcWake for WakeOrder {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.order.lock().unwrap().push(arc_self.id);
+        }
+    }
+
+    #[test]
+    fn wait_queue_fifo() {
+        let num_waiters = 16;
+        let wait_queue = WaitQueue::default();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut async_waits: Vec<Pin<Box<AsyncWait>>> = (0..num_waiters)
+            .map(|_| Box::pin(AsyncWait::default()))
+            .collect();
+        for (id, async_wait) in async_waits.iter_mut().enumerate() {
+            assert!(wait_queue
+                .push_async_entry(async_wait.as_mut().get_mut(), || Err::<(), ()>(()))
+                .is_err());
+            let waker = waker(Arc::new(WakeOrder {
+                id,
+                order: order.clone(),
+            }));
+            let mut cx = Context::from_waker(&waker);
+            assert!(async_wait.as_mut().poll(&mut cx).is_pending());
+        }
+
+        // The oldest waiter has to be woken up first.
+        wait_queue.signal();
+        assert_eq!(*order.lock().unwrap(), (0..num_waiters).collect::<Vec<_>>());
+        for async_wait in &mut async_waits {
+            let waker = futures::task::noop_waker();
+            let mut cx = Context::from_waker(&waker);
+            assert!(async_wait.as_mut().poll(&mut cx).is_ready());
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn wait_queue_fifo_contended() {
+        let num_tasks = 8;
+        let num_iters = 64;
+        let wait_queue = WaitQueue::default();
+        for _ in 0..num_iters {
+            let barrier = Barrier::new(num_tasks);
+            let order = Arc::new(Mutex::new(Vec::new()));
+            let mut async_waits: Vec<(usize, Pin<Box<AsyncWait>>)> = std::thread::scope(|s| {
+                let handles: Vec<_> = (0..num_tasks)
+                    .map(|id| {
+                        let (barrier, order, wait_queue) = (&barrier, &order, &wait_queue);
+                        s.spawn(move || {
+                            let mut async_wait = Box::pin(AsyncWait::default());
+                            barrier.wait();
+                            assert!(wait_queue
+                                .push_async_entry(async_wait.as_mut().get_mut(), || {
+                                    Err::<(), ()>(())
+                                })
+                                .is_err());
+                            let waker = waker(Arc::new(WakeOrder {
+                                id,
+                                order: order.clone(),
+                            }));
+                            let mut cx = Context::from_waker(&waker);
+                            assert!(async_wait.as_mut().poll(&mut cx).is_pending());
+                            (id, async_wait)
+                        })
+                    })
+                    .collect();
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            });
+
+            // Recover the order in which the entries were pushed from the linked list.
+            let mut pushed = Vec::with_capacity(num_tasks);
+            let mut current = wait_queue.wait_queue.load(Acquire);
+            while current != 0 {
+                let entry_ptr = (current & (!ASYNC)) as *const AsyncWait;
+                let (id, _) = async_waits
+                    .iter()
+                    .find(|(_, a)| ptr::eq(&**a, entry_ptr))
+                    .unwrap();
+                pushed.push(*id);
+                current = unsafe { (*entry_ptr).next };
+            }
+            pushed.reverse();
+            assert_eq!(pushed.len(), num_tasks);
+
+            // Entries pushed concurrently are still woken up in the order they were pushed.
+            wait_queue.signal();
+            assert_eq!(*order.lock().unwrap(), pushed);
+            for (_, async_wait) in &mut async_waits {
+                let waker = futures::task::noop_waker();
+                let mut cx = Context::from_waker(&waker);
+                assert!(async_wait.as_mut().poll(&mut cx).is_ready());
+            }
+        }
+    }
+
     #[cfg_attr(miri, ignore)]
     #[test]
     fn wait_queue_sync() {